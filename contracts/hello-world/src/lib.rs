@@ -1,8 +1,55 @@
 #![allow(non_snake_case)]
+#![allow(clippy::too_many_arguments)]
 #![no_std]
-use soroban_sdk::{contract, contracttype, contractimpl, log, Env, Symbol, String, Address, Vec, symbol_short};
+use soroban_sdk::{contract, contracterror, contracttype, contractimpl, log, panic_with_error, Env, Symbol, String, Address, Vec, Map, Bytes, BytesN, IntoVal, TryFromVal, Val, symbol_short};
+use soroban_sdk::token;
+use soroban_sdk::xdr::ToXdr;
 
-// Struct for property details
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
+mod test;
+
+// Error codes surfaced to clients
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    DuplicateRequest = 1,
+    ZeroAmount = 2,
+    SelfTransfer = 3,
+    ComplianceRejected = 4,
+    NotAuthorizedToView = 5,
+    PropertyPaused = 6,
+    PropertyNotFound = 7,
+    PropertyNotVerified = 8,
+    NoActivePhase = 9,
+    NotWhitelisted = 10,
+    PhaseAllocationExceeded = 11,
+    InsufficientShares = 12,
+    SharesLocked = 13,
+    RecipientNotOptedIn = 14,
+    NotOwner = 15,
+    AmountOverflow = 16,
+    PropertyIdCollision = 17,
+    AccountMigrated = 18,
+    OfferingFailed = 19,
+    InvalidCursor = 20,
+    SupplyExceeded = 21,
+    ContractPaused = 22,
+    PropertyDelisted = 23,
+    BelowMinimumPurchase = 24,
+    WalletCapExceeded = 25,
+    InvestorNotApproved = 26,
+    InvestorNotEligible = 27,
+    PropertyLiquidated = 28,
+}
+
+// Monetary amounts in stroops; wide enough that shares * price cannot overflow for real properties
+pub type Money = u128;
+
+// Struct for property details as stored before prices were widened to Money.
+// Still accepted by import_state; live records are PropertyV2.
 #[contracttype]
 #[derive(Clone)]
 pub struct Property {
@@ -15,6 +62,505 @@ pub struct Property {
     pub image_url: String,
     pub registration_time: u64,
     pub is_verified: bool,
+    pub is_private: bool,
+    pub tenant: Symbol,
+    pub is_paused: bool,
+    pub rounding_policy: RoundingPolicy,
+}
+
+// Struct for property details with the price per share in stroops
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyV2 {
+    pub property_id: u64,
+    pub title: String,
+    pub location: String,
+    pub description: String,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub image_url: String,
+    pub registration_time: u64,
+    pub is_verified: bool,
+    pub is_private: bool,
+    pub tenant: Symbol,
+    pub is_paused: bool,
+    pub rounding_policy: RoundingPolicy,
+}
+
+impl From<Property> for PropertyV2 {
+    fn from(property: Property) -> Self {
+        PropertyV2 {
+            property_id: property.property_id,
+            title: property.title,
+            location: property.location,
+            description: property.description,
+            total_shares: property.total_shares,
+            price_per_share: property.price_per_share as Money,
+            image_url: property.image_url,
+            registration_time: property.registration_time,
+            is_verified: property.is_verified,
+            is_private: property.is_private,
+            tenant: property.tenant,
+            is_paused: property.is_paused,
+            rounding_policy: property.rounding_policy,
+        }
+    }
+}
+
+// Enum for how pro-rata payouts (dividends, fee splits, royalties) are rounded
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    // Every part rounds down; the contract keeps the dust
+    Down,
+    // Every part rounds half to even, trimmed so the total is never exceeded
+    Bankers,
+    // Every part rounds down and the dust goes to the largest weight
+    RemainderToLargest,
+}
+
+// Enum for how new property IDs are assigned
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IdMode {
+    // IDs count up from 1 in registration order
+    Sequential,
+    // IDs are derived from the registrant, title, location and a deployment salt
+    ContentHash,
+}
+
+// Struct for the property ID assignment configuration
+#[contracttype]
+#[derive(Clone)]
+pub struct IdConfig {
+    pub mode: IdMode,
+    pub salt: BytesN<32>,
+}
+
+// Enum for non-transferable engagement badges, each awarded at most once per address
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    // First purchase of any property
+    FirstPurchase,
+    // Positions in five or more properties at once
+    FivePropertiesOwned,
+    // A position held for at least a year
+    DiamondHands,
+    // Bought within a day of the property's registration
+    EarlyBacker,
+}
+
+// Enum for delegated duties the admin can grant to other addresses
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    // Completes checklist requirements and verifies properties
+    Verifier,
+    // Registers verified properties on behalf of the platform
+    Registrar,
+    // Pauses and unpauses trading of properties
+    Pauser,
+    // Approves and revokes investors on the KYC allowlist
+    Compliance,
+}
+
+// Enum for the kind of real estate a property is, used to browse by category
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    Residential,
+    Commercial,
+    Industrial,
+    Land,
+    MixedUse,
+}
+
+// Enum for how much of a property is let out, as reported by its manager
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Occupancy {
+    Vacant,
+    Occupied,
+    PartiallyOccupied,
+}
+
+// Struct for the caller-supplied details of a new property
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyInput {
+    pub title: String,
+    pub location: String,
+    pub description: String,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub image_url: String,
+    pub founder_shares: u64,
+    pub founder_lockup_seconds: u64,
+    pub is_private: bool,
+    pub tenant: Option<Symbol>,
+    pub rounding_policy: RoundingPolicy,
+    pub unsold_shares_recipient: Option<Address>,
+    pub milestone_escrow: bool,
+    pub property_type: PropertyType,
+    pub royalty_bps: u32,
+    pub funding_deadline: Option<u64>,
+}
+
+// Struct for the compact property summary returned by list endpoints
+#[contracttype]
+#[derive(Clone)]
+pub struct ReducedProperty {
+    pub property_id: u64,
+    pub title: String,
+    pub location: String,
+    pub price_per_share: Money,
+    pub total_shares: u64,
+    pub shares_sold: u64,
+    pub is_verified: bool,
+    pub status: PropertyStatus,
+}
+
+// Struct for the criteria of a filtered property listing; unset criteria match everything.
+// An empty statuses list matches every status except Delisted and Archived.
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyFilter {
+    pub verified_only: bool,
+    pub statuses: Vec<PropertyStatus>,
+    pub categories: Vec<PropertyType>,
+    pub occupancies: Vec<Occupancy>,
+    pub min_price: Option<Money>,
+    pub max_price: Option<Money>,
+}
+
+// Struct for one page of a paginated list endpoint. Every list endpoint returns the same
+// shape; next_cursor is opaque, only valid for the endpoint that issued it, and None on the
+// last page. (Contract types cannot be generic, so each item type gets its own page struct.)
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyPage {
+    pub items: Vec<ReducedProperty>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for one page of an owner's positions, following the PropertyPage conventions
+#[contracttype]
+#[derive(Clone)]
+pub struct HoldingsPage {
+    pub items: Vec<OwnershipShare>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for one page of a property's holders and their share counts
+#[contracttype]
+#[derive(Clone)]
+pub struct HolderPage {
+    pub items: Vec<(Address, u64)>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for one page of a property's current holders
+#[contracttype]
+#[derive(Clone)]
+pub struct OwnerPage {
+    pub items: Vec<Address>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for one page of a property's exported ownership records
+#[contracttype]
+#[derive(Clone)]
+pub struct CapTablePage {
+    pub items: Vec<OwnershipShare>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for one page of a property's price history, newest trade first
+#[contracttype]
+#[derive(Clone)]
+pub struct PricePage {
+    pub items: Vec<PricePoint>,
+    pub next_cursor: Option<Bytes>,
+    pub total: Option<u64>,
+}
+
+// Struct for share-seconds accumulated during an income period, by a property or a single holder
+#[contracttype]
+#[derive(Clone)]
+pub struct ShareClock {
+    pub period: u32,
+    pub share_seconds: u128,
+    pub last_update: u64,
+}
+
+// Struct for a closed income period of a time-weighted property. rate is the income per
+// share-second scaled by DIVIDEND_PRECISION; cumulative is the scaled income one share earned
+// over all earlier periods.
+#[contracttype]
+#[derive(Clone)]
+pub struct IncomePeriod {
+    pub start: u64,
+    pub end: u64,
+    pub rate: u128,
+    pub cumulative: u128,
+}
+
+// Struct for a frozen point in a property's ownership history
+#[contracttype]
+#[derive(Clone)]
+pub struct Snapshot {
+    pub snapshot_id: u32,
+    pub ledger: u32,
+    pub timestamp: u64,
+    pub shares_sold: u64,
+}
+
+// Enum for what a governance proposal asks co-owners to decide
+#[contracttype]
+#[derive(Clone, PartialEq, Eq)]
+pub enum ProposalAction {
+    // Sell the whole property and wind it down
+    Liquidate,
+    // Replace the property manager
+    ChangeManager(Address),
+    // Approve spending up to an amount, e.g. on a renovation
+    ApproveBudget(Money),
+    // Change the primary-sale price per share
+    SetSalePrice(Money),
+    // Set the share of the property (in basis points) a buyout bidder must hold to squeeze out the rest
+    SetSqueezeOutThreshold(u32),
+    // Allow issuing this many new shares at this price per share
+    IssueShares(u64, Money),
+    // Split the property into the units whose XDR encoding hashes to this value
+    SplitProperty(BytesN<32>),
+    // Define the milestones gating the property's escrowed proceeds, as (description, percentage) pairs
+    SetMilestones(Vec<(String, u32)>),
+    // Release a milestone's share of the escrowed proceeds to the registrant
+    ReleaseMilestone(u32),
+    // A decision with no on-chain effect, described off-chain
+    Other,
+}
+
+// Enum for the lifecycle of a governance proposal. Active proposals become Succeeded or Defeated
+// when voting ends.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Active,
+    Succeeded,
+    Defeated,
+    Executed,
+    Cancelled,
+}
+
+// Struct for the share of a property's shares (in basis points) that must vote for a proposal
+// to count, and the share of the votes cast that must be in favour for it to pass
+#[contracttype]
+#[derive(Clone)]
+pub struct GovernanceRules {
+    pub quorum_bps: u32,
+    pub threshold_bps: u32,
+}
+
+// Struct for the sale of a whole property. Its shares are frozen and holders redeem them for a
+// pro-rata part of the proceeds held by the contract.
+#[contracttype]
+#[derive(Clone)]
+pub struct Liquidation {
+    pub proceeds: Money,
+    pub shares_outstanding: u64,
+    pub shares_redeemed: u64,
+    pub amount_paid: Money,
+    pub recorded_by: Address,
+    pub recorded_at: u64,
+}
+
+// Struct for an offer to buy every outstanding share of a property at one price. The escrow
+// holds what is left of the bidder's deposit for paying holders who accept.
+#[contracttype]
+#[derive(Clone)]
+pub struct BuyoutOffer {
+    pub bidder: Address,
+    pub price_per_share: Money,
+    pub escrow: Money,
+    pub shares_acquired: u64,
+    pub created_at: u64,
+}
+
+// Struct for the deed of a property held whole: its shares were burned when one owner
+// consolidated them, and the deed can be split back into the same number of shares
+#[contracttype]
+#[derive(Clone)]
+pub struct Deed {
+    pub property_id: u64,
+    pub owner: Address,
+    pub shares: u64,
+    pub minted_at: u64,
+}
+
+// Struct for a registrant or manager's offer to buy back and burn up to a number of a property's
+// shares at one price. The escrow holds what is left of the sponsor's deposit.
+#[contracttype]
+#[derive(Clone)]
+pub struct Buyback {
+    pub sponsor: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub shares_tendered: u64,
+    pub escrow: Money,
+    pub created_at: u64,
+}
+
+// Struct for a governance proposal; description_hash points at the full text kept off-chain.
+// Votes are weighted by balances at the snapshot taken when the proposal was created, and the
+// property's governance rules are fixed at creation too.
+#[contracttype]
+#[derive(Clone)]
+pub struct Proposal {
+    pub proposal_id: u64,
+    pub property_id: u64,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub description_hash: BytesN<32>,
+    pub snapshot_id: u32,
+    pub quorum_bps: u32,
+    pub threshold_bps: u32,
+    pub created_at: u64,
+    pub voting_ends: u64,
+    pub status: ProposalStatus,
+    pub votes_for: u64,
+    pub votes_against: u64,
+}
+
+// Struct for the current value of one position in an owner's portfolio
+#[contracttype]
+#[derive(Clone)]
+pub struct PortfolioEntry {
+    pub property_id: u64,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub value: Money,
+}
+
+// Struct for an owner's positions valued at current share prices
+#[contracttype]
+#[derive(Clone)]
+pub struct Portfolio {
+    pub entries: Vec<PortfolioEntry>,
+    pub total_value: Money,
+}
+
+// Struct for the data of a property_registered event
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyRegisteredEvent {
+    pub registrant: Address,
+    pub title: String,
+    pub location: String,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub founder_shares: u64,
+}
+
+// Struct for the data of a property_verified event
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyVerifiedEvent {
+    pub verifier: Address,
+    pub verified_at: u64,
+}
+
+// Struct for corrections to a property's descriptive fields; unset fields are left unchanged
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyUpdate {
+    pub title: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+}
+
+// Struct for the data of a property_updated event
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyUpdatedEvent {
+    pub updated_by: Address,
+    pub update: PropertyUpdate,
+}
+
+// Struct for one unit a property is split into. Without allocations, the parent's holders
+// receive the unit's shares pro-rata to their holdings and the rest stay on sale.
+#[contracttype]
+#[derive(Clone)]
+pub struct UnitSpec {
+    pub title: String,
+    pub description: String,
+    pub image_url: String,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub allocations: Map<Address, u64>,
+}
+
+// Struct for the property several constituents are merged into. appraised_values lists each
+// constituent's appraised value in the order given, and weights how the new shares are shared.
+#[contracttype]
+#[derive(Clone)]
+pub struct MergeSpec {
+    pub title: String,
+    pub location: String,
+    pub description: String,
+    pub image_url: String,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub property_type: PropertyType,
+    pub appraised_values: Vec<Money>,
+}
+
+// Struct for new shares co-owners approved issuing, waiting for the registrant to issue them
+#[contracttype]
+#[derive(Clone)]
+pub struct ShareIssuance {
+    pub proposal_id: u64,
+    pub extra_shares: u64,
+    pub price_per_share: Money,
+}
+
+// Struct for the data of a dilution event
+#[contracttype]
+#[derive(Clone)]
+pub struct DilutionEvent {
+    pub extra_shares: u64,
+    pub price_per_share: Money,
+    pub previous_total_shares: u64,
+    pub total_shares: u64,
+}
+
+// Struct for the data of a shares_purchased event
+#[contracttype]
+#[derive(Clone)]
+pub struct SharesPurchasedEvent {
+    pub buyer: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub total_price: Money,
+    pub resulting_balance: u64,
+}
+
+// Struct for the data of a shares_transferred event
+#[contracttype]
+#[derive(Clone)]
+pub struct SharesTransferredEvent {
+    pub from: Address,
+    pub to: Address,
+    pub shares: u64,
+    pub from_balance: u64,
+    pub to_balance: u64,
 }
 
 // Struct for tracking ownership shares
@@ -25,6 +571,7 @@ pub struct OwnershipShare {
     pub owner: Address,
     pub shares: u64,
     pub purchase_time: u64,
+    pub last_updated: u64,
 }
 
 // Struct for tracking property statistics
@@ -37,329 +584,9326 @@ pub struct PropertyStats {
     pub total_transactions: u64,
 }
 
-// Enum for mapping property IDs
-#[contracttype] 
-pub enum PropertyRegistry { 
-    Property(u64)
+// Struct for a time-boxed sale phase (private round, presale, public sale)
+#[contracttype]
+#[derive(Clone)]
+pub struct SalePhase {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub price_per_share: Money,
+    pub max_shares: u64,
+    pub requires_whitelist: bool,
 }
 
-// Enum for mapping ownership records
-#[contracttype] 
-pub enum OwnershipRegistry { 
-    Ownership(u64, Address)
+// Struct for an operating expense a property's manager recorded; doc_hash points at the invoice
+// or receipt kept off-chain
+#[contracttype]
+#[derive(Clone)]
+pub struct Expense {
+    pub expense_id: u32,
+    pub category: Symbol,
+    pub amount: Money,
+    pub doc_hash: BytesN<32>,
+    pub recorded_by: Address,
+    pub recorded_at: u64,
 }
 
-// Enum for user's owned properties
-#[contracttype] 
-pub enum UserProperties {
-    Properties(Address)
+// Struct for spending co-owners approved through an ApproveBudget proposal, such as an annual
+// budget or major works. The manager draws it down from the property's reserve.
+#[contracttype]
+#[derive(Clone)]
+pub struct Budget {
+    pub proposal_id: u64,
+    pub property_id: u64,
+    pub approved: Money,
+    pub drawn: Money,
+    pub approved_at: u64,
 }
 
-// Constants for contract storage
-const PROPERTY_COUNTER: Symbol = symbol_short!("PROP_CTR");
-const PROPERTY_STATS: Symbol = symbol_short!("PROP_STAT");
-const CONTRACT_ADMIN: Symbol = symbol_short!("ADMIN");
+// Enum for the lifecycle of a lease. A pending lease becomes active when it is signed, and
+// either expires at its end date unless it was terminated first.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LeaseStatus {
+    Pending,
+    Active,
+    Terminated,
+    Expired,
+}
 
-#[contract]
-pub struct RealEstateNFT;
+// Struct for a lease of a property. The lessee is identified by address when they sign and pay
+// on-chain, otherwise by an off-chain reference; rent is due at the start of each period from the
+// start date. deposit_held is the part of the security deposit the contract still holds,
+// rent_paid the rent paid on-chain so far. Rent only falls due on a lease that was activated.
+#[contracttype]
+#[derive(Clone)]
+pub struct Lease {
+    pub lease_id: u64,
+    pub property_id: u64,
+    pub lessee: Option<Address>,
+    pub lessee_ref: Option<String>,
+    pub rent: Money,
+    pub period_seconds: u64,
+    pub deposit: Money,
+    pub deposit_held: Money,
+    pub rent_paid: Money,
+    pub doc_hash: BytesN<32>,
+    pub start: u64,
+    pub end: u64,
+    pub status: LeaseStatus,
+    pub created_at: u64,
+    pub activated_at: Option<u64>,
+    pub ended_at: Option<u64>,
+}
 
-#[contractimpl]
-impl RealEstateNFT {
-    // Initialize the contract with an admin address
-    pub fn initialize(env: Env, admin: Address) {
-        // Ensure contract is only initialized once
-        if env.storage().instance().has(&CONTRACT_ADMIN) {
-            panic!("Contract already initialized");
-        }
-        
-        // Store admin address
-        env.storage().instance().set(&CONTRACT_ADMIN, &admin);
-        
-        // Initialize property stats
-        let stats = PropertyStats {
-            total_properties: 0,
-            verified_properties: 0,
-            total_owners: 0,
-            total_transactions: 0,
-        };
-        
-        env.storage().instance().set(&PROPERTY_STATS, &stats);
-        env.storage().instance().set(&PROPERTY_COUNTER, &0u64);
-        
-        env.storage().instance().extend_ttl(10000, 10000);
-        log!(&env, "RealEstateNFT contract initialized with admin: {}", admin);
-    }
-    
-    // Function to register a new property
-    pub fn register_property(
-        env: Env, 
-        title: String, 
-        location: String, 
-        description: String, 
-        total_shares: u64, 
-        price_per_share: u64,
-        image_url: String
-    ) -> u64 {
-        // Get next property ID
-        let mut property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
-        property_counter += 1;
+// Struct for where a lease stands against its rent schedule
+#[contracttype]
+#[derive(Clone)]
+pub struct RentStatus {
+    pub lease_id: u64,
+    pub periods_due: u64,
+    pub rent_due: Money,
+    pub rent_paid: Money,
+    pub arrears: Money,
+    pub next_due_at: Option<u64>,
+}
+
+// Struct for a property's reported occupancy. vacant_since is when the current vacancy began, and
+// vacant_seconds the total time the property has spent vacant since occupancy was first reported.
+#[contracttype]
+#[derive(Clone)]
+pub struct OccupancyRecord {
+    pub occupancy: Occupancy,
+    pub vacant_since: Option<u64>,
+    pub vacant_seconds: u64,
+    pub updated_by: Address,
+    pub updated_at: u64,
+}
+
+// Struct for the terms of a new lease
+#[contracttype]
+#[derive(Clone)]
+pub struct LeaseTerms {
+    pub lessee: Option<Address>,
+    pub lessee_ref: Option<String>,
+    pub rent: Money,
+    pub period_seconds: u64,
+    pub deposit: Money,
+    pub doc_hash: BytesN<32>,
+    pub start: u64,
+    pub end: u64,
+}
+
+// Enum for where an insurance policy stands against its expiry date
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PolicyStatus {
+    Active,
+    ExpiringSoon,
+    Expired,
+}
+
+// Struct for an insurance policy on a property. The insurer is an off-chain reference and the
+// policy document is kept off-chain under doc_hash; status is resolved against the current time.
+#[contracttype]
+#[derive(Clone)]
+pub struct InsurancePolicy {
+    pub policy_id: u32,
+    pub insurer_ref: String,
+    pub coverage: Money,
+    pub premium: Money,
+    pub expires_at: u64,
+    pub doc_hash: BytesN<32>,
+    pub status: PolicyStatus,
+    pub recorded_by: Address,
+    pub updated_at: u64,
+}
+
+// Struct for the terms of an insurance policy as recorded or renewed by the manager
+#[contracttype]
+#[derive(Clone)]
+pub struct PolicyTerms {
+    pub insurer_ref: String,
+    pub coverage: Money,
+    pub premium: Money,
+    pub expires_at: u64,
+    pub doc_hash: BytesN<32>,
+}
+
+// Enum for the progress of an insurance claim
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Filed,
+    Approved,
+    Rejected,
+    Paid,
+}
+
+// Struct for an insurance claim against one of a property's policies. Payouts are credited to the
+// property's reserve as they arrive, up to the approved amount.
+#[contracttype]
+#[derive(Clone)]
+pub struct InsuranceClaim {
+    pub claim_id: u32,
+    pub policy_id: u32,
+    pub filed_amount: Money,
+    pub approved_amount: Money,
+    pub paid_amount: Money,
+    pub doc_hash: BytesN<32>,
+    pub status: ClaimStatus,
+    pub filed_at: u64,
+}
+
+// Struct for what a property's manager is paid out of each income deposit: a flat amount plus
+// a percentage in basis points, never more than the deposit
+#[contracttype]
+#[derive(Clone)]
+pub struct ManagementFee {
+    pub flat_fee: Money,
+    pub fee_bps: u32,
+}
+
+// Struct for a property's income, expenses and management fees in one calendar month,
+// identified as YYYYMM
+#[contracttype]
+#[derive(Clone)]
+pub struct PeriodStatement {
+    pub period: u32,
+    pub income: Money,
+    pub expenses: Money,
+    pub management_fees: Money,
+    pub net_income: i128,
+}
+
+// Struct for a single day of traded volume
+#[contracttype]
+#[derive(Clone)]
+pub struct DailyVolume {
+    pub shares: u64,
+    pub value: Money,
+}
+
+// Enum for an account's policy on receiving transferred shares
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReceivePolicy {
+    Open,
+    RequireOptIn,
+}
+
+// Struct for the outcome of a call submitted with an idempotency key
+#[contracttype]
+#[derive(Clone)]
+pub struct IdempotencyRecord {
+    pub operation: Symbol,
+    pub property_id: u64,
+    pub shares: u64,
+    pub counterparty: Option<Address>,
+    pub resulting_balance: u64,
+    pub timestamp: u64,
+}
+
+// Struct for the shares a registrant retains at registration
+#[contracttype]
+#[derive(Clone)]
+pub struct FounderAllocation {
+    pub registrant: Address,
+    pub shares: u64,
+    pub unlock_time: u64,
+}
+
+// Struct for shares granted out of the unsold supply that unlock linearly over duration_seconds
+// from start, with nothing unlocked before the cliff
+#[contracttype]
+#[derive(Clone)]
+pub struct VestingSchedule {
+    pub shares: u64,
+    pub start: u64,
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+}
+
+// Struct for an additional class of a property's shares with its own supply, price and rights.
+// Class 0 is the property's ordinary shares, described by the property record itself.
+#[contracttype]
+#[derive(Clone)]
+pub struct ShareClass {
+    pub class_id: u32,
+    pub name: Symbol,
+    pub total_shares: u64,
+    pub price_per_share: Money,
+    pub voting: bool,
+}
+
+// Struct for unsold shares credited to a community treasury when an offering closes
+#[contracttype]
+#[derive(Clone)]
+pub struct TreasuryAllocation {
+    pub recipient: Address,
+    pub shares: u64,
+    pub allocated_at: u64,
+}
+
+// Struct for a completed verification requirement
+#[contracttype]
+#[derive(Clone)]
+pub struct RequirementCompletion {
+    pub completed_by: Address,
+    pub completed_at: u64,
+    pub evidence_uri: String,
+}
+
+// Struct for one checklist item and whether it has been completed for a property
+#[contracttype]
+#[derive(Clone)]
+pub struct RequirementStatus {
+    pub code: Symbol,
+    pub is_completed: bool,
+    pub completed_by: Option<Address>,
+    pub completed_at: u64,
+    pub evidence_uri: Option<String>,
+}
+
+// Enum for the lifecycle stage of a property listing. Delisted and Archived are set explicitly;
+// the others follow from verification and sales.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PropertyStatus {
+    Draft,
+    Verified,
+    Active,
+    Delisted,
+    Sold,
+    Archived,
+}
+
+// Enum for negative outcomes of a verification review
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReviewStatus {
+    // Turned down before verification; the property can never be verified
+    Rejected,
+    // Verification withdrawn; the property may be verified again once the issue is resolved
+    Revoked,
+}
+
+// Struct for the latest negative review of a property and its reason code
+#[contracttype]
+#[derive(Clone)]
+pub struct PropertyReview {
+    pub status: ReviewStatus,
+    pub reason: Symbol,
+    pub reviewer: Address,
+    pub reviewed_at: u64,
+}
+
+// Struct for a milestone gating release of escrowed sale proceeds to the registrant
+#[contracttype]
+#[derive(Clone)]
+pub struct Milestone {
+    pub description: String,
+    pub percentage: u32,
+    pub is_released: bool,
+    pub released_amount: Money,
+    pub released_at: u64,
+}
+
+// Struct for the keeper reward paid for one maintenance function
+#[contracttype]
+#[derive(Clone)]
+pub struct KeeperRate {
+    pub per_item: Money,
+    pub value_bps: u32,
+}
+
+// Struct for the external compliance contract consulted on large transfers
+#[contracttype]
+#[derive(Clone)]
+pub struct ComplianceHook {
+    pub hook: Address,
+    pub threshold_shares: u64,
+}
+
+// Struct for a property's timeshare redemption terms
+#[contracttype]
+#[derive(Clone)]
+pub struct RedemptionConfig {
+    pub shares_per_night: u64,
+    pub max_nights_per_year: u32,
+}
+
+// Struct for a usage booking redeemed against held shares
+#[contracttype]
+#[derive(Clone)]
+pub struct Booking {
+    pub booking_id: u64,
+    pub property_id: u64,
+    pub owner: Address,
+    pub start_day: u64,
+    pub nights: u32,
+    pub shares: u64,
+    pub booked_at: u64,
+}
+
+// Struct for tracking the value dimension of the platform
+#[contracttype]
+#[derive(Clone)]
+pub struct ValueStats {
+    pub total_market_cap: u128,
+    pub verified_market_cap: u128,
+}
+
+// Struct for a price locked for a short time ahead of a purchase
+#[contracttype]
+#[derive(Clone)]
+pub struct PriceQuote {
+    pub quote_id: u64,
+    pub property_id: u64,
+    pub buyer: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub total_price: Money,
+    pub expires_at: u64,
+}
+
+// Struct for the amounts a buyer would pay for shares at current prices and fees
+#[contracttype]
+#[derive(Clone)]
+pub struct PurchaseQuote {
+    pub property_id: u64,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub base_cost: Money,
+    pub platform_fee: Money,
+    pub total_payable: Money,
+    pub available_shares: u64,
+}
+
+// Struct for a holder's inactivity-triggered inheritance plan
+#[contracttype]
+#[derive(Clone)]
+pub struct InheritancePlan {
+    pub beneficiary: Address,
+    pub inactivity_seconds: u64,
+    pub last_active: u64,
+}
+
+// Struct for a basket of properties sold as a single unit
+#[contracttype]
+#[derive(Clone)]
+pub struct Bundle {
+    pub bundle_id: u64,
+    pub creator: Address,
+    pub components: Vec<(u64, u64)>,
+    pub bundle_price: Money,
+    pub created_at: u64,
+}
+
+// Struct for an executed trade in a property's price history
+#[contracttype]
+#[derive(Clone)]
+pub struct PricePoint {
+    pub price_per_share: Money,
+    pub shares: u64,
+    pub timestamp: u64,
+    pub primary: bool,
+}
+
+// Struct for the royalty taken from secondary sales of a property's shares
+#[contracttype]
+#[derive(Clone)]
+pub struct Royalty {
+    pub recipient: Address,
+    pub bps: u32,
+}
+
+// Struct for a holder's standing offer to sell shares on the secondary market.
+// shares is what is still unsold; the seller's listed shares cannot be transferred elsewhere.
+#[contracttype]
+#[derive(Clone)]
+pub struct SellOrder {
+    pub order_id: u64,
+    pub property_id: u64,
+    pub seller: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub created_at: u64,
+}
+
+// Struct for a standing offer to buy shares, with the payment held by the contract.
+// shares is what is still unfilled. Order and offer IDs come from one sequence.
+#[contracttype]
+#[derive(Clone)]
+pub struct BuyOffer {
+    pub offer_id: u64,
+    pub property_id: u64,
+    pub buyer: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+// Struct for a proposed exchange of the proposer's shares of one property for the counterparty's
+// shares of another. The proposer's optional token sweetener is held by the contract until the
+// swap is accepted or closed.
+#[contracttype]
+#[derive(Clone)]
+pub struct SwapProposal {
+    pub swap_id: u64,
+    pub proposer: Address,
+    pub property_give: u64,
+    pub shares_give: u64,
+    pub counterparty: Address,
+    pub property_want: u64,
+    pub shares_want: u64,
+    pub sweetener: Money,
+    pub expires_at: u64,
+}
+
+// Struct for shares the sender has escrowed for a recipient, who can claim them from
+// release_time. The shares stay in the sender's position, locked like listed shares, until the
+// transfer is claimed or cancelled.
+#[contracttype]
+#[derive(Clone)]
+pub struct ScheduledTransfer {
+    pub transfer_id: u64,
+    pub property_id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub shares: u64,
+    pub release_time: u64,
+}
+
+// Struct for the data of a trade event from matching an offer against a sell order
+#[contracttype]
+#[derive(Clone)]
+pub struct TradeEvent {
+    pub offer_id: u64,
+    pub order_id: u64,
+    pub buyer: Address,
+    pub seller: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+}
+
+// Struct for a primary sale priced by a declining-price (Dutch) auction
+#[contracttype]
+#[derive(Clone)]
+pub struct DutchAuction {
+    pub start_price: Money,
+    pub floor_price: Money,
+    pub start_time: u64,
+    pub duration: u64,
+}
+
+// Struct for an ascending-bid (English) auction of a block of shares. The seller's shares are
+// listed for its duration and the highest bid is held by the contract.
+#[contracttype]
+#[derive(Clone)]
+pub struct EnglishAuction {
+    pub auction_id: u64,
+    pub property_id: u64,
+    pub seller: Address,
+    pub shares: u64,
+    pub reserve_price: Money,
+    pub end_time: u64,
+    pub highest_bidder: Option<Address>,
+    pub highest_bid: Money,
+}
+
+// Struct for an investor's entry on the KYC allowlist
+#[contracttype]
+#[derive(Clone)]
+pub struct InvestorApproval {
+    pub tier: u32,
+    pub jurisdiction: Symbol,
+    pub approved_by: Address,
+    pub approved_at: u64,
+}
+
+// Struct for who may acquire a property's shares: approved investors of at least min_tier,
+// from one of the listed jurisdictions (any jurisdiction when empty)
+#[contracttype]
+#[derive(Clone)]
+pub struct EligibilityRules {
+    pub min_tier: u32,
+    pub jurisdictions: Vec<Symbol>,
+}
+
+// Struct for shares bought in one purchase, kept while the property's lockup holds them
+#[contracttype]
+#[derive(Clone)]
+pub struct ShareLot {
+    pub shares: u64,
+    pub acquired_at: u64,
+}
+
+// Struct for a property's offering rules on purchase size; zero means no limit
+#[contracttype]
+#[derive(Clone)]
+pub struct PurchaseLimits {
+    pub min_shares_per_purchase: u64,
+    pub max_shares_per_wallet: u64,
+}
+
+// Struct for a purchase paid in installments. The shares are reserved out of the unsold supply
+// and only credited to the buyer with the final payment; the payments are held by the contract.
+#[contracttype]
+#[derive(Clone)]
+pub struct InstallmentPlan {
+    pub plan_id: u64,
+    pub property_id: u64,
+    pub buyer: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub platform_fee: Money,
+    pub total_payable: Money,
+    pub phase_index: Option<u32>,
+    pub num_payments: u32,
+    pub payments_made: u32,
+    pub amount_paid: Money,
+    pub next_due: u64,
+}
+
+// Struct for an income deposit that can be pushed to holders who have not claimed it.
+// next_holder is where the push sweep resumes in the property's holder list; the record
+// is removed once a sweep has reached every holder.
+#[contracttype]
+#[derive(Clone)]
+pub struct Distribution {
+    pub distribution_id: u32,
+    pub amount: Money,
+    pub deposited_at: u64,
+    pub next_holder: u32,
+}
+
+// Struct for a requested purchase, checked before anything is written
+#[contracttype]
+#[derive(Clone)]
+pub struct PurchaseIntent {
+    pub property_id: u64,
+    pub buyer: Address,
+    pub shares: u64,
+    pub locked_price: Option<Money>,
+}
+
+// Struct for the fully computed outcome of a validated purchase
+#[contracttype]
+#[derive(Clone)]
+pub struct PurchasePlan {
+    pub property_id: u64,
+    pub buyer: Address,
+    pub shares: u64,
+    pub price_per_share: Money,
+    pub total_price: Money,
+    pub platform_fee: Money,
+    pub phase_index: Option<u32>,
+    pub phase_sold: u64,
+    pub ownership: OwnershipShare,
+    pub is_new_owner: bool,
+    pub shares_sold: u64,
+}
+
+// Enum for an operation replayed through record_rejection
+#[contracttype]
+#[derive(Clone)]
+pub enum RejectionCheck {
+    Purchase(PurchaseIntent),
+    // (property_id, from, to, shares)
+    Transfer(u64, Address, Address, u64),
+}
+
+// Struct for the fully computed outcome of a validated transfer
+struct TransferPlan {
+    property_id: u64,
+    from_ownership: OwnershipShare,
+    to_ownership: OwnershipShare,
+    is_new_owner: bool,
+}
+
+// Enum for mapping property IDs
+#[contracttype] 
+pub enum PropertyRegistry { 
+    Property(u64),
+    Registrant(u64),
+    IdAt(u64),
+    PropertyV2(u64),
+    Withdrawn(u64),
+    Type(u64),
+    Manager(u64),
+    Occupancy(u64),
+}
+
+// Enum for the per-category property index
+#[contracttype]
+pub enum CategoryRegistry {
+    Properties(PropertyType),
+}
+
+// Enum for mapping ownership records
+#[contracttype] 
+pub enum OwnershipRegistry { 
+    Ownership(u64, Address)
+}
+
+// Enum for user's owned properties
+#[contracttype] 
+pub enum UserProperties {
+    Properties(Address)
+}
+
+// Enum for sale phase configuration and per-phase allocation tracking
+#[contracttype]
+pub enum SalePhaseRegistry {
+    Phases(u64),
+    PhaseSold(u64, u32),
+    Whitelisted(u64, Address),
+}
+
+// Enum for daily volume buckets (day = timestamp / 86400)
+#[contracttype]
+pub enum VolumeRegistry {
+    Property(u64, u64),
+    Global(u64),
+    PropertyOldest(u64),
+    GlobalOldest,
+}
+
+// Enum for receive policies and per-property opt-ins
+#[contracttype]
+pub enum ReceiveRegistry {
+    Policy(Address),
+    OptIn(Address, u64),
+}
+
+// Enum for idempotency keys, scoped per caller (temporary storage)
+#[contracttype]
+pub enum IdempotencyRegistry {
+    Request(Address, BytesN<32>),
+}
+
+// Enum for share supply accounting and founder allocations
+#[contracttype]
+pub enum SupplyRegistry {
+    SharesSold(u64),
+    Founder(u64),
+    UnsoldRecipient(u64),
+    Treasury(u64),
+    OfferingClosed(u64),
+    Holders(u64),
+    Limits(u64),
+    Issuance(u64),
+}
+
+// Enum for timeshare redemption terms, bookings and per-year usage
+#[contracttype]
+pub enum RedemptionRegistry {
+    Config(u64),
+    Booking(u64),
+    PropertyBookings(u64),
+    NightsUsed(u64, Address, u32),
+}
+
+// Enum for locked price quotes (temporary storage)
+#[contracttype]
+pub enum QuoteRegistry {
+    Quote(u64),
+}
+
+// Enum for the per-tenant property index
+#[contracttype]
+pub enum TenantRegistry {
+    Properties(Symbol),
+}
+
+// Enum for per-owner holdings caches
+#[contracttype]
+pub enum HoldingsRegistry {
+    TotalShares(Address),
+}
+
+// Enum for completed verification requirements
+#[contracttype]
+pub enum VerificationRegistry {
+    Completed(u64, Symbol),
+    Review(u64),
+    Checklist(PropertyType),
+}
+
+// Enum for milestone escrow of primary-sale proceeds
+#[contracttype]
+pub enum EscrowRegistry {
+    Enabled(u64),
+    Milestones(u64),
+    Proceeds(u64),
+    Released(u64),
+    Refunded(u64),
+    Failed(u64),
+    Paid(u64, Address),
+    RefundClaimed(u64, Address),
+}
+
+// Enum for crowdfunded offerings: the deadline to sell every share by, the payments held until
+// then and whether the goal was reached
+#[contracttype]
+pub enum FundingRegistry {
+    Deadline(u64),
+    Raised(u64),
+    Funded(u64),
+    Paid(u64, Address),
+}
+
+// Enum for income distribution: the per-property income accumulated per share (scaled by
+// DIVIDEND_PRECISION), each holder's accumulator checkpoint and income credited to them so far.
+// Time-weighted properties instead keep share-second clocks for the property and each holder,
+// and one record per closed income period.
+#[contracttype]
+pub enum DividendRegistry {
+    AccPerShare(u64),
+    Checkpoint(u64, Address),
+    Pending(u64, Address),
+    TimeWeighted(u64),
+    Clock(u64),
+    HolderClock(u64, Address),
+    Period(u64, u32),
+}
+
+// Enum for ownership snapshots: the number taken per property, each snapshot's details, and each
+// holder's balance history as (first snapshot ID the balance applies to, balance) pairs
+#[contracttype]
+pub enum SnapshotRegistry {
+    Count(u64),
+    Snapshot(u64, u32),
+    History(u64, Address),
+}
+
+// Enum for keeper reward rates, balances and daily payouts
+#[contracttype]
+pub enum KeeperRegistry {
+    Rate(Symbol),
+    Balance(Address),
+    PaidOnDay(u64),
+}
+
+// Enum for free-form property metadata and its size accounting
+#[contracttype]
+pub enum MetadataRegistry {
+    Value(u64, Symbol),
+    Keys(u64),
+    TotalSize(u64),
+}
+
+// Enum for accounts moved to a new address after a key compromise
+#[contracttype]
+pub enum MigrationRegistry {
+    MigratedTo(Address),
+}
+
+// Enum for awarded badges
+#[contracttype]
+pub enum BadgeRegistry {
+    Badges(Address),
+}
+
+// Enum for inheritance plans
+#[contracttype]
+pub enum InheritanceRegistry {
+    Plan(Address),
+}
+
+// Enum for role memberships
+#[contracttype]
+pub enum RoleRegistry {
+    Member(Role, Address),
+}
+
+// Enum for post-purchase lockups: each property's lockup period and each holder's purchased
+// lots that may still be inside it
+#[contracttype]
+pub enum LockupRegistry {
+    Period(u64),
+    Lots(u64, Address),
+}
+
+// Enum for additional share classes: each property's classes, shares sold per class, everyone
+// who held each class, holder balances, and per-class income accumulators, checkpoints and
+// pending income
+#[contracttype]
+pub enum ClassRegistry {
+    Classes(u64),
+    Sold(u64, u32),
+    Holders(u64, u32),
+    Balance(u64, u32, Address),
+    AccPerShare(u64, u32),
+    Checkpoint(u64, u32, Address),
+    Pending(u64, u32, Address),
+}
+
+// Enum for the shares a holder lets a spender move on their behalf, and operators allowed to
+// move all of a holder's shares
+#[contracttype]
+pub enum AllowanceRegistry {
+    Allowance(u64, Address, Address),
+    Operator(Address, Address),
+}
+
+// Enum for governance proposals, each property's proposal IDs and who voted. Holders can
+// delegate their votes per property; the delegate's received votes and each holder's delegate
+// keep lazy per-snapshot histories like balances do.
+#[contracttype]
+pub enum GovernanceRegistry {
+    Proposal(u64),
+    Proposals(u64),
+    Voted(u64, Address),
+    Delegate(u64, Address),
+    DelegateHistory(u64, Address),
+    Delegated(u64, Address),
+    DelegatedHistory(u64, Address),
+    Rules(u64),
+}
+
+// Enum for a property's expense records and its income and expense totals per calendar month
+#[contracttype]
+pub enum AccountingRegistry {
+    Expense(u64, u32),
+    ExpenseCount(u64),
+    Income(u64, u32),
+    Expenses(u64, u32),
+    Fees(u64, u32),
+    Periods(u64),
+    FeeTerms(u64),
+    FeesAccrued(u64),
+}
+
+// Enum for each property's reserve balance, its approved budgets and the budgets by proposal
+#[contracttype]
+pub enum BudgetRegistry {
+    Reserve(u64),
+    Budgets(u64),
+    Budget(u64),
+}
+
+// Enum for leases and each property's lease IDs
+#[contracttype]
+pub enum LeaseRegistry {
+    Lease(u64),
+    Leases(u64),
+}
+
+// Enum for a property's insurance policies and the claims made against them
+#[contracttype]
+pub enum InsuranceRegistry {
+    Policy(u64, u32),
+    PolicyCount(u64),
+    Claim(u64, u32),
+    ClaimCount(u64),
+}
+
+// Enum for a property's liquidation, which starts once co-owners approve it, and its recorded sale
+#[contracttype]
+pub enum LiquidationRegistry {
+    Approved(u64),
+    Sale(u64),
+}
+
+// Enum for a property's open buyback and the shares each holder tendered into it
+#[contracttype]
+pub enum BuybackRegistry {
+    Offer(u64),
+    Tendered(u64, Address),
+}
+
+// Enum for approved subdivisions and the links between a split property and its units, and
+// between merged properties and what they were merged into
+#[contracttype]
+pub enum SubdivisionRegistry {
+    Approved(u64),
+    Children(u64),
+    Parent(u64),
+    Constituents(u64),
+    MergedInto(u64),
+}
+
+// Enum for property deeds and the deeds each address holds
+#[contracttype]
+pub enum DeedRegistry {
+    Deed(u64),
+    Owned(Address),
+}
+
+// Enum for a property's open buyout offer and its governance-approved squeeze-out threshold
+#[contracttype]
+pub enum BuyoutRegistry {
+    Offer(u64),
+    Threshold(u64),
+}
+
+// Enum for scheduled transfers
+#[contracttype]
+pub enum ScheduleRegistry {
+    Transfer(u64),
+}
+
+// Enum for each holder's vesting grants
+#[contracttype]
+pub enum VestingRegistry {
+    Grants(u64, Address),
+}
+
+// Enum for the KYC allowlist
+#[contracttype]
+pub enum KycRegistry {
+    Investor(Address),
+    Eligibility(u64),
+}
+
+// Enum for secondary-market orders and offers, each property's open ones and each seller's
+// listed shares (in sell orders or auctions)
+#[contracttype]
+pub enum MarketRegistry {
+    SellOrder(u64),
+    Asks(u64),
+    Listed(u64, Address),
+    Offer(u64),
+    Bids(u64),
+    Royalty(u64),
+    PriceHistory(u64),
+    Swap(u64),
+}
+
+// Enum for installment purchases: each plan and the shares reserved per property
+#[contracttype]
+pub enum InstallmentRegistry {
+    Plan(u64),
+    Reserved(u64),
+}
+
+// Enum for auctions
+#[contracttype]
+pub enum AuctionRegistry {
+    Dutch(u64),
+    English(u64),
+}
+
+// Enum for income distributions: each deposit numbered per property, the number made so far,
+// and the income pushed to each holder's internal balance until withdrawn
+#[contracttype]
+pub enum DistributionRegistry {
+    Distribution(u64, u32),
+    Count(u64),
+    Balance(Address),
+}
+
+// Enum for bundles and bundle share balances
+#[contracttype]
+pub enum BundleRegistry {
+    Bundle(u64),
+    Balance(u64, Address),
+    Outstanding(u64),
+}
+
+// Constants for contract storage
+const PROPERTY_COUNTER: Symbol = symbol_short!("PROP_CTR");
+const PROPERTY_STATS: Symbol = symbol_short!("PROP_STAT");
+const VALUE_STATS: Symbol = symbol_short!("VAL_STAT");
+const CONTRACT_ADMIN: Symbol = symbol_short!("ADMIN");
+const VOLUME_RETENTION: Symbol = symbol_short!("VOL_RET");
+const IMPORT_MODE: Symbol = symbol_short!("IMPORT");
+const IMPORT_DONE: Symbol = symbol_short!("IMP_DONE");
+const COMPLIANCE_HOOK: Symbol = symbol_short!("COMPLY");
+const BOOKING_COUNTER: Symbol = symbol_short!("BOOK_CTR");
+const QUOTE_COUNTER: Symbol = symbol_short!("QUOTE_CTR");
+const TENANTS: Symbol = symbol_short!("TENANTS");
+const BUNDLE_COUNTER: Symbol = symbol_short!("BNDL_CTR");
+const ORDER_COUNTER: Symbol = symbol_short!("ORDER_CTR");
+const AUCTION_COUNTER: Symbol = symbol_short!("AUCT_CTR");
+const INSTALLMENT_COUNTER: Symbol = symbol_short!("INST_CTR");
+const SWAP_COUNTER: Symbol = symbol_short!("SWAP_CTR");
+const SCHEDULE_COUNTER: Symbol = symbol_short!("SCHED_CTR");
+const PROPOSAL_COUNTER: Symbol = symbol_short!("PRPSL_CTR");
+const LEASE_COUNTER: Symbol = symbol_short!("LEASE_CTR");
+const ID_CONFIG: Symbol = symbol_short!("ID_CFG");
+const SANDBOX_MODE: Symbol = symbol_short!("SANDBOX");
+const SANDBOX_ACCOUNTS: Symbol = symbol_short!("SBX_ACCTS");
+const STRICT_AUTH: Symbol = symbol_short!("STRICT");
+const ERROR_COUNTERS: Symbol = symbol_short!("ERR_CTRS");
+const PAYMENT_TOKEN: Symbol = symbol_short!("PAY_TOKEN");
+const KEEPER_DAILY_CAP: Symbol = symbol_short!("KEEP_CAP");
+const PLATFORM_FEE_BPS: Symbol = symbol_short!("FEE_BPS");
+const FEE_BALANCE: Symbol = symbol_short!("FEES");
+const CONTRACT_PAUSED: Symbol = symbol_short!("PAUSED");
+const WITHDRAWN_COUNT: Symbol = symbol_short!("WDRN_CTR");
+const KYC_REQUIRED: Symbol = symbol_short!("KYC_REQ");
+
+// Content-hash IDs always have the top bit set so they never meet sequential or imported IDs
+const HASHED_ID_FLAG: u64 = 1 << 63;
+
+// Endpoint tags embedded in pagination cursors so a cursor cannot be replayed elsewhere
+const CURSOR_PROPERTIES: u32 = 1;
+const CURSOR_HOLDINGS: u32 = 2;
+const CURSOR_FILTERED: u32 = 3;
+const CURSOR_CATEGORY: u32 = 4;
+const CURSOR_HOLDERS: u32 = 5;
+const CURSOR_CAP_EXPORT: u32 = 6;
+const CURSOR_PRICE_HISTORY: u32 = 7;
+const CURSOR_CAP_TABLE: u32 = 8;
+const CURSOR_OWNERS: u32 = 9;
+
+// Most index positions a filtered listing examines per call
+const MAX_FILTER_SCAN: u64 = 200;
+
+// Every property category, for sweeping the per-category indexes
+const PROPERTY_TYPES: [PropertyType; 5] = [
+    PropertyType::Residential,
+    PropertyType::Commercial,
+    PropertyType::Industrial,
+    PropertyType::Land,
+    PropertyType::MixedUse,
+];
+
+// Tenant assigned to properties registered without one
+const DEFAULT_TENANT: Symbol = symbol_short!("default");
+
+// Metadata size limits in bytes
+const MAX_METADATA_VALUE_BYTES: u32 = 2048;
+const MAX_METADATA_TOTAL_BYTES: u32 = 16384;
+
+// Badge thresholds
+const EARLY_BACKER_WINDOW_SECONDS: u64 = 86400;
+const DIAMOND_HANDS_SECONDS: u64 = 365 * 86400;
+const FIVE_PROPERTIES: u32 = 5;
+
+// Constants for volume analytics
+const SECONDS_PER_DAY: u64 = 86400;
+const DEFAULT_VOLUME_RETENTION_DAYS: u64 = 90;
+const MAX_VOLUME_DAYS_PER_QUERY: u64 = 366;
+
+// Property, ownership and holdings records live in persistent storage; each read or write
+// tops its TTL back up to roughly 30 days once it drops below roughly a week
+const RECORD_TTL_THRESHOLD: u32 = 120960;
+const RECORD_TTL_LEDGERS: u32 = 518400;
+
+// Idempotency records are kept for roughly a week (ledgers close every ~5 seconds)
+const IDEMPOTENCY_TTL_LEDGERS: u32 = 120960;
+
+// Largest number of items accepted by batch purchases, transfers and lookups, to stay within resource limits
+const MAX_BATCH_ITEMS: u32 = 20;
+
+// Fixed-point scale of the per-share income accumulator
+const DIVIDEND_PRECISION: u128 = 1_000_000_000_000;
+
+// Highest platform fee the admin can set, in basis points
+const MAX_PLATFORM_FEE_BPS: u32 = 1000;
+
+// Highest percentage of income a property's manager can be paid, in basis points
+const MAX_MANAGEMENT_FEE_BPS: u32 = 2000;
+
+// Highest royalty a registrant can set on secondary sales, in basis points
+const MAX_ROYALTY_BPS: u32 = 1000;
+
+// Number of trades kept in each property's price history; older ones are dropped
+const PRICE_HISTORY_LEN: u32 = 50;
+
+// How long governance proposals stay open for voting
+const VOTING_PERIOD_SECONDS: u64 = 7 * 86400;
+
+// Governance rules of properties that have not set their own: no quorum, simple majority
+const DEFAULT_QUORUM_BPS: u32 = 0;
+const DEFAULT_THRESHOLD_BPS: u32 = 5000;
+
+// Share of a property a buyout bidder must hold to squeeze out the rest: all of it unless
+// co-owners approve a lower threshold, which cannot go below the floor
+const DEFAULT_SQUEEZE_OUT_BPS: u32 = 10000;
+const MIN_SQUEEZE_OUT_BPS: u32 = 9000;
+
+// Installment plans: most payments allowed, time allowed for each and the share of the total
+// payable (in basis points) the seller keeps when a plan defaults
+const MAX_INSTALLMENTS: u32 = 24;
+const INSTALLMENT_INTERVAL_SECONDS: u64 = 30 * 86400;
+const INSTALLMENT_FORFEIT_BPS: u32 = 1000;
+
+// Quotes hold their price for ten minutes; storage outlives them by a margin
+const QUOTE_VALIDITY_SECONDS: u64 = 600;
+const QUOTE_TTL_LEDGERS: u32 = 720;
+
+// How long before expiry an insurance policy is flagged as expiring soon
+const INSURANCE_WARNING_SECONDS: u64 = 30 * 86400;
+
+#[contract]
+pub struct RealEstateNFT;
+
+#[contractimpl]
+impl RealEstateNFT {
+    // Initialize the contract with an admin address and the token (SAC) purchases are paid in.
+    // A sandbox deployment accepts real flows but can be wiped until go_live is called.
+    pub fn initialize(env: Env, admin: Address, payment_token: Address, sandbox: bool) {
+        // Ensure contract is only initialized once
+        if env.storage().instance().has(&CONTRACT_ADMIN) {
+            panic!("Contract already initialized");
+        }
+        
+        // Store admin address and payment token
+        env.storage().instance().set(&CONTRACT_ADMIN, &admin);
+        env.storage().instance().set(&PAYMENT_TOKEN, &payment_token);
+        
+        // Initialize property stats
+        let stats = PropertyStats {
+            total_properties: 0,
+            verified_properties: 0,
+            total_owners: 0,
+            total_transactions: 0,
+        };
+        
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&PROPERTY_COUNTER, &0u64);
+        if sandbox {
+            env.storage().instance().set(&SANDBOX_MODE, &true);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "RealEstateNFT contract initialized with admin: {}", admin);
+    }
+    
+    // Function to register a new property
+    pub fn register_property(env: Env, registrant: Address, input: PropertyInput) -> u64 {
+        // Authentication
+        registrant.require_auth();
+        
+        Self::register(env, registrant, input)
+    }
+    
+    // Function to verify a property (admin or verifier)
+    pub fn verify_property(env: Env, verifier: Address, property_id: u64) {
+        Self::require_role(&env, &verifier, Role::Verifier);
+        
+        Self::verify(env, verifier, property_id);
+    }
+    
+    // Function to withdraw a listing from sale and from property listings (registrant or admin).
+    // Existing holders keep and can still transfer their shares.
+    pub fn delist_property(env: Env, caller: Address, property_id: u64) {
+        // Check registrant or admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        if caller != admin && caller != Self::get_registrant(env.clone(), property_id) {
+            panic!("Not authorized to delist property");
+        }
+        caller.require_auth();
+        
+        let status = Self::get_property_status(env.clone(), property_id);
+        if status == PropertyStatus::Delisted || status == PropertyStatus::Archived {
+            panic!("Property already withdrawn");
+        }
+        
+        Self::withdraw_listing(&env, property_id, PropertyStatus::Delisted);
+        log!(&env, "Property {} delisted", property_id);
+    }
+    
+    // Function to permanently archive a delisted or sold-out property (admin only)
+    pub fn archive_property(env: Env, property_id: u64) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let status = Self::get_property_status(env.clone(), property_id);
+        if status != PropertyStatus::Delisted && status != PropertyStatus::Sold {
+            panic!("Only delisted or sold properties can be archived");
+        }
+        
+        Self::withdraw_listing(&env, property_id, PropertyStatus::Archived);
+        log!(&env, "Property {} archived", property_id);
+    }
+    
+    // Function to turn down an unverified property with a reason code (admin or verifier)
+    pub fn reject_property(env: Env, verifier: Address, property_id: u64, reason: Symbol) {
+        Self::require_role(&env, &verifier, Role::Verifier);
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if property.is_verified {
+            panic!("Property already verified");
+        }
+        if Self::get_review(env.clone(), property_id).is_some_and(|review| review.status == ReviewStatus::Rejected) {
+            panic!("Property already rejected");
+        }
+        
+        Self::record_review(&env, property_id, ReviewStatus::Rejected, reason.clone(), verifier);
+        log!(&env, "Property {} rejected: {}", property_id, reason);
+    }
+    
+    // Function to withdraw a property's verification with a reason code, closing it to purchases
+    // (admin or verifier)
+    pub fn revoke_verification(env: Env, verifier: Address, property_id: u64, reason: Symbol) {
+        Self::require_role(&env, &verifier, Role::Verifier);
+        
+        let mut property = Self::get_property(env.clone(), property_id);
+        if !property.is_verified {
+            panic!("Property not verified");
+        }
+        property.is_verified = false;
+        
+        let mut stats = Self::get_property_stats(env.clone());
+        stats.verified_properties -= 1;
+        let mut value_stats = Self::get_value_stats(env.clone());
+        if !Self::is_withdrawn(&env, property_id) {
+            value_stats.verified_market_cap -= Self::market_cap(&property);
+        }
+        
+        Self::store_property(&env, &property);
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+        
+        Self::record_review(&env, property_id, ReviewStatus::Revoked, reason.clone(), verifier);
+        log!(&env, "Verification of property {} revoked: {}", property_id, reason);
+    }
+    
+    // Function for trusted partners to register, verify and credit a founder stake in one call,
+    // authorized by both the admin and the registrant. Any failing step reverts all of it.
+    pub fn register_verified_property(env: Env, admin: Address, registrant: Address, property_input: PropertyInput, founder_shares: u64) -> u64 {
+        // Check admin and registrant authorization
+        let contract_admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        if admin != contract_admin {
+            panic!("Not authorized to register verified properties");
+        }
+        admin.require_auth();
+        registrant.require_auth();
+        
+        let mut input = property_input;
+        input.founder_shares = founder_shares;
+        
+        let property_id = Self::register(env.clone(), registrant, input);
+        Self::verify(env, admin, property_id);
+        
+        property_id
+    }
+    
+    // Function to set the requirement codes every property of a type must complete before verification (admin only)
+    pub fn set_verification_checklist(env: Env, property_type: PropertyType, requirements: Vec<Symbol>) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        for (i, code) in requirements.iter().enumerate() {
+            if requirements.iter().skip(i + 1).any(|other| other == code) {
+                panic!("Duplicate requirement code");
+            }
+        }
+        
+        env.storage().instance().set(&VerificationRegistry::Checklist(property_type), &requirements);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Verification checklist set with {} requirements", requirements.len());
+    }
+    
+    // Function to record a checklist requirement as completed for a property (admin or verifier)
+    pub fn complete_requirement(env: Env, verifier: Address, property_id: u64, code: Symbol, evidence_uri: String) {
+        Self::require_role(&env, &verifier, Role::Verifier);
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if property.is_verified {
+            panic!("Property already verified");
+        }
+        if !Self::property_checklist(&env, property_id).contains(&code) {
+            panic!("Unknown requirement code");
+        }
+        
+        let completion = RequirementCompletion {
+            completed_by: verifier,
+            completed_at: env.ledger().timestamp(),
+            evidence_uri,
+        };
+        env.storage().instance().set(&VerificationRegistry::Completed(property_id, code.clone()), &completion);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Requirement {} completed for property {}", code, property_id);
+    }
+    
+    // Function to enter import mode for migrating a legacy deployment (admin only, empty contract)
+    pub fn begin_import(env: Env) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        // Import mode is one-way: once finalized it can never be re-entered
+        if env.storage().instance().has(&IMPORT_DONE) {
+            panic!("Import already finalized");
+        }
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        if property_counter > 0 {
+            panic!("Import requires an empty contract");
+        }
+        
+        env.storage().instance().set(&IMPORT_MODE, &true);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Import mode enabled");
+    }
+    
+    // Function to bulk load properties and ownership records from a legacy deployment (admin only).
+    // May be called repeatedly in batches; counters, stats and indexes are rebuilt as records arrive.
+    pub fn import_state(env: Env, properties: Vec<Property>, ownerships: Vec<OwnershipShare>) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if !Self::is_import_mode(env.clone()) {
+            panic!("Import mode is not active");
+        }
+        
+        let mut stats = Self::get_property_stats(env.clone());
+        let mut value_stats = Self::get_value_stats(env.clone());
+        
+        // Load properties, keeping their legacy IDs
+        for property in properties.iter() {
+            let property = PropertyV2::from(property);
+            if property.property_id == 0 || Self::load_property(&env, property.property_id).is_some() {
+                panic!("Invalid or duplicate imported property ID");
+            }
+            
+            stats.total_properties += 1;
+            value_stats.total_market_cap += Self::market_cap(&property);
+            if property.is_verified {
+                stats.verified_properties += 1;
+                value_stats.verified_market_cap += Self::market_cap(&property);
+            }
+            
+            Self::store_property(&env, &property);
+            Self::index_property_id(&env, property.property_id);
+            Self::index_tenant_property(&env, &property.tenant, property.property_id);
+        }
+        
+        // Load ownership records against properties imported in this or an earlier batch
+        for ownership in ownerships.iter() {
+            let property = Self::load_property(&env, ownership.property_id)
+                .expect("Imported ownership references unknown property");
+            
+            if ownership.shares == 0 || Self::load_ownership(&env, ownership.property_id, &ownership.owner).is_some() {
+                panic!("Invalid or duplicate imported ownership record");
+            }
+            
+            let sold_key = SupplyRegistry::SharesSold(ownership.property_id);
+            let shares_sold: u64 = env.storage().instance().get(&sold_key).unwrap_or(0);
+            if shares_sold + ownership.shares > property.total_shares {
+                panic!("Imported shares exceed total shares");
+            }
+            
+            stats.total_owners += 1;
+            
+            // Legacy records predate last_updated; backfill it from the acquisition time
+            let mut ownership = ownership;
+            if ownership.last_updated < ownership.purchase_time {
+                ownership.last_updated = ownership.purchase_time;
+            }
+            
+            Self::store_ownership(&env, &ownership);
+            Self::advance_income_clock(&env, ownership.property_id);
+            env.storage().instance().set(&sold_key, &(shares_sold + ownership.shares));
+            Self::add_cached_total(&env, &ownership.owner, ownership.shares);
+            Self::add_user_property(&env, &ownership.owner, ownership.property_id);
+        }
+        
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Imported {} properties and {} ownership records", properties.len(), ownerships.len());
+    }
+    
+    // Function to assign the registrant of an imported property, which legacy records do not carry.
+    // Authorized by the admin and the new registrant; a property's registrant can never be replaced.
+    pub fn assign_registrant(env: Env, property_id: u64, registrant: Address) {
+        // Check admin and registrant authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        registrant.require_auth();
+        
+        Self::load_property(&env, property_id).expect("Property not found");
+        let key = PropertyRegistry::Registrant(property_id);
+        if env.storage().instance().has(&key) {
+            panic!("Registrant already assigned");
+        }
+        env.storage().instance().set(&key, &registrant);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Registrant {} assigned to property {}", registrant, property_id);
+    }
+    
+    // Function to set the category of an imported property, which legacy records do not carry (admin only)
+    pub fn classify_property(env: Env, property_id: u64, property_type: PropertyType) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        Self::load_property(&env, property_id).expect("Property not found");
+        if Self::get_property_type(env.clone(), property_id).is_some() {
+            panic!("Property already classified");
+        }
+        Self::classify(&env, property_id, property_type);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Property {} classified", property_id);
+    }
+    
+    // Function to permanently leave import mode (admin only)
+    pub fn finalize_import(env: Env) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if !Self::is_import_mode(env.clone()) {
+            panic!("Import mode is not active");
+        }
+        
+        env.storage().instance().remove(&IMPORT_MODE);
+        env.storage().instance().set(&IMPORT_DONE, &true);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Import finalized");
+    }
+    
+    // Function to choose how property IDs are assigned (admin only, empty contract).
+    // Sequential remains the default; the salt only matters for content-hash IDs.
+    pub fn set_id_mode(env: Env, mode: IdMode, salt: BytesN<32>) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        // Switching modes would leave existing IDs unreachable from the position index
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        if property_counter > 0 {
+            panic!("ID mode requires an empty contract");
+        }
+        
+        env.storage().instance().set(&ID_CONFIG, &IdConfig { mode, salt });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Property ID mode updated");
+    }
+    
+    // Function to move legacy property records to persistent storage in the PropertyV2 layout, at most max_items
+    // list positions per call (admin only). Unmigrated records are still readable, so this can
+    // run at leisure; returns the cursor to pass next, or None once every position is done.
+    pub fn migrate_properties(env: Env, cursor: u64, max_items: u64) -> Option<u64> {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let property_count: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let end = property_count.min(cursor.saturating_add(max_items));
+        
+        for position in cursor..end {
+            let property_id = Self::property_id_at(&env, position + 1);
+            if !env.storage().persistent().has(&PropertyRegistry::PropertyV2(property_id)) {
+                if let Some(property) = Self::load_property(&env, property_id) {
+                    Self::store_property(&env, &property);
+                }
+            }
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Migrated property records up to position {}", end);
+        
+        if end < property_count { Some(end) } else { None }
+    }
+    
+    // Function to wipe sandbox state in batches of at most max_items properties or accounts (admin only).
+    // Start with cursor 0 and pass back the returned cursor until None; counters, stats and
+    // indexes are cleared by the final batch. Trading should be idle while a reset runs.
+    pub fn reset_sandbox(env: Env, cursor: u64, max_items: u64) -> Option<u64> {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if !Self::is_sandbox(env.clone()) {
+            panic!("Contract is not in sandbox mode");
+        }
+        
+        // Properties come first, then every account that ever held shares
+        let property_count: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let accounts: Vec<Address> = env.storage().instance().get(&SANDBOX_ACCOUNTS).unwrap_or(Vec::new(&env));
+        let total = property_count + accounts.len() as u64;
+        let end = total.min(cursor.saturating_add(max_items));
+        
+        for item in cursor..end {
+            if item < property_count {
+                Self::wipe_sandbox_property(&env, Self::property_id_at(&env, item + 1));
+                env.storage().instance().remove(&PropertyRegistry::IdAt(item + 1));
+            } else {
+                Self::wipe_sandbox_account(&env, &accounts.get((item - property_count) as u32).unwrap());
+            }
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        if end < total {
+            log!(&env, "Sandbox reset progressed to {} of {}", end, total);
+            return Some(end);
+        }
+        
+        // Final batch: clear the platform-wide counters and indexes
+        let mut tenants = Self::get_tenants(env.clone());
+        tenants.push_back(DEFAULT_TENANT);
+        for tenant in tenants.iter() {
+            env.storage().instance().remove(&TenantRegistry::Properties(tenant));
+        }
+        for property_type in PROPERTY_TYPES {
+            env.storage().instance().remove(&CategoryRegistry::Properties(property_type));
+        }
+        Self::wipe_recent_volume(&env, None);
+        
+        env.storage().instance().set(&PROPERTY_COUNTER, &0u64);
+        env.storage().instance().remove(&PROPERTY_STATS);
+        env.storage().instance().remove(&WITHDRAWN_COUNT);
+        env.storage().instance().remove(&VALUE_STATS);
+        env.storage().instance().remove(&SANDBOX_ACCOUNTS);
+        
+        log!(&env, "Sandbox reset complete");
+        None
+    }
+    
+    // Function to leave sandbox mode for good; reset_sandbox is impossible afterwards (admin only)
+    pub fn go_live(env: Env) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if !Self::is_sandbox(env.clone()) {
+            panic!("Contract is not in sandbox mode");
+        }
+        
+        env.storage().instance().remove(&SANDBOX_MODE);
+        env.storage().instance().remove(&SANDBOX_ACCOUNTS);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Contract is live");
+    }
+    
+    // Function to set the compliance contract consulted on transfers of at least threshold_shares (admin only)
+    pub fn set_compliance_hook(env: Env, hook: Address, threshold_shares: u64) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let config = ComplianceHook { hook: hook.clone(), threshold_shares };
+        env.storage().instance().set(&COMPLIANCE_HOOK, &config);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Compliance hook set to {} for transfers of {} shares or more", hook, threshold_shares);
+    }
+    
+    // Function to require that purchase and transfer signatures cover their exact economic terms (admin only).
+    // Off by default while wallets migrate from signing the plain invocation.
+    pub fn set_strict_auth(env: Env, enabled: bool) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        env.storage().instance().set(&STRICT_AUTH, &enabled);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Strict auth set to {}", enabled);
+    }
+    
+    // Function to remove the compliance hook (admin only)
+    pub fn clear_compliance_hook(env: Env) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        env.storage().instance().remove(&COMPLIANCE_HOOK);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Compliance hook cleared");
+    }
+    
+    // Function to correct a property's title, location, description or image
+    // (registrant before verification, admin after)
+    pub fn update_property(env: Env, caller: Address, property_id: u64, update: PropertyUpdate) {
+        let mut property = Self::get_property(env.clone(), property_id);
+        
+        // Check registrant or admin authorization
+        let editor = if property.is_verified {
+            env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized")
+        } else {
+            Self::get_registrant(env.clone(), property_id)
+        };
+        if caller != editor {
+            panic!("Not authorized to update property");
+        }
+        caller.require_auth();
+        
+        if let Some(title) = update.title.clone() {
+            property.title = title;
+        }
+        if let Some(location) = update.location.clone() {
+            property.location = location;
+        }
+        if let Some(description) = update.description.clone() {
+            property.description = description;
+        }
+        if let Some(image_url) = update.image_url.clone() {
+            property.image_url = image_url;
+        }
+        Self::store_property(&env, &property);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "property_updated"), property_id, PropertyUpdatedEvent {
+            updated_by: caller,
+            update,
+        });
+        log!(&env, "Property {} updated", property_id);
+    }
+    
+    // Function to change whether a property's cap table is private (registrant only, before the first sale)
+    pub fn set_property_privacy(env: Env, property_id: u64, is_private: bool) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let mut property = Self::get_property(env.clone(), property_id);
+        
+        // Investors buy in under the privacy terms they saw, so the flag freezes at the first sale
+        let founder_shares = Self::get_founder_allocation(env.clone(), property_id).map(|allocation| allocation.shares).unwrap_or(0);
+        if Self::get_shares_sold(env.clone(), property_id) > founder_shares {
+            panic!("Privacy cannot change after the first sale");
+        }
+        
+        property.is_private = is_private;
+        Self::store_property(&env, &property);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Property {} privacy set to {}", property_id, is_private);
+    }
+    
+    // Function to close a verified property's offering (registrant only). Unsold shares are credited
+    // to the unsold shares recipient as an ordinary position; without one they stay available.
+    pub fn finalize_offering(env: Env, property_id: u64) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if !property.is_verified {
+            panic!("Cannot finalize offering of unverified property");
+        }
+        
+        if Self::is_funding_open(&env, property_id) {
+            panic!("Funding goal not reached");
+        }
+        
+        let closed_key = SupplyRegistry::OfferingClosed(property_id);
+        if env.storage().instance().has(&closed_key) {
+            panic!("Offering already finalized");
+        }
+        env.storage().instance().set(&closed_key, &true);
+        
+        let recipient: Option<Address> = env.storage().instance().get(&SupplyRegistry::UnsoldRecipient(property_id));
+        let unsold = Self::get_available_shares(env.clone(), property_id);
+        if let Some(recipient) = recipient.filter(|_| unsold > 0) {
+            let timestamp = env.ledger().timestamp();
+            let existing = Self::load_ownership(&env, property_id, &recipient);
+            
+            let ownership = match existing.clone() {
+                Some(existing) => OwnershipShare {
+                    shares: existing.shares + unsold,
+                    last_updated: timestamp,
+                    ..existing
+                },
+                None => OwnershipShare {
+                    property_id,
+                    owner: recipient.clone(),
+                    shares: unsold,
+                    purchase_time: timestamp,
+                    last_updated: timestamp,
+                },
+            };
+            
+            if existing.is_none() {
+                Self::add_user_property(&env, &recipient, property_id);
+                let mut stats = Self::get_property_stats(env.clone());
+                stats.total_owners += 1;
+                env.storage().instance().set(&PROPERTY_STATS, &stats);
+            }
+            
+            let allocation = TreasuryAllocation {
+                recipient: recipient.clone(),
+                shares: unsold,
+                allocated_at: timestamp,
+            };
+            Self::store_ownership(&env, &ownership);
+            env.storage().instance().set(&SupplyRegistry::Treasury(property_id), &allocation);
+            Self::advance_income_clock(&env, property_id);
+            env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &property.total_shares);
+            Self::add_cached_total(&env, &recipient, unsold);
+            
+            Self::publish_property_event(&env, symbol_short!("treasury"), property_id, (recipient.clone(), unsold));
+            log!(&env, "{} unsold shares of property {} credited to treasury {}", unsold, property_id, recipient);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Offering for property {} finalized", property_id);
+    }
+    
+    // Function to attach or overwrite a metadata value on a property (registrant or property manager).
+    // Values are capped per key and in total per property; overwrites are charged only the difference.
+    pub fn set_property_metadata(env: Env, caller: Address, property_id: u64, key: Symbol, value: Bytes) {
+        Self::require_metadata_editor(&env, &caller, property_id);
+        
+        if value.len() > MAX_METADATA_VALUE_BYTES {
+            panic!("Metadata value too large");
+        }
+        
+        let value_key = MetadataRegistry::Value(property_id, key.clone());
+        let previous_size = Self::load_record::<MetadataRegistry, Bytes>(&env, &value_key).map(|previous| previous.len());
+        let total_size = Self::get_metadata_size(env.clone(), property_id) - previous_size.unwrap_or(0) + value.len();
+        if total_size > MAX_METADATA_TOTAL_BYTES {
+            panic!("Property metadata limit exceeded");
+        }
+        
+        if previous_size.is_none() {
+            let mut keys = Self::list_metadata_keys(env.clone(), property_id);
+            keys.push_back(key.clone());
+            Self::store_record(&env, &MetadataRegistry::Keys(property_id), &keys);
+        }
+        Self::store_record(&env, &value_key, &value);
+        Self::store_record(&env, &MetadataRegistry::TotalSize(property_id), &total_size);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Metadata {} set on property {} ({} bytes)", key, property_id, value.len());
+    }
+    
+    // Function to remove a metadata key from a property, releasing its size (registrant or property manager)
+    pub fn delete_metadata_key(env: Env, caller: Address, property_id: u64, key: Symbol) {
+        Self::require_metadata_editor(&env, &caller, property_id);
+        
+        let value_key = MetadataRegistry::Value(property_id, key.clone());
+        let value: Bytes = Self::load_record(&env, &value_key).expect("Metadata key not found");
+        
+        let mut keys = Self::list_metadata_keys(env.clone(), property_id);
+        if let Some(index) = keys.first_index_of(&key) {
+            keys.remove(index);
+        }
+        let total_size = Self::get_metadata_size(env.clone(), property_id) - value.len();
+        
+        Self::remove_record(&env, &value_key);
+        Self::store_record(&env, &MetadataRegistry::Keys(property_id), &keys);
+        Self::store_record(&env, &MetadataRegistry::TotalSize(property_id), &total_size);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Metadata {} deleted from property {}", key, property_id);
+    }
+    
+    // Function to define the milestones gating an escrowed property's proceeds (verifier; co-owners can
+    // also set them through a SetMilestones proposal). Takes (description, percentage) pairs that must
+    // add up to 100; fixed once a milestone is released.
+    pub fn set_milestones(env: Env, caller: Address, property_id: u64, milestones: Vec<(String, u32)>) {
+        // Check verifier authorization
+        Self::require_role(&env, &caller, Role::Verifier);
+        
+        Self::configure_milestones(&env, property_id, milestones);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+    }
+    
+    // Function to release a milestone's share of the proceeds escrowed so far to the registrant
+    // (verifier; co-owners can also release it through a ReleaseMilestone proposal). Returns the amount released.
+    pub fn release_milestone(env: Env, caller: Address, property_id: u64, milestone_index: u32) -> Money {
+        // Check verifier authorization
+        Self::require_role(&env, &caller, Role::Verifier);
+        
+        let amount = Self::release_escrowed_milestone(&env, property_id, milestone_index);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        amount
+    }
+    
+    // Function to mark an escrowed offering as failed, freezing releases and opening refunds (admin only)
+    pub fn mark_offering_failed(env: Env, property_id: u64) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if !Self::is_milestone_escrow(env.clone(), property_id) {
+            panic!("Property does not escrow proceeds");
+        }
+        let failed_key = EscrowRegistry::Failed(property_id);
+        if env.storage().instance().has(&failed_key) {
+            panic!("Offering already marked failed");
+        }
+        
+        // Refunds split what is left at the moment of failure
+        env.storage().instance().set(&failed_key, &Self::get_escrow_balance(env.clone(), property_id));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Offering for property {} marked failed", property_id);
+    }
+    
+    // Function for a buyer to reclaim their pro-rata share of the unreleased proceeds of a failed
+    // offering. Their shares return to the unsold supply, as with a crowdfunding refund.
+    pub fn claim_escrow_refund(env: Env, property_id: u64, buyer: Address) -> Money {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        let unreleased: Money = env.storage().instance().get(&EscrowRegistry::Failed(property_id)).expect("Offering has not failed");
+        let claimed_key = EscrowRegistry::RefundClaimed(property_id, buyer.clone());
+        if Self::has_record(&env, &claimed_key) {
+            panic!("Refund already claimed");
+        }
+        
+        let paid: Money = Self::load_record(&env, &EscrowRegistry::Paid(property_id, buyer.clone())).unwrap_or(0);
+        let proceeds: Money = env.storage().instance().get(&EscrowRegistry::Proceeds(property_id)).unwrap_or(0);
+        if paid == 0 || proceeds == 0 {
+            panic!("Nothing to refund");
+        }
+        let refund = unreleased * paid / proceeds;
+        
+        Self::burn_position(&env, property_id, &buyer);
+        Self::burn_class_positions(&env, property_id, &buyer);
+        
+        let refunded: Money = env.storage().instance().get(&EscrowRegistry::Refunded(property_id)).unwrap_or(0);
+        env.storage().instance().set(&EscrowRegistry::Refunded(property_id), &(refunded + refund));
+        Self::store_record(&env, &claimed_key, &true);
+        Self::pay(&env, &env.current_contract_address(), &buyer, refund);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("refund"), property_id, (buyer.clone(), refund));
+        log!(&env, "{} refunded {} from property {} escrow", buyer, refund, property_id);
+        
+        refund
+    }
+    
+    // Function for a buyer of a crowdfunded property that missed its deadline to reclaim their
+    // payment. Their shares return to the unsold supply; platform fees are not refunded.
+    pub fn refund(env: Env, property_id: u64, buyer: Address) -> Money {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        if !Self::is_funding_failed(&env, property_id) {
+            panic!("Funding has not failed");
+        }
+        let paid_key = FundingRegistry::Paid(property_id, buyer.clone());
+        let paid: Money = Self::load_record(&env, &paid_key).unwrap_or(0);
+        if paid == 0 {
+            panic!("Nothing to refund");
+        }
+        Self::remove_record(&env, &paid_key);
+        
+        Self::burn_position(&env, property_id, &buyer);
+        Self::burn_class_positions(&env, property_id, &buyer);
+        
+        let raised = Self::get_funds_raised(env.clone(), property_id);
+        env.storage().instance().set(&FundingRegistry::Raised(property_id), &(raised - paid));
+        Self::pay(&env, &env.current_contract_address(), &buyer, paid);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("refund"), property_id, (buyer.clone(), paid));
+        log!(&env, "{} refunded {} from crowdfunded property {}", buyer, paid, property_id);
+        
+        paid
+    }
+    
+    // Function to add a tenant (white-label frontend) to the registry (admin only)
+    pub fn add_tenant(env: Env, tenant: Symbol) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let mut tenants = Self::get_tenants(env.clone());
+        if tenants.contains(&tenant) {
+            panic!("Tenant already registered");
+        }
+        tenants.push_back(tenant.clone());
+        env.storage().instance().set(&TENANTS, &tenants);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Tenant {} added", tenant);
+    }
+    
+    // Function to remove a tenant from the registry (admin only).
+    // Existing properties keep their tenant; new registrations can no longer use it.
+    pub fn remove_tenant(env: Env, tenant: Symbol) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let mut tenants = Self::get_tenants(env.clone());
+        let index = tenants.first_index_of(&tenant).expect("Tenant not registered");
+        tenants.remove(index);
+        env.storage().instance().set(&TENANTS, &tenants);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Tenant {} removed", tenant);
+    }
+    
+    // Function to grant a role to an address (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let key = RoleRegistry::Member(role, account.clone());
+        if env.storage().instance().has(&key) {
+            panic!("Role already granted");
+        }
+        env.storage().instance().set(&key, &true);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "role_granted"), role), account.clone());
+        log!(&env, "Role granted to {}", account);
+    }
+    
+    // Function to revoke a role from an address (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let key = RoleRegistry::Member(role, account.clone());
+        if !env.storage().instance().has(&key) {
+            panic!("Role not granted");
+        }
+        env.storage().instance().remove(&key);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "role_revoked"), role), account.clone());
+        log!(&env, "Role revoked from {}", account);
+    }
+    
+    // Function to freeze registrations, purchases and transfers contract-wide during an incident (admin or pauser)
+    pub fn pause(env: Env, pauser: Address) {
+        Self::require_role(&env, &pauser, Role::Pauser);
+        Self::set_contract_paused(&env, true);
+        log!(&env, "Contract paused by {}", pauser);
+    }
+    
+    // Function to lift a contract-wide pause (admin or pauser)
+    pub fn unpause(env: Env, pauser: Address) {
+        Self::require_role(&env, &pauser, Role::Pauser);
+        Self::set_contract_paused(&env, false);
+        log!(&env, "Contract unpaused by {}", pauser);
+    }
+    
+    // Function to require every buyer and transfer recipient to be on the KYC allowlist (admin only)
+    pub fn set_kyc_required(env: Env, required: bool) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        env.storage().instance().set(&KYC_REQUIRED, &required);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "KYC requirement set to {}", required);
+    }
+    
+    // Function to add an investor to the KYC allowlist at a tier and jurisdiction, or change them
+    // (admin or compliance)
+    pub fn approve_investor(env: Env, compliance: Address, investor: Address, tier: u32, jurisdiction: Symbol) {
+        Self::require_role(&env, &compliance, Role::Compliance);
+        
+        let approval = InvestorApproval {
+            tier,
+            jurisdiction,
+            approved_by: compliance.clone(),
+            approved_at: env.ledger().timestamp(),
+        };
+        Self::store_record(&env, &KycRegistry::Investor(investor.clone()), &approval);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "investor_approved"), investor.clone()), tier);
+        log!(&env, "Investor {} approved at tier {} by {}", investor, tier, compliance);
+    }
+    
+    // Function to remove an investor from the KYC allowlist (admin or compliance). Shares they
+    // already hold stay with them but they can no longer buy or receive more.
+    pub fn revoke_investor(env: Env, compliance: Address, investor: Address) {
+        Self::require_role(&env, &compliance, Role::Compliance);
+        
+        let key = KycRegistry::Investor(investor.clone());
+        if !Self::has_record(&env, &key) {
+            panic!("Investor not approved");
+        }
+        Self::remove_record(&env, &key);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "investor_revoked"), investor.clone()), compliance.clone());
+        log!(&env, "Investor {} revoked by {}", investor, compliance);
+    }
+    
+    // Function to set who may acquire a property's shares (registrant only). The rules apply to
+    // purchases and incoming transfers whether or not the contract-wide KYC requirement is on;
+    // shares already held are unaffected.
+    pub fn set_eligibility_rules(env: Env, property_id: u64, rules: EligibilityRules) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        env.storage().instance().set(&KycRegistry::Eligibility(property_id), &rules);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("eligible"), property_id, rules);
+        log!(&env, "Eligibility rules of property {} updated", property_id);
+    }
+    
+    // Function to pause all trading of a single property, e.g. during legal review: purchases, transfers,
+    // listings, offers and income deposits fail while dividend claims stay open (admin or pauser)
+    pub fn pause_property(env: Env, pauser: Address, property_id: u64) {
+        Self::require_role(&env, &pauser, Role::Pauser);
+        Self::set_property_paused(&env, property_id, true);
+        log!(&env, "Property {} paused", property_id);
+    }
+    
+    // Function to resume trading of a paused property (admin or pauser)
+    pub fn unpause_property(env: Env, pauser: Address, property_id: u64) {
+        Self::require_role(&env, &pauser, Role::Pauser);
+        Self::set_property_paused(&env, property_id, false);
+        log!(&env, "Property {} unpaused", property_id);
+    }
+    
+    // Function to configure the sale phases of a property (admin only, before verification)
+    pub fn configure_sale_phases(env: Env, property_id: u64, phases: Vec<SalePhase>) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        // Phases are locked in once the property is open for sale
+        let property = Self::get_property(env.clone(), property_id);
+        if property.is_verified {
+            panic!("Sale phases must be configured before verification");
+        }
+        if Self::get_dutch_auction(env.clone(), property_id).is_some() {
+            panic!("Property is sold by Dutch auction");
+        }
+        
+        // Validate each phase window and ensure no two windows overlap
+        for (i, phase) in phases.iter().enumerate() {
+            if phase.start_time >= phase.end_time {
+                panic!("Sale phase must end after it starts");
+            }
+            if phase.max_shares == 0 || phase.max_shares > property.total_shares {
+                panic!("Invalid sale phase allocation");
+            }
+            for other in phases.iter().skip(i + 1) {
+                if phase.start_time < other.end_time && other.start_time < phase.end_time {
+                    panic!("Sale phases must not overlap");
+                }
+            }
+        }
+        
+        env.storage().instance().set(&SalePhaseRegistry::Phases(property_id), &phases);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Configured {} sale phases for property {}", phases.len(), property_id);
+    }
+    
+    // Function to sell a property's shares by Dutch auction, the price falling linearly from
+    // start_price to floor_price over duration seconds and staying at the floor afterwards
+    // (registrant only, before the first sale and without sale phases)
+    pub fn start_dutch_auction(env: Env, property_id: u64, start_price: Money, floor_price: Money, duration: u64) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let founder_shares = Self::get_founder_allocation(env.clone(), property_id).map_or(0, |allocation| allocation.shares);
+        if Self::get_shares_sold(env.clone(), property_id) > founder_shares {
+            panic!("Sales have already started");
+        }
+        if !Self::get_sale_phases(env.clone(), property_id).is_empty() {
+            panic!("Property uses sale phases");
+        }
+        if floor_price == 0 || start_price <= floor_price || duration == 0 {
+            panic!("Invalid auction terms");
+        }
+        
+        let auction = DutchAuction {
+            start_price,
+            floor_price,
+            start_time: env.ledger().timestamp(),
+            duration,
+        };
+        env.storage().instance().set(&AuctionRegistry::Dutch(property_id), &auction);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("dutch"), property_id, auction);
+        log!(&env, "Dutch auction started for property {}", property_id);
+    }
+    
+    // View function to get a property's Dutch auction terms, if it is sold by one
+    pub fn get_dutch_auction(env: Env, property_id: u64) -> Option<DutchAuction> {
+        env.storage().instance().get(&AuctionRegistry::Dutch(property_id))
+    }
+    
+    // Function to add or remove an address from a property's phase whitelist (admin only)
+    pub fn set_phase_whitelist(env: Env, property_id: u64, investor: Address, allowed: bool) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let key = SalePhaseRegistry::Whitelisted(property_id, investor.clone());
+        if allowed {
+            Self::store_record(&env, &key, &true);
+        } else {
+            Self::remove_record(&env, &key);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Phase whitelist for property {} updated for {}: {}", property_id, investor, allowed);
+    }
+    
+    // Function to purchase property shares
+    pub fn purchase_shares(env: Env, property_id: u64, shares: u64, buyer: Address, idempotency_key: Option<BytesN<32>>) {
+        // Authentication, bound to the property and amount under strict auth
+        Self::require_auth_for_terms(&env, &buyer, (property_id, shares).into_val(&env));
+        Self::record_activity(&env, &buyer);
+        
+        // Reject degenerate purchases and replays before touching any state
+        Self::validate_movement(&env, None, &buyer, shares);
+        Self::ensure_new_request(&env, &buyer, &idempotency_key);
+        
+        let new_shares = Self::execute_purchase(&env, property_id, shares, &buyer, &buyer, None).ownership.shares;
+        
+        Self::record_request(&env, &buyer, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("purchase"),
+            property_id,
+            shares,
+            counterparty: None,
+            resulting_balance: new_shares,
+            timestamp: env.ledger().timestamp(),
+        });
+    }
+    
+    // Function to buy shares of several properties in one all-or-nothing call; any failing leg reverts the batch.
+    // An idempotency key records the total shares bought and the buyer's balance in the last leg's property.
+    pub fn purchase_shares_batch(env: Env, buyer: Address, purchases: Vec<(u64, u64)>, idempotency_key: Option<BytesN<32>>) {
+        // Authentication, bound to the whole batch under strict auth
+        Self::require_auth_for_terms(&env, &buyer, (purchases.clone(),).into_val(&env));
+        Self::record_activity(&env, &buyer);
+        
+        if purchases.is_empty() || purchases.len() > MAX_BATCH_ITEMS {
+            panic!("Invalid batch size");
+        }
+        Self::ensure_new_request(&env, &buyer, &idempotency_key);
+        
+        let mut last = (0, 0);
+        let mut total_shares: u64 = 0;
+        for (property_id, shares) in purchases.iter() {
+            Self::validate_movement(&env, None, &buyer, shares);
+            let new_shares = Self::execute_purchase(&env, property_id, shares, &buyer, &buyer, None).ownership.shares;
+            last = (property_id, new_shares);
+            total_shares = total_shares.saturating_add(shares);
+        }
+        
+        Self::record_request(&env, &buyer, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("buy_batch"),
+            property_id: last.0,
+            shares: total_shares,
+            counterparty: None,
+            resulting_balance: last.1,
+            timestamp: env.ledger().timestamp(),
+        });
+        
+        log!(&env, "{} bought shares of {} properties in one batch", buyer, purchases.len());
+    }
+    
+    // Function to check a purchase against every restriction and compute its outcome without
+    // writing anything; doubles as the quoting primitive for wallets
+    pub fn validate_purchase(env: Env, intent: PurchaseIntent) -> Result<PurchasePlan, Error> {
+        let property_id = intent.property_id;
+        let shares = intent.shares;
+        if Self::is_paused(env.clone()) {
+            return Err(Error::ContractPaused);
+        }
+        if shares == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        
+        // Get property data
+        let property = Self::load_property(&env, property_id).ok_or(Error::PropertyNotFound)?;
+        
+        // Check if property is verified and open for trading
+        if !property.is_verified {
+            return Err(Error::PropertyNotVerified);
+        }
+        if property.is_paused {
+            return Err(Error::PropertyPaused);
+        }
+        if Self::is_withdrawn(&env, property_id) {
+            return Err(Error::PropertyDelisted);
+        }
+        // Imported properties stay closed until a registrant exists to receive the proceeds
+        if !env.storage().instance().has(&PropertyRegistry::Registrant(property_id)) {
+            return Err(Error::PropertyNotVerified);
+        }
+        if Self::get_migrated_to(env.clone(), intent.buyer.clone()).is_some() {
+            return Err(Error::AccountMigrated);
+        }
+        if let Some(reason) = Self::eligibility_blocker(&env, property_id, &intent.buyer) {
+            return Err(Self::transfer_error(&reason));
+        }
+        if env.storage().instance().has(&EscrowRegistry::Failed(property_id)) || Self::is_funding_failed(&env, property_id) {
+            return Err(Error::OfferingFailed);
+        }
+        if env.storage().instance().has(&LiquidationRegistry::Sale(property_id)) {
+            return Err(Error::PropertyLiquidated);
+        }
+        // A deeded property's shares are all accounted for by its deed
+        if env.storage().instance().has(&DeedRegistry::Deed(property_id)) {
+            return Err(Error::SupplyExceeded);
+        }
+        
+        // Never sell more shares than the property has
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id).checked_add(shares).ok_or(Error::AmountOverflow)?;
+        if shares_sold.saturating_add(Self::get_reserved_shares(env.clone(), property_id)) > property.total_shares {
+            return Err(Error::SupplyExceeded);
+        }
+        
+        // Enforce the active sale phase when the property runs a phased offering
+        let current_timestamp = env.ledger().timestamp();
+        let mut price_per_share = Self::list_price(&env, &property);
+        let mut phase_index = None;
+        let mut phase_sold = 0;
+        let phases = Self::get_sale_phases(env.clone(), property_id);
+        
+        if !phases.is_empty() {
+            let index = Self::active_phase_index(&phases, current_timestamp).ok_or(Error::NoActivePhase)?;
+            let phase = phases.get(index).unwrap();
+            
+            if phase.requires_whitelist && !Self::is_phase_whitelisted(env.clone(), property_id, intent.buyer.clone()) {
+                return Err(Error::NotWhitelisted);
+            }
+            
+            phase_sold = Self::get_phase_sold(env.clone(), property_id, index) + shares;
+            if phase_sold > phase.max_shares {
+                return Err(Error::PhaseAllocationExceeded);
+            }
+            
+            phase_index = Some(index);
+            price_per_share = phase.price_per_share;
+        }
+        
+        // A locked quote overrides the price at execution time
+        if let Some(locked_price) = intent.locked_price {
+            price_per_share = locked_price;
+        }
+        let total_price = (shares as Money).checked_mul(price_per_share).ok_or(Error::AmountOverflow)?;
+        
+        let (ownership, is_new_owner) = Self::position_after_purchase(&env, property_id, &intent.buyer, shares)?;
+        Self::check_purchase_limits(&env, property_id, shares, ownership.shares)?;
+        
+        Ok(PurchasePlan {
+            property_id,
+            buyer: intent.buyer,
+            shares,
+            price_per_share,
+            total_price,
+            platform_fee: Self::platform_fee(&env, total_price),
+            phase_index,
+            phase_sold,
+            ownership,
+            is_new_owner,
+            shares_sold,
+        })
+    }
+    
+    // Function to count why an operation would be rejected (admin only, for the support backend).
+    //
+    // A rejected purchase or transfer reverts its transaction, and a reverted transaction cannot
+    // leave a counter behind. Instead the backend replays the failed call here: the check runs
+    // through the same pure validation as the real entry point and, when it fails, the error code
+    // is counted and its code returned as a successful result, so the write commits. The code is
+    // a plain u32 because the host treats a returned Error value as a failed call. Returns None
+    // when the operation would currently succeed, in which case nothing is counted.
+    pub fn record_rejection(env: Env, check: RejectionCheck) -> Option<u32> {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let error = match check {
+            RejectionCheck::Purchase(intent) => Self::validate_purchase(env.clone(), intent).err(),
+            RejectionCheck::Transfer(property_id, from, to, shares) => {
+                Self::validate_transfer(&env, property_id, &from, &to, shares).err()
+            }
+        }?;
+        
+        let mut counters = Self::get_error_counters(env.clone());
+        let code = error as u32;
+        counters.set(code, counters.get(code).unwrap_or(0) + 1);
+        env.storage().instance().set(&ERROR_COUNTERS, &counters);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Recorded rejection with error code {}", code);
+        
+        Some(code)
+    }
+    
+    // Function to clear the rejection counters (admin only)
+    pub fn reset_error_counters(env: Env) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        env.storage().instance().remove(&ERROR_COUNTERS);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Error counters reset");
+    }
+    
+    // View function to get the number of recorded rejections per error code
+    pub fn get_error_counters(env: Env) -> Map<u32, u64> {
+        env.storage().instance().get(&ERROR_COUNTERS).unwrap_or(Map::new(&env))
+    }
+    
+    // Function to lock the current price of a purchase for QUOTE_VALIDITY_SECONDS.
+    // Quotes lock price only; they do not reserve supply.
+    pub fn lock_quote(env: Env, property_id: u64, buyer: Address, shares: u64) -> u64 {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        Self::validate_movement(&env, None, &buyer, shares);
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if !property.is_verified {
+            panic!("Cannot quote shares of unverified property");
+        }
+        
+        let price_per_share = Self::current_price(&env, &property);
+        let expires_at = env.ledger().timestamp() + QUOTE_VALIDITY_SECONDS;
+        
+        let mut quote_counter: u64 = env.storage().instance().get(&QUOTE_COUNTER).unwrap_or(0);
+        quote_counter += 1;
+        
+        let quote = PriceQuote {
+            quote_id: quote_counter,
+            property_id,
+            buyer: buyer.clone(),
+            shares,
+            price_per_share,
+            total_price: (shares as Money).checked_mul(price_per_share)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow)),
+            expires_at,
+        };
+        
+        let key = QuoteRegistry::Quote(quote_counter);
+        env.storage().temporary().set(&key, &quote);
+        env.storage().temporary().extend_ttl(&key, QUOTE_TTL_LEDGERS, QUOTE_TTL_LEDGERS);
+        env.storage().instance().set(&QUOTE_COUNTER, &quote_counter);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Quote {} locked for {} shares of property {} at {} per share", quote_counter, shares, property_id, price_per_share);
+        
+        quote_counter
+    }
+    
+    // Function to execute a purchase at the price locked by a quote, before it expires
+    pub fn purchase_with_quote(env: Env, quote_id: u64) {
+        let key = QuoteRegistry::Quote(quote_id);
+        let quote: PriceQuote = env.storage().temporary().get(&key).expect("Quote not found");
+        
+        // Authentication, bound to the quoted amount and price under strict auth
+        Self::require_auth_for_terms(&env, &quote.buyer, (quote.property_id, quote.shares, quote.price_per_share).into_val(&env));
+        Self::record_activity(&env, &quote.buyer);
+        
+        if env.ledger().timestamp() > quote.expires_at {
+            panic!("Quote expired");
+        }
+        
+        // Quotes are single use
+        env.storage().temporary().remove(&key);
+        
+        Self::execute_purchase(&env, quote.property_id, quote.shares, &quote.buyer, &quote.buyer, Some(quote.price_per_share));
+    }
+    
+    // Function to transfer shares to another user
+    pub fn transfer_shares(env: Env, property_id: u64, from: Address, to: Address, shares: u64, idempotency_key: Option<BytesN<32>>) {
+        // Authentication, bound to the property, amount and recipient under strict auth
+        Self::require_auth_for_terms(&env, &from, (property_id, shares, to.clone()).into_val(&env));
+        Self::record_activity(&env, &from);
+        
+        // Reject degenerate transfers and replays before touching any state
+        Self::validate_movement(&env, Some(&from), &to, shares);
+        Self::ensure_new_request(&env, &from, &idempotency_key);
+        
+        let remaining = Self::execute_transfer(&env, property_id, &from, &to, shares);
+        
+        Self::record_request(&env, &from, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("transfer"),
+            property_id,
+            shares,
+            counterparty: Some(to.clone()),
+            resulting_balance: remaining,
+            timestamp: env.ledger().timestamp(),
+        });
+    }
+    
+    // Function to send shares of several properties in one all-or-nothing call; any failing leg reverts the batch.
+    // An idempotency key records the total shares sent and the sender's balance left in the last leg's property.
+    pub fn transfer_shares_batch(env: Env, from: Address, transfers: Vec<(u64, Address, u64)>, idempotency_key: Option<BytesN<32>>) {
+        // Authentication, bound to the whole batch under strict auth
+        Self::require_auth_for_terms(&env, &from, (transfers.clone(),).into_val(&env));
+        Self::record_activity(&env, &from);
+        
+        if transfers.is_empty() || transfers.len() > MAX_BATCH_ITEMS {
+            panic!("Invalid batch size");
+        }
+        Self::ensure_new_request(&env, &from, &idempotency_key);
+        
+        let mut last = (0, 0, None);
+        let mut total_shares: u64 = 0;
+        for (property_id, to, shares) in transfers.iter() {
+            Self::validate_movement(&env, Some(&from), &to, shares);
+            let remaining = Self::execute_transfer(&env, property_id, &from, &to, shares);
+            last = (property_id, remaining, Some(to));
+            total_shares = total_shares.saturating_add(shares);
+        }
+        
+        Self::record_request(&env, &from, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("xfer_bat"),
+            property_id: last.0,
+            shares: total_shares,
+            counterparty: last.2,
+            resulting_balance: last.1,
+            timestamp: env.ledger().timestamp(),
+        });
+        
+        log!(&env, "{} made {} transfers in one batch", from, transfers.len());
+    }
+    
+    // Function to let a spender move up to shares of a property on the owner's behalf through
+    // transfer_from; replaces any previous allowance, zero revokes it
+    pub fn approve(env: Env, owner: Address, property_id: u64, spender: Address, shares: u64) {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        Self::get_property(env.clone(), property_id);
+        let key = AllowanceRegistry::Allowance(property_id, owner.clone(), spender.clone());
+        if shares == 0 {
+            Self::remove_record(&env, &key);
+        } else {
+            Self::store_record(&env, &key, &shares);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("approval"), property_id, (owner.clone(), spender.clone(), shares));
+        log!(&env, "{} approved {} to move {} shares of property {}", owner, spender, shares, property_id);
+    }
+    
+    // Function to give or take away an operator's right to move any of the owner's shares in
+    // every property through transfer_from
+    pub fn set_operator(env: Env, owner: Address, operator: Address, approved: bool) {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let key = AllowanceRegistry::Operator(owner.clone(), operator.clone());
+        if approved {
+            Self::store_record(&env, &key, &true);
+        } else {
+            Self::remove_record(&env, &key);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((symbol_short!("operator"), owner.clone()), (operator.clone(), approved));
+        log!(&env, "Operator {} of {} set to {}", operator, owner, approved);
+    }
+    
+    // Function for an approved spender or operator to move shares from a holder to any recipient.
+    // Spenders use up their allowance; operators need none. The transfer goes through the usual checks.
+    pub fn transfer_from(env: Env, spender: Address, property_id: u64, from: Address, to: Address, shares: u64) {
+        // Authentication
+        spender.require_auth();
+        Self::record_activity(&env, &spender);
+        
+        Self::validate_movement(&env, Some(&from), &to, shares);
+        if !Self::is_operator(env.clone(), from.clone(), spender.clone()) {
+            let allowance = Self::get_allowance(env.clone(), property_id, from.clone(), spender.clone());
+            if allowance < shares {
+                panic!("Insufficient allowance");
+            }
+            let key = AllowanceRegistry::Allowance(property_id, from.clone(), spender.clone());
+            if allowance == shares {
+                Self::remove_record(&env, &key);
+            } else {
+                Self::store_record(&env, &key, &(allowance - shares));
+            }
+        }
+        
+        Self::execute_transfer(&env, property_id, &from, &to, shares);
+        log!(&env, "{} moved {} shares of property {} from {}", spender, shares, property_id, from);
+    }
+    
+    // View function to check whether an address is an operator for all of an owner's shares
+    pub fn is_operator(env: Env, owner: Address, operator: Address) -> bool {
+        Self::has_record(&env, &AllowanceRegistry::Operator(owner, operator))
+    }
+    
+    // View function to get how many of an owner's shares a spender may still move
+    pub fn get_allowance(env: Env, property_id: u64, owner: Address, spender: Address) -> u64 {
+        Self::load_record(&env, &AllowanceRegistry::Allowance(property_id, owner, spender)).unwrap_or(0)
+    }
+    
+    // Function to atomically swap shares of one property held by a for shares of another held by b.
+    // Both legs go through the full transfer checks; a violation on either leg reverts the swap.
+    pub fn swap_shares(env: Env, a: Address, property_a: u64, shares_a: u64, b: Address, property_b: u64, shares_b: u64) {
+        // Authentication of both parties
+        a.require_auth();
+        b.require_auth();
+        Self::record_activity(&env, &a);
+        Self::record_activity(&env, &b);
+        
+        Self::validate_movement(&env, Some(&a), &b, shares_a);
+        Self::validate_movement(&env, Some(&b), &a, shares_b);
+        if property_a == property_b {
+            panic!("Swap legs must be different properties");
+        }
+        
+        Self::execute_transfer(&env, property_a, &a, &b, shares_a);
+        Self::execute_transfer(&env, property_b, &b, &a, shares_b);
+        
+        log!(&env, "{} swapped {} shares of property {} for {} shares of property {}", a, shares_a, property_a, shares_b, property_b);
+    }
+    
+    // Function to propose a swap to a counterparty, who can accept it until expires_at. The
+    // proposer's shares are not locked; both legs are checked when the swap is accepted.
+    // Returns the swap ID.
+    pub fn propose_swap(env: Env, proposer: Address, property_give: u64, shares_give: u64, counterparty: Address, property_want: u64, shares_want: u64, sweetener: Money, expires_at: u64) -> u64 {
+        // Authentication
+        proposer.require_auth();
+        Self::record_activity(&env, &proposer);
+        
+        Self::validate_movement(&env, Some(&proposer), &counterparty, shares_give);
+        Self::validate_movement(&env, Some(&counterparty), &proposer, shares_want);
+        if property_give == property_want {
+            panic!("Swap legs must be different properties");
+        }
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Expiry must be in the future");
+        }
+        Self::get_property(env.clone(), property_give);
+        Self::get_property(env.clone(), property_want);
+        
+        Self::pay(&env, &proposer, &env.current_contract_address(), sweetener);
+        let swap_id: u64 = env.storage().instance().get(&SWAP_COUNTER).unwrap_or(0) + 1;
+        let swap = SwapProposal {
+            swap_id,
+            proposer: proposer.clone(),
+            property_give,
+            shares_give,
+            counterparty: counterparty.clone(),
+            property_want,
+            shares_want,
+            sweetener,
+            expires_at,
+        };
+        Self::store_record(&env, &MarketRegistry::Swap(swap_id), &swap);
+        env.storage().instance().set(&SWAP_COUNTER, &swap_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "swap_proposed"), counterparty.clone()), swap);
+        log!(&env, "{} proposed swap {} to {}", proposer, swap_id, counterparty);
+        swap_id
+    }
+    
+    // Function for the counterparty to accept a swap before it expires. Both legs go through the
+    // full transfer checks and the sweetener is paid to the counterparty.
+    pub fn accept_swap(env: Env, swap_id: u64) {
+        let swap = Self::get_swap(env.clone(), swap_id);
+        
+        // Check counterparty authorization
+        swap.counterparty.require_auth();
+        Self::record_activity(&env, &swap.counterparty);
+        
+        if env.ledger().timestamp() >= swap.expires_at {
+            panic!("Swap expired");
+        }
+        
+        Self::remove_record(&env, &MarketRegistry::Swap(swap_id));
+        Self::execute_transfer(&env, swap.property_give, &swap.proposer, &swap.counterparty, swap.shares_give);
+        Self::execute_transfer(&env, swap.property_want, &swap.counterparty, &swap.proposer, swap.shares_want);
+        Self::pay(&env, &env.current_contract_address(), &swap.counterparty, swap.sweetener);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((Symbol::new(&env, "swap_accepted"), swap.proposer.clone()), swap_id);
+        log!(&env, "Swap {} accepted by {}", swap_id, swap.counterparty);
+    }
+    
+    // Function to withdraw a swap proposal and refund its sweetener (proposer only, or anyone once
+    // it has expired, with a keeper address collecting the keeper reward)
+    pub fn cancel_swap(env: Env, swap_id: u64, keeper: Option<Address>) {
+        let swap = Self::get_swap(env.clone(), swap_id);
+        
+        // Check proposer authorization unless the swap has expired
+        let expired = env.ledger().timestamp() >= swap.expires_at;
+        if !expired {
+            swap.proposer.require_auth();
+        }
+        
+        Self::remove_record(&env, &MarketRegistry::Swap(swap_id));
+        Self::pay(&env, &env.current_contract_address(), &swap.proposer, swap.sweetener);
+        if let (true, Some(keeper)) = (expired, keeper) {
+            Self::reward_keeper(&env, &keeper, symbol_short!("swap_exp"), 1, swap.sweetener);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Swap {} closed", swap_id);
+    }
+    
+    // View function to get a swap proposal
+    pub fn get_swap(env: Env, swap_id: u64) -> SwapProposal {
+        Self::load_record(&env, &MarketRegistry::Swap(swap_id)).expect("Swap not found")
+    }
+    
+    // Function to escrow shares for a recipient to claim from release_time, e.g. for a closing tied
+    // to a real-world date. The shares stay in the sender's position, earning income but locked,
+    // until delivered. Returns the transfer ID.
+    pub fn schedule_transfer(env: Env, from: Address, property_id: u64, to: Address, shares: u64, release_time: u64) -> u64 {
+        // Authentication, bound to the property, amount and recipient under strict auth
+        Self::require_auth_for_terms(&env, &from, (property_id, shares, to.clone()).into_val(&env));
+        Self::record_activity(&env, &from);
+        
+        Self::validate_movement(&env, Some(&from), &to, shares);
+        if release_time <= env.ledger().timestamp() {
+            panic!("Release time must be in the future");
+        }
+        
+        Self::list_shares(&env, property_id, &from, shares);
+        let transfer_id: u64 = env.storage().instance().get(&SCHEDULE_COUNTER).unwrap_or(0) + 1;
+        let scheduled = ScheduledTransfer {
+            transfer_id,
+            property_id,
+            from: from.clone(),
+            to: to.clone(),
+            shares,
+            release_time,
+        };
+        Self::store_record(&env, &ScheduleRegistry::Transfer(transfer_id), &scheduled);
+        env.storage().instance().set(&SCHEDULE_COUNTER, &transfer_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("scheduled"), property_id, scheduled);
+        log!(&env, "{} scheduled {} shares of property {} for {}", from, shares, property_id, to);
+        transfer_id
+    }
+    
+    // Function for the recipient to claim a scheduled transfer once its release time has passed;
+    // the delivery goes through the usual transfer checks
+    pub fn claim_scheduled_transfer(env: Env, transfer_id: u64) {
+        let scheduled = Self::get_scheduled_transfer(env.clone(), transfer_id);
+        
+        // Check recipient authorization
+        scheduled.to.require_auth();
+        Self::record_activity(&env, &scheduled.to);
+        
+        if env.ledger().timestamp() < scheduled.release_time {
+            panic!("Transfer not released yet");
+        }
+        
+        Self::remove_record(&env, &ScheduleRegistry::Transfer(transfer_id));
+        Self::unlist_shares(&env, scheduled.property_id, &scheduled.from, scheduled.shares);
+        Self::execute_transfer(&env, scheduled.property_id, &scheduled.from, &scheduled.to, scheduled.shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Scheduled transfer {} claimed by {}", transfer_id, scheduled.to);
+    }
+    
+    // Function for the sender to take back a scheduled transfer before its release time
+    pub fn cancel_scheduled_transfer(env: Env, transfer_id: u64) {
+        let scheduled = Self::get_scheduled_transfer(env.clone(), transfer_id);
+        
+        // Check sender authorization
+        scheduled.from.require_auth();
+        
+        if env.ledger().timestamp() >= scheduled.release_time {
+            panic!("Transfer already released");
+        }
+        
+        Self::remove_record(&env, &ScheduleRegistry::Transfer(transfer_id));
+        Self::unlist_shares(&env, scheduled.property_id, &scheduled.from, scheduled.shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Scheduled transfer {} cancelled", transfer_id);
+    }
+    
+    // View function to get a pending scheduled transfer
+    pub fn get_scheduled_transfer(env: Env, transfer_id: u64) -> ScheduledTransfer {
+        Self::load_record(&env, &ScheduleRegistry::Transfer(transfer_id)).expect("Scheduled transfer not found")
+    }
+    
+    // Function to name a beneficiary who inherits all holdings after inactivity_seconds without activity
+    pub fn set_beneficiary(env: Env, owner: Address, beneficiary: Address, inactivity_seconds: u64) {
+        owner.require_auth();
+        
+        if beneficiary == owner || inactivity_seconds == 0 {
+            panic!("Invalid inheritance plan");
+        }
+        
+        let plan = InheritancePlan {
+            beneficiary: beneficiary.clone(),
+            inactivity_seconds,
+            last_active: env.ledger().timestamp(),
+        };
+        Self::store_record(&env, &InheritanceRegistry::Plan(owner.clone()), &plan);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} named {} as beneficiary", owner, beneficiary);
+    }
+    
+    // Function to remove an inheritance plan
+    pub fn clear_beneficiary(env: Env, owner: Address) {
+        owner.require_auth();
+        
+        Self::remove_record(&env, &InheritanceRegistry::Plan(owner.clone()));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} cleared their beneficiary", owner);
+    }
+    
+    // Function to signal that an owner is still active, resetting the inactivity clock
+    pub fn touch(env: Env, owner: Address) {
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+    }
+    
+    // Function for a beneficiary to take over an inactive owner's holdings across all properties.
+    // Properties whose transfer restrictions block the move are skipped and reported with the
+    // blocking reason; they can be claimed again once the restriction lifts.
+    pub fn claim_inheritance(env: Env, owner: Address, beneficiary: Address) -> Vec<(u64, Symbol)> {
+        // Authentication
+        beneficiary.require_auth();
+        
+        let plan = Self::get_inheritance_plan(env.clone(), owner.clone()).expect("No inheritance plan");
+        if plan.beneficiary != beneficiary {
+            panic!("Not the named beneficiary");
+        }
+        if env.ledger().timestamp() < plan.last_active + plan.inactivity_seconds {
+            panic!("Owner is not inactive yet");
+        }
+        
+        let mut skipped = Vec::new(&env);
+        for property_id in Self::get_user_properties(env.clone(), owner.clone()).iter() {
+            let shares = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+            if shares == 0 {
+                continue;
+            }
+            
+            let property = Self::get_property(env.clone(), property_id);
+            match Self::transfer_blocker(&env, &property, &owner, &beneficiary, shares, shares) {
+                Some(reason) => skipped.push_back((property_id, reason)),
+                None => {
+                    Self::execute_transfer(&env, property_id, &owner, &beneficiary, shares);
+                }
+            }
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} claimed the inheritance of {}, {} properties skipped", beneficiary, owner, skipped.len());
+        
+        skipped
+    }
+    
+    // Function to create a bundle of (property_id, shares per bundle share) components
+    pub fn create_bundle(env: Env, creator: Address, components: Vec<(u64, u64)>, bundle_price: Money) -> u64 {
+        // Authentication
+        creator.require_auth();
+        Self::record_activity(&env, &creator);
+        
+        if components.is_empty() {
+            panic!("Bundle must have components");
+        }
+        
+        // Every component must be an existing, distinct property with a positive weight
+        let mut seen = Vec::new(&env);
+        for (property_id, weight) in components.iter() {
+            Self::get_property(env.clone(), property_id);
+            if weight == 0 || seen.contains(property_id) {
+                panic!("Invalid bundle component");
+            }
+            seen.push_back(property_id);
+        }
+        
+        let mut bundle_counter: u64 = env.storage().instance().get(&BUNDLE_COUNTER).unwrap_or(0);
+        bundle_counter += 1;
+        
+        let bundle = Bundle {
+            bundle_id: bundle_counter,
+            creator: creator.clone(),
+            components,
+            bundle_price,
+            created_at: env.ledger().timestamp(),
+        };
+        
+        env.storage().instance().set(&BundleRegistry::Bundle(bundle_counter), &bundle);
+        env.storage().instance().set(&BUNDLE_COUNTER, &bundle_counter);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Bundle {} created by {}", bundle_counter, creator);
+        
+        bundle_counter
+    }
+    
+    // Function to buy bundle shares at the bundle price; each one atomically buys the weighted
+    // underlying shares, which the contract holds in custody until the bundle shares are redeemed.
+    // The underlying shares are paid for at their own prices out of the bundle price, and the
+    // rest goes to the bundle's creator.
+    pub fn purchase_bundle_shares(env: Env, bundle_id: u64, buyer: Address, count: u64) {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        Self::validate_movement(&env, None, &buyer, count);
+        
+        let bundle = Self::get_bundle(env.clone(), bundle_id);
+        let custody = env.current_contract_address();
+        let price = bundle.bundle_price.checked_mul(count as Money).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        
+        let mut underlying_cost: Money = 0;
+        for (property_id, weight) in bundle.components.iter() {
+            let shares = weight.checked_mul(count).expect("Bundle purchase too large");
+            if Self::get_available_shares(env.clone(), property_id) < shares {
+                panic!("Not enough underlying shares available");
+            }
+            let plan = Self::execute_purchase(&env, property_id, shares, &custody, &buyer, None);
+            underlying_cost += plan.total_price + plan.platform_fee;
+        }
+        if underlying_cost > price {
+            panic!("Bundle price below the cost of its underlying shares");
+        }
+        Self::pay(&env, &buyer, &bundle.creator, price - underlying_cost);
+        
+        let balance_key = BundleRegistry::Balance(bundle_id, buyer.clone());
+        let balance: u64 = Self::load_record(&env, &balance_key).unwrap_or(0);
+        let outstanding: u64 = env.storage().instance().get(&BundleRegistry::Outstanding(bundle_id)).unwrap_or(0);
+        
+        Self::store_record(&env, &balance_key, &(balance + count));
+        env.storage().instance().set(&BundleRegistry::Outstanding(bundle_id), &(outstanding + count));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} bought {} shares of bundle {} for {}", buyer, count, bundle_id, price);
+    }
+    
+    // Function to unwrap bundle shares back into the underlying property shares
+    pub fn redeem_bundle_shares(env: Env, bundle_id: u64, owner: Address, count: u64) {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        Self::validate_movement(&env, None, &owner, count);
+        
+        let bundle = Self::get_bundle(env.clone(), bundle_id);
+        let balance_key = BundleRegistry::Balance(bundle_id, owner.clone());
+        let balance: u64 = Self::load_record(&env, &balance_key).unwrap_or(0);
+        if balance < count {
+            panic!("Insufficient bundle shares");
+        }
+        
+        let custody = env.current_contract_address();
+        for (property_id, weight) in bundle.components.iter() {
+            Self::execute_transfer(&env, property_id, &custody, &owner, weight * count);
+        }
+        
+        let outstanding: u64 = env.storage().instance().get(&BundleRegistry::Outstanding(bundle_id)).unwrap_or(0);
+        Self::store_record(&env, &balance_key, &(balance - count));
+        env.storage().instance().set(&BundleRegistry::Outstanding(bundle_id), &(outstanding - count));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} redeemed {} shares of bundle {}", owner, count, bundle_id);
+    }
+    
+    // Function to sweep the income holders left unclaimed after a distribution into their internal
+    // balances, for at most max_holders holders of the property's holder list from start_cursor
+    // (property manager or admin). Income from earlier deposits is swept along with it. Holders with
+    // nothing unclaimed are skipped, and pushing takes the same pending income a claim would, so
+    // nobody is paid twice. The sweep must resume where the last batch stopped; once it reaches
+    // the end of the list the distribution is settled. Returns the cursor for the next batch, or
+    // None once settled.
+    pub fn push_distribute(env: Env, caller: Address, property_id: u64, distribution_id: u32, start_cursor: u32, max_holders: u32) -> Option<u32> {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &caller) {
+            panic!("Not authorized to distribute income");
+        }
+        caller.require_auth();
+        
+        if Self::is_distribution_settled(env.clone(), property_id, distribution_id) {
+            panic!("Distribution already settled");
+        }
+        let key = DistributionRegistry::Distribution(property_id, distribution_id);
+        let mut distribution = Self::get_distribution(env.clone(), property_id, distribution_id);
+        if start_cursor != distribution.next_holder {
+            panic!("Sweep must resume at the saved cursor");
+        }
+        
+        let holders: Vec<Address> = Self::load_record(&env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(&env));
+        let end = holders.len().min(start_cursor.saturating_add(max_holders));
+        let mut pushed: Money = 0;
+        for index in start_cursor..end {
+            let holder = holders.get(index).unwrap();
+            if Self::get_claimable(env.clone(), property_id, holder.clone()) == 0 {
+                continue;
+            }
+            let amount = Self::take_dividends(&env, property_id, &holder);
+            let balance_key = DistributionRegistry::Balance(holder.clone());
+            let balance: Money = Self::load_record(&env, &balance_key).unwrap_or(0);
+            Self::store_record(&env, &balance_key, &(balance + amount));
+            pushed += amount;
+        }
+        
+        // A full sweep settles the distribution, and its record is no longer needed
+        let settled = end == holders.len();
+        if settled {
+            Self::remove_record(&env, &key);
+        } else {
+            distribution.next_holder = end;
+            Self::store_record(&env, &key, &distribution);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("push"), property_id, (distribution_id, start_cursor, end, pushed));
+        log!(&env, "Pushed {} of distribution {} to holders {} to {}", pushed, distribution_id, start_cursor, end);
+        if settled { None } else { Some(end) }
+    }
+    
+    // Function for a holder to withdraw the income pushed to their internal balance
+    pub fn withdraw_balance(env: Env, owner: Address) -> Money {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let key = DistributionRegistry::Balance(owner.clone());
+        let amount: Money = Self::load_record(&env, &key).unwrap_or(0);
+        if amount == 0 {
+            panic!("Nothing to withdraw");
+        }
+        Self::remove_record(&env, &key);
+        Self::pay(&env, &env.current_contract_address(), &owner, amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((symbol_short!("withdraw"), owner.clone()), amount);
+        log!(&env, "{} withdrew {} from their internal balance", owner, amount);
+        amount
+    }
+    
+    // View function to check whether a push sweep has reached every holder of a distribution
+    pub fn is_distribution_settled(env: Env, property_id: u64, distribution_id: u32) -> bool {
+        distribution_id >= 1
+            && distribution_id <= Self::get_distribution_count(env.clone(), property_id)
+            && !Self::has_record(&env, &DistributionRegistry::Distribution(property_id, distribution_id))
+    }
+    
+    // View function to get an income distribution of a property that has not been settled yet
+    pub fn get_distribution(env: Env, property_id: u64, distribution_id: u32) -> Distribution {
+        Self::load_record(&env, &DistributionRegistry::Distribution(property_id, distribution_id)).expect("Distribution not found")
+    }
+    
+    // View function to get the number of income distributions made to a property
+    pub fn get_distribution_count(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&DistributionRegistry::Count(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get the income pushed to an address and not yet withdrawn
+    pub fn get_internal_balance(env: Env, owner: Address) -> Money {
+        Self::load_record(&env, &DistributionRegistry::Balance(owner)).unwrap_or(0)
+    }
+    
+    // Function to offer shares for sale at a fixed price. The shares stay in the seller's position,
+    // still earning income, but cannot be moved until the order is filled or cancelled.
+    pub fn create_sell_order(env: Env, seller: Address, property_id: u64, shares: u64, price_per_share: Money) -> u64 {
+        // Authentication
+        seller.require_auth();
+        Self::record_activity(&env, &seller);
+        
+        if shares == 0 || price_per_share == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        Self::list_shares(&env, property_id, &seller, shares);
+        
+        let order_id: u64 = env.storage().instance().get(&ORDER_COUNTER).unwrap_or(0) + 1;
+        let order = SellOrder {
+            order_id,
+            property_id,
+            seller: seller.clone(),
+            shares,
+            price_per_share,
+            created_at: env.ledger().timestamp(),
+        };
+        
+        let asks_key = MarketRegistry::Asks(property_id);
+        let mut asks: Vec<u64> = env.storage().instance().get(&asks_key).unwrap_or(Vec::new(&env));
+        asks.push_back(order_id);
+        env.storage().instance().set(&asks_key, &asks);
+        Self::store_record(&env, &MarketRegistry::SellOrder(order_id), &order);
+        env.storage().instance().set(&ORDER_COUNTER, &order_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("ask"), property_id, order);
+        log!(&env, "Sell order {} created by {}", order_id, seller);
+        order_id
+    }
+    
+    // Function to withdraw the unsold remainder of a sell order (seller only)
+    pub fn cancel_sell_order(env: Env, order_id: u64) {
+        let order = Self::get_sell_order(env.clone(), order_id);
+        
+        // Check seller authorization
+        order.seller.require_auth();
+        
+        Self::close_sell_order(&env, &order, order.shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("ask_cncl"), order.property_id, order_id);
+        log!(&env, "Sell order {} cancelled", order_id);
+    }
+    
+    // Function to buy shares from a sell order at its price. The shares go through the usual
+    // transfer checks and are paid for in the same call; partial fills leave the rest listed.
+    pub fn fill_sell_order(env: Env, buyer: Address, order_id: u64, shares: u64, idempotency_key: Option<BytesN<32>>) {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        let mut order = Self::get_sell_order(env.clone(), order_id);
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if shares > order.shares {
+            panic!("Order has fewer shares left");
+        }
+        Self::ensure_new_request(&env, &buyer, &idempotency_key);
+        
+        let cost = (shares as Money).checked_mul(order.price_per_share).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        Self::check_buyer_limits(&env, order.property_id, &buyer, shares).unwrap_or_else(|error| panic_with_error!(&env, error));
+        Self::close_sell_order(&env, &order, shares);
+        Self::execute_transfer(&env, order.property_id, &order.seller, &buyer, shares);
+        Self::pay_sale_proceeds(&env, order.property_id, &buyer, &order.seller, cost);
+        Self::record_trade_price(&env, order.property_id, shares, order.price_per_share, false);
+        Self::record_lot(&env, order.property_id, &buyer, shares);
+        
+        order.shares -= shares;
+        if order.shares > 0 {
+            Self::store_record(&env, &MarketRegistry::SellOrder(order_id), &order);
+        }
+        Self::record_request(&env, &buyer, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("fill"),
+            property_id: order.property_id,
+            shares,
+            counterparty: Some(order.seller.clone()),
+            resulting_balance: Self::get_ownership(env.clone(), order.property_id, buyer.clone()).shares,
+            timestamp: env.ledger().timestamp(),
+        });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("fill"), order.property_id, (order_id, buyer.clone(), shares, cost));
+        log!(&env, "{} bought {} shares from sell order {}", buyer, shares, order_id);
+    }
+    
+    // Function to bid for shares at a fixed price until expires_at; the full payment is moved into
+    // the contract up front and returned for whatever is not filled
+    pub fn make_offer(env: Env, buyer: Address, property_id: u64, shares: u64, price_per_share: Money, expires_at: u64) -> u64 {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        Self::require_unpaused_property(&env, property_id);
+        if shares == 0 || price_per_share == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Offer already expired");
+        }
+        
+        let cost = (shares as Money).checked_mul(price_per_share).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        Self::pay(&env, &buyer, &env.current_contract_address(), cost);
+        
+        let offer_id: u64 = env.storage().instance().get(&ORDER_COUNTER).unwrap_or(0) + 1;
+        let offer = BuyOffer {
+            offer_id,
+            property_id,
+            buyer: buyer.clone(),
+            shares,
+            price_per_share,
+            created_at: env.ledger().timestamp(),
+            expires_at,
+        };
+        
+        let bids_key = MarketRegistry::Bids(property_id);
+        let mut bids: Vec<u64> = env.storage().instance().get(&bids_key).unwrap_or(Vec::new(&env));
+        bids.push_back(offer_id);
+        env.storage().instance().set(&bids_key, &bids);
+        Self::store_record(&env, &MarketRegistry::Offer(offer_id), &offer);
+        env.storage().instance().set(&ORDER_COUNTER, &offer_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("bid"), property_id, offer);
+        log!(&env, "Offer {} made by {}", offer_id, buyer);
+        offer_id
+    }
+    
+    // Function to sell shares into an open offer at its price; any holder may accept
+    pub fn accept_offer(env: Env, seller: Address, offer_id: u64, shares: u64, idempotency_key: Option<BytesN<32>>) {
+        // Authentication
+        seller.require_auth();
+        Self::record_activity(&env, &seller);
+        
+        let mut offer = Self::get_offer(env.clone(), offer_id);
+        if env.ledger().timestamp() >= offer.expires_at {
+            panic!("Offer expired");
+        }
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if shares > offer.shares {
+            panic!("Offer has fewer shares left");
+        }
+        Self::ensure_new_request(&env, &seller, &idempotency_key);
+        
+        let proceeds = shares as Money * offer.price_per_share;
+        Self::check_buyer_limits(&env, offer.property_id, &offer.buyer, shares).unwrap_or_else(|error| panic_with_error!(&env, error));
+        let remaining = Self::execute_transfer(&env, offer.property_id, &seller, &offer.buyer, shares);
+        Self::pay_sale_proceeds(&env, offer.property_id, &env.current_contract_address(), &seller, proceeds);
+        Self::record_trade_price(&env, offer.property_id, shares, offer.price_per_share, false);
+        Self::record_lot(&env, offer.property_id, &offer.buyer, shares);
+        
+        offer.shares -= shares;
+        if offer.shares == 0 {
+            Self::remove_offer(&env, &offer);
+        } else {
+            Self::store_record(&env, &MarketRegistry::Offer(offer_id), &offer);
+        }
+        Self::record_request(&env, &seller, &idempotency_key, IdempotencyRecord {
+            operation: symbol_short!("accept"),
+            property_id: offer.property_id,
+            shares,
+            counterparty: Some(offer.buyer.clone()),
+            resulting_balance: remaining,
+            timestamp: env.ledger().timestamp(),
+        });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("accept"), offer.property_id, (offer_id, seller.clone(), shares, proceeds));
+        log!(&env, "{} sold {} shares into offer {}", seller, shares, offer_id);
+    }
+    
+    // Function to withdraw an offer and refund its unfilled payment. The buyer may cancel at any
+    // time; once expired, anyone may close it on the buyer's behalf, with a keeper address
+    // collecting the keeper reward.
+    pub fn cancel_offer(env: Env, offer_id: u64, keeper: Option<Address>) {
+        let offer = Self::get_offer(env.clone(), offer_id);
+        
+        // Check buyer authorization unless the offer has expired
+        let expired = env.ledger().timestamp() >= offer.expires_at;
+        if !expired {
+            offer.buyer.require_auth();
+        }
+        
+        let refund = offer.shares as Money * offer.price_per_share;
+        Self::remove_offer(&env, &offer);
+        Self::pay(&env, &env.current_contract_address(), &offer.buyer, refund);
+        if let (true, Some(keeper)) = (expired, keeper) {
+            Self::reward_keeper(&env, &keeper, symbol_short!("offer_exp"), 1, refund);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("bid_cncl"), offer.property_id, (offer_id, refund));
+        log!(&env, "Offer {} closed, refunding {}", offer_id, refund);
+    }
+    
+    // Function to settle crossing offers and sell orders of a property, best prices first, for at
+    // most max_trades fills. Each trade executes at the price of whichever side was placed first;
+    // a buyer filled below their bid gets the difference back. Anyone may call it, with a keeper
+    // address collecting the keeper reward for the trades. A crossing pair that fails the transfer
+    // checks reverts the call, and one side has to be cancelled.
+    pub fn match_orders(env: Env, property_id: u64, max_trades: u32, keeper: Option<Address>) -> u32 {
+        let mut trades = 0;
+        let mut volume: Money = 0;
+        while trades < max_trades {
+            let (mut offer, mut order) = match Self::best_crossing(&env, property_id) {
+                Some(pair) => pair,
+                None => break,
+            };
+            
+            let shares = offer.shares.min(order.shares);
+            let price_per_share = if offer.offer_id < order.order_id { offer.price_per_share } else { order.price_per_share };
+            
+            Self::check_buyer_limits(&env, property_id, &offer.buyer, shares).unwrap_or_else(|error| panic_with_error!(&env, error));
+            Self::close_sell_order(&env, &order, shares);
+            Self::execute_transfer(&env, property_id, &order.seller, &offer.buyer, shares);
+            Self::pay_sale_proceeds(&env, property_id, &env.current_contract_address(), &order.seller, shares as Money * price_per_share);
+            Self::pay(&env, &env.current_contract_address(), &offer.buyer, shares as Money * (offer.price_per_share - price_per_share));
+            Self::record_trade_price(&env, property_id, shares, price_per_share, false);
+            Self::record_lot(&env, property_id, &offer.buyer, shares);
+            
+            order.shares -= shares;
+            if order.shares > 0 {
+                Self::store_record(&env, &MarketRegistry::SellOrder(order.order_id), &order);
+            }
+            offer.shares -= shares;
+            if offer.shares == 0 {
+                Self::remove_offer(&env, &offer);
+            } else {
+                Self::store_record(&env, &MarketRegistry::Offer(offer.offer_id), &offer);
+            }
+            
+            Self::publish_property_event(&env, symbol_short!("trade"), property_id, TradeEvent {
+                offer_id: offer.offer_id,
+                order_id: order.order_id,
+                buyer: offer.buyer.clone(),
+                seller: order.seller.clone(),
+                shares,
+                price_per_share,
+            });
+            volume = volume.saturating_add(shares as Money * price_per_share);
+            trades += 1;
+        }
+        if let Some(keeper) = keeper {
+            Self::reward_keeper(&env, &keeper, symbol_short!("match"), trades as u64, volume);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Matched {} trades for property {}", trades, property_id);
+        trades
+    }
+    
+    // Function to auction a block of shares to the highest bidder above a reserve for the whole block,
+    // ending after duration seconds
+    pub fn create_auction(env: Env, seller: Address, property_id: u64, shares: u64, reserve_price: Money, duration: u64) -> u64 {
+        // Authentication
+        seller.require_auth();
+        Self::record_activity(&env, &seller);
+        
+        if shares == 0 || reserve_price == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if duration == 0 {
+            panic!("Auction must have a duration");
+        }
+        Self::list_shares(&env, property_id, &seller, shares);
+        
+        let auction_id: u64 = env.storage().instance().get(&AUCTION_COUNTER).unwrap_or(0) + 1;
+        let auction = EnglishAuction {
+            auction_id,
+            property_id,
+            seller: seller.clone(),
+            shares,
+            reserve_price,
+            end_time: env.ledger().timestamp() + duration,
+            highest_bidder: None,
+            highest_bid: 0,
+        };
+        env.storage().instance().set(&AuctionRegistry::English(auction_id), &auction);
+        env.storage().instance().set(&AUCTION_COUNTER, &auction_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("auction"), property_id, auction);
+        log!(&env, "Auction {} created by {}", auction_id, seller);
+        auction_id
+    }
+    
+    // Function to bid on an auction; the bid is held by the contract and the previous highest
+    // bidder is refunded in the same call
+    pub fn place_bid(env: Env, bidder: Address, auction_id: u64, amount: Money) {
+        // Authentication
+        bidder.require_auth();
+        Self::record_activity(&env, &bidder);
+        
+        let mut auction = Self::get_auction(env.clone(), auction_id);
+        if env.ledger().timestamp() >= auction.end_time {
+            panic!("Auction ended");
+        }
+        if bidder == auction.seller {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        if amount < auction.reserve_price || amount <= auction.highest_bid {
+            panic!("Bid too low");
+        }
+        Self::check_buyer_limits(&env, auction.property_id, &bidder, auction.shares).unwrap_or_else(|error| panic_with_error!(&env, error));
+        
+        Self::pay(&env, &bidder, &env.current_contract_address(), amount);
+        if let Some(previous) = auction.highest_bidder.clone() {
+            Self::pay(&env, &env.current_contract_address(), &previous, auction.highest_bid);
+        }
+        auction.highest_bidder = Some(bidder.clone());
+        auction.highest_bid = amount;
+        env.storage().instance().set(&AuctionRegistry::English(auction_id), &auction);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("auct_bid"), auction.property_id, (auction_id, bidder.clone(), amount));
+        log!(&env, "{} bid {} on auction {}", bidder, amount, auction_id);
+    }
+    
+    // Function to close an auction after its end time; anyone may call it. The shares go to the
+    // highest bidder and the bid to the seller. If there was no bid, or the transfer checks now
+    // reject the winner, the bid is refunded and the seller keeps the shares. A keeper address
+    // collects the keeper reward.
+    pub fn settle_auction(env: Env, auction_id: u64, keeper: Option<Address>) -> Option<Address> {
+        let auction = Self::get_auction(env.clone(), auction_id);
+        if env.ledger().timestamp() < auction.end_time {
+            panic!("Auction still running");
+        }
+        
+        env.storage().instance().remove(&AuctionRegistry::English(auction_id));
+        Self::unlist_shares(&env, auction.property_id, &auction.seller, auction.shares);
+        
+        let mut winner = None;
+        if let Some(bidder) = auction.highest_bidder.clone() {
+            if Self::validate_transfer(&env, auction.property_id, &auction.seller, &bidder, auction.shares).is_ok()
+                && Self::check_buyer_limits(&env, auction.property_id, &bidder, auction.shares).is_ok()
+            {
+                Self::execute_transfer(&env, auction.property_id, &auction.seller, &bidder, auction.shares);
+                Self::pay_sale_proceeds(&env, auction.property_id, &env.current_contract_address(), &auction.seller, auction.highest_bid);
+                let price_per_share = auction.highest_bid.checked_div(auction.shares as Money).unwrap_or(0);
+                Self::record_trade_price(&env, auction.property_id, auction.shares, price_per_share, false);
+                Self::record_lot(&env, auction.property_id, &bidder, auction.shares);
+                winner = Some(bidder);
+            } else {
+                Self::pay(&env, &env.current_contract_address(), &bidder, auction.highest_bid);
+            }
+        }
+        if let Some(keeper) = keeper {
+            Self::reward_keeper(&env, &keeper, symbol_short!("auction"), 1, auction.highest_bid);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("auct_end"), auction.property_id, (auction_id, winner.clone(), auction.highest_bid));
+        log!(&env, "Auction {} settled", auction_id);
+        winner
+    }
+    
+    // Function to withdraw an auction that has no bids yet (seller only)
+    pub fn cancel_auction(env: Env, auction_id: u64) {
+        let auction = Self::get_auction(env.clone(), auction_id);
+        
+        // Check seller authorization
+        auction.seller.require_auth();
+        
+        if auction.highest_bidder.is_some() {
+            panic!("Auction has bids");
+        }
+        env.storage().instance().remove(&AuctionRegistry::English(auction_id));
+        Self::unlist_shares(&env, auction.property_id, &auction.seller, auction.shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Auction {} cancelled", auction_id);
+    }
+    
+    // Function to reserve shares at today's price and pay for them in num_payments equal installments,
+    // the first one now and each later one within INSTALLMENT_INTERVAL_SECONDS of the previous.
+    // The platform fee is spread across the installments. Returns the plan ID.
+    pub fn start_installment_purchase(env: Env, buyer: Address, property_id: u64, shares: u64, num_payments: u32) -> u64 {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        if !(2..=MAX_INSTALLMENTS).contains(&num_payments) {
+            panic!("Invalid number of installments");
+        }
+        if Self::get_funding_deadline(env.clone(), property_id).is_some() {
+            panic!("Crowdfunded properties cannot be bought in installments");
+        }
+        
+        let intent = PurchaseIntent {
+            property_id,
+            buyer: buyer.clone(),
+            shares,
+            locked_price: None,
+        };
+        let purchase = Self::validate_purchase(env.clone(), intent).unwrap_or_else(|error| panic_with_error!(&env, error));
+        
+        // The reservation holds the phase allocation and the supply until the plan closes
+        if let Some(phase_index) = purchase.phase_index {
+            env.storage().instance().set(&SalePhaseRegistry::PhaseSold(property_id, phase_index), &purchase.phase_sold);
+        }
+        let reserved = Self::get_reserved_shares(env.clone(), property_id);
+        env.storage().instance().set(&InstallmentRegistry::Reserved(property_id), &(reserved + shares));
+        
+        let plan_id: u64 = env.storage().instance().get(&INSTALLMENT_COUNTER).unwrap_or(0) + 1;
+        let total_payable = purchase.total_price + purchase.platform_fee;
+        let first_payment = Self::installment_amount(total_payable, num_payments, 0);
+        Self::pay(&env, &buyer, &env.current_contract_address(), first_payment);
+        
+        let plan = InstallmentPlan {
+            plan_id,
+            property_id,
+            buyer: buyer.clone(),
+            shares,
+            price_per_share: purchase.price_per_share,
+            platform_fee: purchase.platform_fee,
+            total_payable,
+            phase_index: purchase.phase_index,
+            num_payments,
+            payments_made: 1,
+            amount_paid: first_payment,
+            next_due: env.ledger().timestamp() + INSTALLMENT_INTERVAL_SECONDS,
+        };
+        Self::store_record(&env, &InstallmentRegistry::Plan(plan_id), &plan);
+        env.storage().instance().set(&INSTALLMENT_COUNTER, &plan_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("instal"), property_id, plan);
+        log!(&env, "{} reserved {} shares of property {} under installment plan {}", buyer, shares, property_id, plan_id);
+        plan_id
+    }
+    
+    // Function for the buyer to pay the next installment of a plan before it falls due. The final
+    // payment completes the purchase and credits the shares. Returns the payments made so far.
+    pub fn pay_installment(env: Env, plan_id: u64) -> u32 {
+        let mut plan = Self::get_installment_plan(env.clone(), plan_id);
+        
+        // Check buyer authorization
+        plan.buyer.require_auth();
+        Self::record_activity(&env, &plan.buyer);
+        
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(&env, Error::ContractPaused);
+        }
+        if env.ledger().timestamp() > plan.next_due {
+            panic!("Installment plan in default");
+        }
+        
+        let amount = Self::installment_amount(plan.total_payable, plan.num_payments, plan.payments_made);
+        Self::pay(&env, &plan.buyer, &env.current_contract_address(), amount);
+        plan.payments_made += 1;
+        plan.amount_paid += amount;
+        plan.next_due += INSTALLMENT_INTERVAL_SECONDS;
+        
+        if plan.payments_made < plan.num_payments {
+            Self::store_record(&env, &InstallmentRegistry::Plan(plan_id), &plan);
+        } else {
+            Self::complete_installments(&env, &plan);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Installment {} of {} paid on plan {}", plan.payments_made, plan.num_payments, plan_id);
+        plan.payments_made
+    }
+    
+    // Function to reclaim the shares of a plan whose buyer missed a payment; anyone may call it, with
+    // a keeper address collecting the keeper reward. The registrant keeps up to INSTALLMENT_FORFEIT_BPS
+    // of the total payable out of what was paid and the rest is refunded to the buyer. Returns the
+    // amount forfeited.
+    pub fn reclaim_reservation(env: Env, plan_id: u64, keeper: Option<Address>) -> Money {
+        let plan = Self::get_installment_plan(env.clone(), plan_id);
+        let registrant = Self::get_registrant(env.clone(), plan.property_id);
+        
+        if env.ledger().timestamp() <= plan.next_due {
+            panic!("Installment plan not in default");
+        }
+        
+        Self::release_reservation(&env, &plan);
+        let forfeited = plan.amount_paid.min(plan.total_payable * INSTALLMENT_FORFEIT_BPS as Money / 10000);
+        Self::pay(&env, &env.current_contract_address(), &registrant, forfeited);
+        Self::pay(&env, &env.current_contract_address(), &plan.buyer, plan.amount_paid - forfeited);
+        if let Some(keeper) = keeper {
+            Self::reward_keeper(&env, &keeper, symbol_short!("default"), 1, plan.amount_paid);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("default"), plan.property_id, (plan_id, plan.buyer.clone(), forfeited));
+        log!(&env, "Installment plan {} defaulted, {} forfeited", plan_id, forfeited);
+        forfeited
+    }
+    
+    // View function to get an open installment plan
+    pub fn get_installment_plan(env: Env, plan_id: u64) -> InstallmentPlan {
+        Self::load_record(&env, &InstallmentRegistry::Plan(plan_id)).expect("Installment plan not found")
+    }
+    
+    // View function to get an auction
+    pub fn get_auction(env: Env, auction_id: u64) -> EnglishAuction {
+        env.storage().instance().get(&AuctionRegistry::English(auction_id)).expect("Auction not found")
+    }
+    
+    // Function to change who receives a property's royalty (registrant only)
+    pub fn set_royalty_recipient(env: Env, property_id: u64, recipient: Address) {
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let key = MarketRegistry::Royalty(property_id);
+        let mut royalty: Royalty = env.storage().instance().get(&key).expect("Property has no royalty");
+        royalty.recipient = recipient.clone();
+        env.storage().instance().set(&key, &royalty);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "royalty_recipient"), property_id, recipient.clone());
+        log!(&env, "Royalty recipient of property {} set to {}", property_id, recipient);
+    }
+    
+    // View function to get a property's royalty, if it has one
+    pub fn get_royalty(env: Env, property_id: u64) -> Option<Royalty> {
+        env.storage().instance().get(&MarketRegistry::Royalty(property_id))
+    }
+    
+    // View function to page back through a property's retained trades, newest first; pass None
+    // for the first page
+    pub fn get_price_history(env: Env, property_id: u64, cursor: Option<Bytes>, limit: u32) -> PricePage {
+        let history: Vec<PricePoint> = env.storage().instance().get(&MarketRegistry::PriceHistory(property_id)).unwrap_or(Vec::new(&env));
+        let mut points = Vec::new(&env);
+        
+        let start = Self::decode_cursor(&env, CURSOR_PRICE_HISTORY, &cursor).min(history.len() as u64) as u32;
+        let end = history.len().min(start.saturating_add(limit));
+        for skipped in start..end {
+            points.push_back(history.get(history.len() - 1 - skipped).unwrap());
+        }
+        
+        PricePage {
+            items: points,
+            next_cursor: Self::next_cursor(&env, CURSOR_PRICE_HISTORY, end as u64, history.len() as u64),
+            total: Some(history.len() as u64),
+        }
+    }
+    
+    // View function to get the price per share of a property's latest trade
+    pub fn last_trade_price(env: Env, property_id: u64) -> Option<Money> {
+        let history: Vec<PricePoint> = env.storage().instance().get(&MarketRegistry::PriceHistory(property_id)).unwrap_or(Vec::new(&env));
+        history.last().map(|point| point.price_per_share)
+    }
+    
+    // View function to get a buy offer
+    pub fn get_offer(env: Env, offer_id: u64) -> BuyOffer {
+        Self::load_record(&env, &MarketRegistry::Offer(offer_id)).expect("Offer not found")
+    }
+    
+    // View function to get the IDs of a property's open offers, oldest first; expired ones stay
+    // listed until closed
+    pub fn get_offers(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&MarketRegistry::Bids(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get a sell order
+    pub fn get_sell_order(env: Env, order_id: u64) -> SellOrder {
+        Self::load_record(&env, &MarketRegistry::SellOrder(order_id)).expect("Order not found")
+    }
+    
+    // View function to get the IDs of a property's open sell orders, oldest first
+    pub fn get_sell_orders(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&MarketRegistry::Asks(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get how many of a holder's shares are listed in open sell orders and
+    // auctions or escrowed for scheduled transfers
+    pub fn get_listed_shares(env: Env, property_id: u64, owner: Address) -> u64 {
+        Self::load_record(&env, &MarketRegistry::Listed(property_id, owner)).unwrap_or(0)
+    }
+    
+    // Function to award the badges that depend on elapsed time; anyone may call it for an owner.
+    // Returns the badges newly awarded by this call.
+    pub fn claim_time_based_badges(env: Env, owner: Address) -> Vec<Badge> {
+        let mut awarded = Vec::new(&env);
+        
+        let now = env.ledger().timestamp();
+        for property_id in Self::get_user_properties(env.clone(), owner.clone()).iter() {
+            let ownership = Self::get_ownership(env.clone(), property_id, owner.clone());
+            if ownership.shares > 0 && now >= ownership.purchase_time + DIAMOND_HANDS_SECONDS {
+                if Self::award_badge(&env, &owner, Badge::DiamondHands) {
+                    awarded.push_back(Badge::DiamondHands);
+                }
+                break;
+            }
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        awarded
+    }
+    
+    // Function to move a compromised account's holdings to a new address, at most max_items
+    // properties per call. The first call marks old as migrated, so it stops receiving shares,
+    // and carries over its account-level settings; pass back the returned cursor until None.
+    pub fn migrate_account(env: Env, old: Address, new: Address, cursor: u32, max_items: u32) -> Option<u32> {
+        // Authentication
+        old.require_auth();
+        
+        Self::validate_movement(&env, Some(&old), &new, 1);
+        if Self::get_migrated_to(env.clone(), new.clone()).is_some() {
+            panic_with_error!(&env, Error::AccountMigrated);
+        }
+        
+        let migrated_key = MigrationRegistry::MigratedTo(old.clone());
+        match Self::get_migrated_to(env.clone(), old.clone()) {
+            Some(target) if target != new => panic!("Account already migrated to another address"),
+            Some(_) => {}
+            None => {
+                if cursor != 0 {
+                    panic!("Migration must start at cursor 0");
+                }
+                Self::store_record(&env, &migrated_key, &new);
+                Self::migrate_account_settings(&env, &old, &new);
+            }
+        }
+        
+        let properties = Self::get_user_properties(env.clone(), old.clone());
+        let end = properties.len().min(cursor.saturating_add(max_items));
+        for index in cursor..end {
+            Self::migrate_position(&env, properties.get(index).unwrap(), &old, &new);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Migrated positions {} to {} of {} to {}", cursor, end, old, new);
+        
+        if end < properties.len() { Some(end) } else { None }
+    }
+    
+    // Function to set whether an address accepts unsolicited share transfers
+    pub fn set_receive_policy(env: Env, address: Address, policy: ReceivePolicy) {
+        address.require_auth();
+        Self::record_activity(&env, &address);
+        
+        Self::store_record(&env, &ReceiveRegistry::Policy(address.clone()), &policy);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Receive policy updated for {}", address);
+    }
+    
+    // Function to opt in to receiving shares of a property
+    pub fn opt_in_property(env: Env, address: Address, property_id: u64) {
+        address.require_auth();
+        Self::record_activity(&env, &address);
+        
+        // Ensure property exists
+        Self::get_property(env.clone(), property_id);
+        
+        Self::store_record(&env, &ReceiveRegistry::OptIn(address.clone(), property_id), &true);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} opted in to property {}", address, property_id);
+    }
+    
+    // Function to withdraw a previous opt-in for a property
+    pub fn opt_out_property(env: Env, address: Address, property_id: u64) {
+        address.require_auth();
+        Self::record_activity(&env, &address);
+        
+        Self::remove_record(&env, &ReceiveRegistry::OptIn(address.clone(), property_id));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} opted out of property {}", address, property_id);
+    }
+    
+    // Function to let holders redeem shares for usage nights (registrant only)
+    pub fn configure_redemption(env: Env, property_id: u64, shares_per_night: u64, max_nights_per_year: u32) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if shares_per_night == 0 || max_nights_per_year == 0 || max_nights_per_year > 366 {
+            panic!("Invalid redemption terms");
+        }
+        
+        let config = RedemptionConfig { shares_per_night, max_nights_per_year };
+        env.storage().instance().set(&RedemptionRegistry::Config(property_id), &config);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Redemption configured for property {}: {} shares per night", property_id, shares_per_night);
+    }
+    
+    // Function to book usage nights starting on the day containing start_ts.
+    // Shares are not burned, but nights * shares_per_night of the holder's shares are locked until
+    // the stay ends, so the same shares cannot be booked twice or moved to another holder to book.
+    pub fn redeem_usage(env: Env, property_id: u64, owner: Address, nights: u32, start_ts: u64) -> u64 {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let config: RedemptionConfig = env.storage().instance().get(&RedemptionRegistry::Config(property_id))
+            .expect("Redemption not enabled for this property");
+        
+        if nights == 0 || nights > config.max_nights_per_year {
+            panic!("Invalid number of nights");
+        }
+        
+        let start_day = start_ts / SECONDS_PER_DAY;
+        let end_day = start_day + nights as u64;
+        if start_day < env.ledger().timestamp() / SECONDS_PER_DAY {
+            panic!("Cannot book nights in the past");
+        }
+        
+        // Ensure the holder's free balance covers the stay
+        let mut booking_ids = Self::prune_bookings(&env, property_id);
+        let shares = nights as u64 * config.shares_per_night;
+        let balance = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+        let committed = Self::get_listed_shares(env.clone(), property_id, owner.clone())
+            + Self::get_locked_shares(env.clone(), property_id, owner.clone())
+            + Self::get_unvested_shares(env.clone(), property_id, owner.clone())
+            + Self::get_booked_shares(env.clone(), property_id, owner.clone());
+        if balance < committed + shares {
+            panic!("Insufficient shares for this booking");
+        }
+        
+        // Reject any overlap with existing bookings (adjacent stays are fine)
+        for booking_id in booking_ids.iter() {
+            let existing = Self::get_booking(env.clone(), booking_id);
+            if existing.start_day < end_day && start_day < existing.start_day + existing.nights as u64 {
+                panic!("Booking overlaps an existing booking");
+            }
+        }
+        
+        // Charge each night to the calendar year it falls in, enforcing the yearly cap
+        for day in start_day..end_day {
+            let used_key = RedemptionRegistry::NightsUsed(property_id, owner.clone(), Self::year_of_day(day));
+            let used: u32 = Self::load_record(&env, &used_key).unwrap_or(0);
+            if used + 1 > config.max_nights_per_year {
+                panic!("Yearly night limit exceeded");
+            }
+            Self::store_record(&env, &used_key, &(used + 1));
+        }
+        
+        // Record the booking
+        let mut booking_counter: u64 = env.storage().instance().get(&BOOKING_COUNTER).unwrap_or(0);
+        booking_counter += 1;
+        
+        let booking = Booking {
+            booking_id: booking_counter,
+            property_id,
+            owner: owner.clone(),
+            start_day,
+            nights,
+            shares,
+            booked_at: env.ledger().timestamp(),
+        };
+        booking_ids.push_back(booking_counter);
+        
+        Self::store_record(&env, &RedemptionRegistry::Booking(booking_counter), &booking);
+        Self::store_record(&env, &RedemptionRegistry::PropertyBookings(property_id), &booking_ids);
+        env.storage().instance().set(&BOOKING_COUNTER, &booking_counter);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} booked {} nights at property {}", owner, nights, property_id);
+        
+        booking_counter
+    }
+    
+    // Function to cancel a booking before it starts, freeing its dates and yearly allowance
+    pub fn cancel_booking(env: Env, booking_id: u64) {
+        let booking = Self::get_booking(env.clone(), booking_id);
+        
+        // Authentication
+        booking.owner.require_auth();
+        Self::record_activity(&env, &booking.owner);
+        
+        if booking.start_day <= env.ledger().timestamp() / SECONDS_PER_DAY {
+            panic!("Cannot cancel a booking that has started");
+        }
+        
+        // Return the nights to the yearly allowance
+        for day in booking.start_day..booking.start_day + booking.nights as u64 {
+            let used_key = RedemptionRegistry::NightsUsed(booking.property_id, booking.owner.clone(), Self::year_of_day(day));
+            let used: u32 = Self::load_record(&env, &used_key).unwrap_or(0);
+            Self::store_record(&env, &used_key, &used.saturating_sub(1));
+        }
+        
+        // Free the dates and the shares
+        let mut booking_ids = Self::prune_bookings(&env, booking.property_id);
+        if let Some(index) = booking_ids.first_index_of(booking_id) {
+            booking_ids.remove(index);
+        }
+        
+        Self::store_record(&env, &RedemptionRegistry::PropertyBookings(booking.property_id), &booking_ids);
+        Self::remove_record(&env, &RedemptionRegistry::Booking(booking_id));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Booking {} cancelled", booking_id);
+    }
+    
+    // View function to get a locked quote, if it still exists
+    pub fn get_quote(env: Env, quote_id: u64) -> Option<PriceQuote> {
+        env.storage().temporary().get(&QuoteRegistry::Quote(quote_id))
+    }
+    
+    // View function to price a purchase at the current sale price, including the platform fee.
+    // Purchase restrictions are not checked; use validate_purchase for that.
+    pub fn quote_purchase(env: Env, property_id: u64, shares: u64) -> PurchaseQuote {
+        let property = Self::get_property(env.clone(), property_id);
+        let price_per_share = Self::current_price(&env, &property);
+        let base_cost = (shares as Money).checked_mul(price_per_share).expect("Amount overflow");
+        let platform_fee = Self::platform_fee(&env, base_cost);
+        PurchaseQuote {
+            property_id,
+            shares,
+            price_per_share,
+            base_cost,
+            platform_fee,
+            total_payable: base_cost + platform_fee,
+            available_shares: Self::get_available_shares(env.clone(), property_id),
+        }
+    }
+    
+    // View function to get property details
+    pub fn get_property(env: Env, property_id: u64) -> PropertyV2 {
+        Self::load_property(&env, property_id).expect("Property not found")
+    }
+    
+    // View function to get the details of several properties in one call, skipping unknown IDs
+    pub fn get_properties(env: Env, ids: Vec<u64>) -> Vec<PropertyV2> {
+        if ids.len() > MAX_BATCH_ITEMS {
+            panic!("Too many property IDs");
+        }
+        
+        let mut properties = Vec::new(&env);
+        for property_id in ids.iter() {
+            if let Some(property) = Self::load_property(&env, property_id) {
+                properties.push_back(property);
+            }
+        }
+        properties
+    }
+    
+    // View function to get the lifecycle stage of a property
+    pub fn get_property_status(env: Env, property_id: u64) -> PropertyStatus {
+        if let Some(status) = env.storage().instance().get(&PropertyRegistry::Withdrawn(property_id)) {
+            return status;
+        }
+        
+        let property = Self::get_property(env.clone(), property_id);
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id);
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            PropertyStatus::Archived
+        } else if !property.is_verified || Self::is_funding_failed(&env, property_id) {
+            PropertyStatus::Draft
+        } else if shares_sold >= property.total_shares
+            || Self::is_offering_finalized(env.clone(), property_id)
+            || Self::get_deed(env.clone(), property_id).is_some()
+        {
+            PropertyStatus::Sold
+        } else if shares_sold > 0 {
+            PropertyStatus::Active
+        } else {
+            PropertyStatus::Verified
+        }
+    }
+    
+    // View function to get the latest rejection or revocation of a property, if it is still in force
+    pub fn get_review(env: Env, property_id: u64) -> Option<PropertyReview> {
+        env.storage().instance().get(&VerificationRegistry::Review(property_id))
+    }
+    
+    // View function to get the requirement codes checked before verifying a property of a type
+    pub fn get_verification_checklist(env: Env, property_type: PropertyType) -> Vec<Symbol> {
+        env.storage().instance().get(&VerificationRegistry::Checklist(property_type)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get each checklist requirement of a property with its completion, if any
+    pub fn get_verification_progress(env: Env, property_id: u64) -> Vec<RequirementStatus> {
+        Self::get_property(env.clone(), property_id);
+        
+        let mut progress = Vec::new(&env);
+        for code in Self::property_checklist(&env, property_id).iter() {
+            let completion: Option<RequirementCompletion> = env.storage().instance().get(&VerificationRegistry::Completed(property_id, code.clone()));
+            progress.push_back(match completion {
+                Some(completion) => RequirementStatus {
+                    code,
+                    is_completed: true,
+                    completed_by: Some(completion.completed_by),
+                    completed_at: completion.completed_at,
+                    evidence_uri: Some(completion.evidence_uri),
+                },
+                None => RequirementStatus {
+                    code,
+                    is_completed: false,
+                    completed_by: None,
+                    completed_at: 0,
+                    evidence_uri: None,
+                },
+            });
+        }
+        progress
+    }
+    
+    // View function to get a property's metadata value for a key, if set
+    pub fn get_property_metadata(env: Env, property_id: u64, key: Symbol) -> Option<Bytes> {
+        Self::load_record(&env, &MetadataRegistry::Value(property_id, key))
+    }
+    
+    // View function to get a property's metadata keys in the order they were first set
+    pub fn list_metadata_keys(env: Env, property_id: u64) -> Vec<Symbol> {
+        Self::load_record(&env, &MetadataRegistry::Keys(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the bytes of metadata currently stored on a property
+    pub fn get_metadata_size(env: Env, property_id: u64) -> u32 {
+        Self::load_record(&env, &MetadataRegistry::TotalSize(property_id)).unwrap_or(0)
+    }
+    
+    // View function to check whether the contract-wide pause is on
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&CONTRACT_PAUSED).unwrap_or(false)
+    }
+    
+    // View function to check whether buyers and recipients must be on the KYC allowlist
+    pub fn is_kyc_required(env: Env) -> bool {
+        env.storage().instance().get(&KYC_REQUIRED).unwrap_or(false)
+    }
+    
+    // View function to get an investor's KYC approval, if any
+    pub fn get_investor_approval(env: Env, investor: Address) -> Option<InvestorApproval> {
+        Self::load_record(&env, &KycRegistry::Investor(investor))
+    }
+    
+    // View function to get a property's eligibility rules, if it has any
+    pub fn get_eligibility_rules(env: Env, property_id: u64) -> Option<EligibilityRules> {
+        env.storage().instance().get(&KycRegistry::Eligibility(property_id))
+    }
+    
+    // View function to explain why an address may not acquire a property's shares: "kyc" when it
+    // is not an approved investor, "tier" when its tier is too low and "region" when its
+    // jurisdiction is not allowed. None when it is eligible.
+    pub fn check_eligibility(env: Env, property_id: u64, account: Address) -> Option<Symbol> {
+        Self::eligibility_blocker(&env, property_id, &account)
+    }
+    
+    // View function to check whether an address holds a role
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        env.storage().instance().has(&RoleRegistry::Member(role, account))
+    }
+    
+    // View function to get the token purchases are paid in
+    pub fn get_payment_token(env: Env) -> Address {
+        env.storage().instance().get(&PAYMENT_TOKEN).expect("Contract not initialized")
+    }
+    
+    // View function to get the configured compliance hook, if any
+    pub fn get_compliance_hook(env: Env) -> Option<ComplianceHook> {
+        env.storage().instance().get(&COMPLIANCE_HOOK)
+    }
+    
+    // View function to get the property ID assignment configuration, if changed from the default
+    pub fn get_id_config(env: Env) -> Option<IdConfig> {
+        env.storage().instance().get(&ID_CONFIG)
+    }
+    
+    // View function to check whether purchases and transfers require authorization bound to their terms
+    pub fn is_strict_auth(env: Env) -> bool {
+        env.storage().instance().get(&STRICT_AUTH).unwrap_or(false)
+    }
+    
+    // View function to check whether the contract is still a wipeable sandbox
+    pub fn is_sandbox(env: Env) -> bool {
+        env.storage().instance().get(&SANDBOX_MODE).unwrap_or(false)
+    }
+    
+    // View function to check whether the contract is currently importing legacy state
+    pub fn is_import_mode(env: Env) -> bool {
+        env.storage().instance().get(&IMPORT_MODE).unwrap_or(false)
+    }
+    
+    // View function to get the configured sale phases of a property
+    pub fn get_sale_phases(env: Env, property_id: u64) -> Vec<SalePhase> {
+        env.storage().instance().get(&SalePhaseRegistry::Phases(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the sale phase active at the current ledger time
+    pub fn get_active_phase(env: Env, property_id: u64) -> Option<SalePhase> {
+        let phases = Self::get_sale_phases(env.clone(), property_id);
+        Self::active_phase_index(&phases, env.ledger().timestamp()).map(|index| phases.get(index).unwrap())
+    }
+    
+    // View function to get the number of shares sold in a given sale phase
+    pub fn get_phase_sold(env: Env, property_id: u64, phase_index: u32) -> u64 {
+        env.storage().instance().get(&SalePhaseRegistry::PhaseSold(property_id, phase_index)).unwrap_or(0)
+    }
+    
+    // View function to check whether an address is whitelisted for a property's phases
+    pub fn is_phase_whitelisted(env: Env, property_id: u64, investor: Address) -> bool {
+        Self::has_record(&env, &SalePhaseRegistry::Whitelisted(property_id, investor))
+    }
+    
+    // View function to get bundle composition
+    pub fn get_bundle(env: Env, bundle_id: u64) -> Bundle {
+        env.storage().instance().get(&BundleRegistry::Bundle(bundle_id)).expect("Bundle not found")
+    }
+    
+    // View function to get a holder's bundle share balance
+    pub fn get_bundle_balance(env: Env, bundle_id: u64, owner: Address) -> u64 {
+        Self::load_record(&env, &BundleRegistry::Balance(bundle_id, owner)).unwrap_or(0)
+    }
+    
+    // View function to get how many more bundle shares can be bought, limited by the scarcest component
+    pub fn get_bundle_available(env: Env, bundle_id: u64) -> u64 {
+        let bundle = Self::get_bundle(env.clone(), bundle_id);
+        let mut available = u64::MAX;
+        for (property_id, weight) in bundle.components.iter() {
+            available = available.min(Self::get_available_shares(env.clone(), property_id) / weight);
+        }
+        available
+    }
+    
+    // View function to get the badges awarded to an address, in award order
+    pub fn get_badges(env: Env, owner: Address) -> Vec<Badge> {
+        Self::load_record(&env, &BadgeRegistry::Badges(owner)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the address an account was migrated to, if any
+    pub fn get_migrated_to(env: Env, account: Address) -> Option<Address> {
+        Self::load_record(&env, &MigrationRegistry::MigratedTo(account))
+    }
+    
+    // View function to get an owner's inheritance plan, if any
+    pub fn get_inheritance_plan(env: Env, owner: Address) -> Option<InheritancePlan> {
+        Self::load_record(&env, &InheritanceRegistry::Plan(owner))
+    }
+    
+    // View function to get an address's receive policy (Open unless set otherwise)
+    pub fn get_receive_policy(env: Env, address: Address) -> ReceivePolicy {
+        Self::load_record(&env, &ReceiveRegistry::Policy(address)).unwrap_or(ReceivePolicy::Open)
+    }
+    
+    // View function to check whether an address has opted in to a property
+    pub fn is_opted_in(env: Env, address: Address, property_id: u64) -> bool {
+        Self::has_record(&env, &ReceiveRegistry::OptIn(address, property_id))
+    }
+    
+    // View function to check whether an address currently accepts transfers of a property.
+    // Accounts already holding the property are never surprised by more of it, so a
+    // positive balance counts as an opt-in.
+    pub fn can_receive(env: Env, address: Address, property_id: u64) -> bool {
+        if Self::get_receive_policy(env.clone(), address.clone()) == ReceivePolicy::Open {
+            return true;
+        }
+        Self::is_opted_in(env.clone(), address.clone(), property_id)
+            || Self::get_ownership(env, property_id, address).shares > 0
+    }
+    
+    // View function to get the recorded outcome of a request submitted with an idempotency key,
+    // so a client whose retry was rejected as a duplicate can reconcile with the original call
+    pub fn get_request_record(env: Env, caller: Address, idempotency_key: BytesN<32>) -> Option<IdempotencyRecord> {
+        env.storage().temporary().get(&IdempotencyRegistry::Request(caller, idempotency_key))
+    }
+    
+    // View function to get the founder allocation of a property, if any
+    pub fn get_founder_allocation(env: Env, property_id: u64) -> Option<FounderAllocation> {
+        env.storage().instance().get(&SupplyRegistry::Founder(property_id))
+    }
+    
+    // View function to get a crowdfunded property's funding deadline
+    pub fn get_funding_deadline(env: Env, property_id: u64) -> Option<u64> {
+        env.storage().instance().get(&FundingRegistry::Deadline(property_id))
+    }
+    
+    // View function to get the payments a crowdfunded property holds until its goal is reached
+    pub fn get_funds_raised(env: Env, property_id: u64) -> Money {
+        env.storage().instance().get(&FundingRegistry::Raised(property_id)).unwrap_or(0)
+    }
+    
+    // View function to check whether a crowdfunded property sold every share before its deadline
+    pub fn is_funded(env: Env, property_id: u64) -> bool {
+        env.storage().instance().has(&FundingRegistry::Funded(property_id))
+    }
+    
+    // View function to check whether a property routes primary-sale proceeds into milestone escrow
+    pub fn is_milestone_escrow(env: Env, property_id: u64) -> bool {
+        env.storage().instance().has(&EscrowRegistry::Enabled(property_id))
+    }
+    
+    // View function to get a property's milestones and their release status
+    pub fn get_milestones(env: Env, property_id: u64) -> Vec<Milestone> {
+        env.storage().instance().get(&EscrowRegistry::Milestones(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the proceeds still held in a property's escrow
+    pub fn get_escrow_balance(env: Env, property_id: u64) -> Money {
+        let proceeds: Money = env.storage().instance().get(&EscrowRegistry::Proceeds(property_id)).unwrap_or(0);
+        let released: Money = env.storage().instance().get(&EscrowRegistry::Released(property_id)).unwrap_or(0);
+        let refunded: Money = env.storage().instance().get(&EscrowRegistry::Refunded(property_id)).unwrap_or(0);
+        proceeds - released - refunded
+    }
+    
+    // View function to get the treasury allocation made when a property's offering closed, if any
+    pub fn get_treasury_allocation(env: Env, property_id: u64) -> Option<TreasuryAllocation> {
+        env.storage().instance().get(&SupplyRegistry::Treasury(property_id))
+    }
+    
+    // View function to check whether a property's offering has been finalized
+    pub fn is_offering_finalized(env: Env, property_id: u64) -> bool {
+        env.storage().instance().has(&SupplyRegistry::OfferingClosed(property_id))
+    }
+    
+    // View function to get the number of shares already allocated (sold or founder-held)
+    pub fn get_shares_sold(env: Env, property_id: u64) -> u64 {
+        env.storage().instance().get(&SupplyRegistry::SharesSold(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get the number of shares still available for sale
+    pub fn get_available_shares(env: Env, property_id: u64) -> u64 {
+        let property = Self::get_property(env.clone(), property_id);
+        if Self::get_deed(env.clone(), property_id).is_some() {
+            return 0;
+        }
+        property.total_shares
+            .saturating_sub(Self::get_shares_sold(env.clone(), property_id))
+            .saturating_sub(Self::get_reserved_shares(env, property_id))
+    }
+    
+    // Function to set a property's minimum shares per purchase and maximum shares per wallet,
+    // zero meaning no limit (registrant only). Applies to primary purchases and marketplace fills,
+    // and the wallet cap also to incoming transfers.
+    pub fn set_purchase_limits(env: Env, property_id: u64, min_shares_per_purchase: u64, max_shares_per_wallet: u64) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if max_shares_per_wallet > 0 && max_shares_per_wallet < min_shares_per_purchase {
+            panic!("Wallet cap is below the minimum purchase");
+        }
+        let limits = PurchaseLimits { min_shares_per_purchase, max_shares_per_wallet };
+        env.storage().instance().set(&SupplyRegistry::Limits(property_id), &limits);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("limits"), property_id, limits);
+        log!(&env, "Purchase limits of property {} set to {} min, {} max", property_id, min_shares_per_purchase, max_shares_per_wallet);
+    }
+    
+    // Function to set how long shares bought in a purchase or marketplace fill stay
+    // non-transferable and cannot be listed, zero for no lockup (registrant only)
+    pub fn set_lockup_period(env: Env, property_id: u64, lockup_seconds: u64) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        env.storage().instance().set(&LockupRegistry::Period(property_id), &lockup_seconds);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("lockup"), property_id, lockup_seconds);
+        log!(&env, "Lockup period of property {} set to {} seconds", property_id, lockup_seconds);
+    }
+    
+    // View function to get a property's post-purchase lockup period in seconds
+    pub fn get_lockup_period(env: Env, property_id: u64) -> u64 {
+        env.storage().instance().get(&LockupRegistry::Period(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get how many of a holder's shares are still inside the lockup period
+    pub fn get_locked_shares(env: Env, property_id: u64, owner: Address) -> u64 {
+        let lockup = Self::get_lockup_period(env.clone(), property_id);
+        let now = env.ledger().timestamp();
+        let lots: Vec<ShareLot> = Self::load_record(&env, &LockupRegistry::Lots(property_id, owner)).unwrap_or(Vec::new(&env));
+        lots.iter()
+            .filter(|lot| lot.acquired_at.saturating_add(lockup) > now)
+            .map(|lot| lot.shares)
+            .sum()
+    }
+    
+    // Function to grant unsold shares that vest linearly over duration_seconds after a cliff
+    // (registrant only). The shares are credited at once but only vested ones can be transferred
+    // or listed.
+    pub fn grant_vested_shares(env: Env, property_id: u64, to: Address, shares: u64, cliff_seconds: u64, duration_seconds: u64) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if duration_seconds == 0 || cliff_seconds > duration_seconds {
+            panic!("Invalid vesting schedule");
+        }
+        if Self::is_funding_open(&env, property_id) {
+            panic!("Funding goal not reached");
+        }
+        if shares > Self::get_available_shares(env.clone(), property_id) {
+            panic_with_error!(&env, Error::SupplyExceeded);
+        }
+        
+        let timestamp = env.ledger().timestamp();
+        let existing = Self::load_ownership(&env, property_id, &to);
+        let ownership = match existing.clone() {
+            Some(existing) => OwnershipShare {
+                shares: existing.shares + shares,
+                last_updated: timestamp,
+                ..existing
+            },
+            None => OwnershipShare {
+                property_id,
+                owner: to.clone(),
+                shares,
+                purchase_time: timestamp,
+                last_updated: timestamp,
+            },
+        };
+        if existing.is_none() {
+            Self::add_user_property(&env, &to, property_id);
+            let mut stats = Self::get_property_stats(env.clone());
+            stats.total_owners += 1;
+            env.storage().instance().set(&PROPERTY_STATS, &stats);
+        }
+        Self::store_ownership(&env, &ownership);
+        Self::advance_income_clock(&env, property_id);
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id);
+        env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &(shares_sold + shares));
+        Self::add_cached_total(&env, &to, shares);
+        
+        let schedule = VestingSchedule {
+            shares,
+            start: timestamp,
+            cliff_seconds,
+            duration_seconds,
+        };
+        let grants_key = VestingRegistry::Grants(property_id, to.clone());
+        let mut grants = Self::get_vesting_schedules(env.clone(), property_id, to.clone());
+        grants.push_back(schedule.clone());
+        Self::store_record(&env, &grants_key, &grants);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("vest"), property_id, (to.clone(), schedule));
+        log!(&env, "Granted {} vesting shares of property {} to {}", shares, property_id, to);
+    }
+    
+    // View function to get a holder's vesting grants for a property
+    pub fn get_vesting_schedules(env: Env, property_id: u64, owner: Address) -> Vec<VestingSchedule> {
+        Self::load_record(&env, &VestingRegistry::Grants(property_id, owner)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get how many of a holder's granted shares have not vested yet
+    pub fn get_unvested_shares(env: Env, property_id: u64, owner: Address) -> u64 {
+        let now = env.ledger().timestamp();
+        Self::get_vesting_schedules(env.clone(), property_id, owner)
+            .iter()
+            .map(|schedule| {
+                let elapsed = now.saturating_sub(schedule.start);
+                let vested = if elapsed < schedule.cliff_seconds {
+                    0
+                } else if elapsed >= schedule.duration_seconds {
+                    schedule.shares
+                } else {
+                    (schedule.shares as u128 * elapsed as u128 / schedule.duration_seconds as u128) as u64
+                };
+                schedule.shares - vested
+            })
+            .sum()
+    }
+    
+    // Function to add a share class to a property (registrant only). Returns the class ID;
+    // classes are numbered from 1 as class 0 is the property's ordinary shares.
+    pub fn create_share_class(env: Env, property_id: u64, name: Symbol, total_shares: u64, price_per_share: Money, voting: bool) -> u32 {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if total_shares == 0 || price_per_share == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        
+        let mut classes = Self::get_share_classes(env.clone(), property_id);
+        let share_class = ShareClass {
+            class_id: classes.len() + 1,
+            name,
+            total_shares,
+            price_per_share,
+            voting,
+        };
+        classes.push_back(share_class.clone());
+        env.storage().instance().set(&ClassRegistry::Classes(property_id), &classes);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("class"), property_id, share_class.clone());
+        log!(&env, "Share class {} added to property {}", share_class.class_id, property_id);
+        share_class.class_id
+    }
+    
+    // Function to buy shares of an additional class at its price plus the platform fee
+    pub fn purchase_class_shares(env: Env, buyer: Address, property_id: u64, class_id: u32, shares: u64) {
+        // Authentication
+        buyer.require_auth();
+        Self::record_activity(&env, &buyer);
+        
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(&env, Error::ContractPaused);
+        }
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        let property = Self::get_property(env.clone(), property_id);
+        if !property.is_verified {
+            panic_with_error!(&env, Error::PropertyNotVerified);
+        }
+        if property.is_paused {
+            panic_with_error!(&env, Error::PropertyPaused);
+        }
+        if Self::is_withdrawn(&env, property_id) {
+            panic_with_error!(&env, Error::PropertyDelisted);
+        }
+        if let Some(reason) = Self::eligibility_blocker(&env, property_id, &buyer) {
+            panic_with_error!(&env, Self::transfer_error(&reason));
+        }
+        
+        let share_class = Self::get_share_class(&env, property_id, class_id);
+        let sold_key = ClassRegistry::Sold(property_id, class_id);
+        let sold: u64 = env.storage().instance().get(&sold_key).unwrap_or(0);
+        if sold + shares > share_class.total_shares {
+            panic_with_error!(&env, Error::SupplyExceeded);
+        }
+        let cost = (shares as Money).checked_mul(share_class.price_per_share).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        
+        let balance = Self::get_class_balance(env.clone(), property_id, class_id, buyer.clone());
+        Self::set_class_balance(&env, property_id, class_id, &buyer, balance, balance + shares);
+        env.storage().instance().set(&sold_key, &(sold + shares));
+        
+        Self::route_proceeds(&env, property_id, &buyer, &buyer, cost, Self::get_shares_sold(env.clone(), property_id));
+        let fee = Self::platform_fee(&env, cost);
+        if fee > 0 {
+            Self::pay(&env, &buyer, &env.current_contract_address(), fee);
+            let fees = Self::get_fee_balance(env.clone());
+            env.storage().instance().set(&FEE_BALANCE, &(fees + fee));
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "class_purchased"), property_id, (buyer.clone(), class_id, shares, cost));
+        log!(&env, "{} bought {} class {} shares of property {}", buyer, shares, class_id, property_id);
+    }
+    
+    // Function to transfer shares of an additional class, subject to the property's pause and
+    // the recipient's eligibility
+    pub fn transfer_class_shares(env: Env, property_id: u64, class_id: u32, from: Address, to: Address, shares: u64) {
+        // Authentication
+        from.require_auth();
+        Self::record_activity(&env, &from);
+        
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if from == to {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(&env, Error::ContractPaused);
+        }
+        if Self::get_property(env.clone(), property_id).is_paused {
+            panic_with_error!(&env, Error::PropertyPaused);
+        }
+        if let Some(reason) = Self::eligibility_blocker(&env, property_id, &to) {
+            panic_with_error!(&env, Self::transfer_error(&reason));
+        }
+        Self::get_share_class(&env, property_id, class_id);
+        
+        let from_balance = Self::get_class_balance(env.clone(), property_id, class_id, from.clone());
+        if from_balance < shares {
+            panic_with_error!(&env, Error::InsufficientShares);
+        }
+        let to_balance = Self::get_class_balance(env.clone(), property_id, class_id, to.clone());
+        Self::set_class_balance(&env, property_id, class_id, &from, from_balance, from_balance - shares);
+        Self::set_class_balance(&env, property_id, class_id, &to, to_balance, to_balance + shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "class_transferred"), property_id, (from.clone(), to.clone(), class_id, shares));
+        log!(&env, "{} class {} shares of property {} moved from {} to {}", shares, class_id, property_id, from, to);
+    }
+    
+    // Function to deposit income for the holders of one additional share class, split by their
+    // balances in that class (property manager or admin)
+    pub fn deposit_class_income(env: Env, depositor: Address, property_id: u64, class_id: u32, amount: Money) {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &depositor) {
+            panic!("Not authorized to deposit income");
+        }
+        depositor.require_auth();
+        
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        Self::get_share_class(&env, property_id, class_id);
+        let sold: u64 = env.storage().instance().get(&ClassRegistry::Sold(property_id, class_id)).unwrap_or(0);
+        if sold == 0 {
+            panic!("Share class has no holders");
+        }
+        
+        Self::pay(&env, &depositor, &env.current_contract_address(), amount);
+        let distributable = amount - Self::accrue_management_fee(&env, property_id, amount);
+        let scaled = distributable.checked_mul(DIVIDEND_PRECISION).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        let key = ClassRegistry::AccPerShare(property_id, class_id);
+        let acc_per_share: u128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(acc_per_share + scaled / sold as u128));
+        
+        Self::add_to_period(&env, property_id, AccountingRegistry::Income(property_id, Self::current_period(&env)), amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "class_income"), property_id, (depositor, class_id, amount));
+        log!(&env, "Income of {} deposited for class {} of property {}", amount, class_id, property_id);
+    }
+    
+    // Function to pay out a holder's income from one additional share class
+    pub fn claim_class_dividends(env: Env, property_id: u64, class_id: u32, owner: Address) -> Money {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let balance = Self::get_class_balance(env.clone(), property_id, class_id, owner.clone());
+        Self::settle_class_dividends(&env, property_id, class_id, &owner, balance);
+        let pending_key = ClassRegistry::Pending(property_id, class_id, owner.clone());
+        let amount: Money = Self::load_record(&env, &pending_key).unwrap_or(0);
+        if amount == 0 {
+            panic!("Nothing to claim");
+        }
+        Self::remove_record(&env, &pending_key);
+        Self::pay(&env, &env.current_contract_address(), &owner, amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("dividend"), property_id, (owner.clone(), amount));
+        log!(&env, "{} claimed {} in class {} income from property {}", owner, amount, class_id, property_id);
+        amount
+    }
+    
+    // View function to get a property's additional share classes
+    pub fn get_share_classes(env: Env, property_id: u64) -> Vec<ShareClass> {
+        env.storage().instance().get(&ClassRegistry::Classes(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get a holder's balance in an additional share class
+    pub fn get_class_balance(env: Env, property_id: u64, class_id: u32, owner: Address) -> u64 {
+        Self::load_record(&env, &ClassRegistry::Balance(property_id, class_id, owner)).unwrap_or(0)
+    }
+    
+    // View function to get how many shares of an additional class have been sold
+    pub fn get_class_shares_sold(env: Env, property_id: u64, class_id: u32) -> u64 {
+        env.storage().instance().get(&ClassRegistry::Sold(property_id, class_id)).unwrap_or(0)
+    }
+    
+    // View function to get a property's purchase limits
+    pub fn get_purchase_limits(env: Env, property_id: u64) -> PurchaseLimits {
+        env.storage().instance().get(&SupplyRegistry::Limits(property_id)).unwrap_or(PurchaseLimits {
+            min_shares_per_purchase: 0,
+            max_shares_per_wallet: 0,
+        })
+    }
+    
+    // View function to get the shares reserved by open installment plans
+    pub fn get_reserved_shares(env: Env, property_id: u64) -> u64 {
+        env.storage().instance().get(&InstallmentRegistry::Reserved(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get the rounding policy of a property
+    pub fn get_rounding_policy(env: Env, property_id: u64) -> RoundingPolicy {
+        Self::get_property(env, property_id).rounding_policy
+    }
+    
+    // View function to preview how an amount splits across weights under a property's rounding policy
+    pub fn preview_split(env: Env, property_id: u64, total: u128, weights: Vec<u64>) -> Vec<u128> {
+        let policy = Self::get_rounding_policy(env.clone(), property_id);
+        Self::split_pro_rata(&env, total, &weights, policy)
+    }
+    
+    // View function to get the registered tenants
+    pub fn get_tenants(env: Env) -> Vec<Symbol> {
+        env.storage().instance().get(&TENANTS).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the IDs of a tenant's properties, skipping the first start entries
+    pub fn get_properties_by_tenant(env: Env, tenant: Symbol, start: u32, limit: u32) -> Vec<u64> {
+        let property_ids: Vec<u64> = env.storage().instance().get(&TenantRegistry::Properties(tenant)).unwrap_or(Vec::new(&env));
+        let end = property_ids.len().min(start.saturating_add(limit));
+        if start >= end {
+            return Vec::new(&env);
+        }
+        property_ids.slice(start..end)
+    }
+    
+    // View function to get the category of a property; None for imported properties not yet classified
+    pub fn get_property_type(env: Env, property_id: u64) -> Option<PropertyType> {
+        env.storage().instance().get(&PropertyRegistry::Type(property_id))
+    }
+    
+    // View function to browse compact summaries of a category's listed properties in registration
+    // order; pass None for the first page. Delisted and archived properties are skipped, so a page
+    // can hold fewer than limit items.
+    pub fn list_by_category(env: Env, property_type: PropertyType, cursor: Option<Bytes>, limit: u64) -> PropertyPage {
+        let property_ids: Vec<u64> = env.storage().instance().get(&CategoryRegistry::Properties(property_type)).unwrap_or(Vec::new(&env));
+        let mut properties = Vec::new(&env);
+        
+        let start = Self::decode_cursor(&env, CURSOR_CATEGORY, &cursor);
+        let end = (property_ids.len() as u64).min(start.saturating_add(limit));
+        for index in start..end {
+            let property_id = property_ids.get(index as u32).unwrap();
+            if Self::is_withdrawn(&env, property_id) {
+                continue;
+            }
+            if let Some(property) = Self::load_property(&env, property_id) {
+                properties.push_back(Self::reduce_property(&env, &property));
+            }
+        }
+        
+        PropertyPage {
+            items: properties,
+            next_cursor: Self::next_cursor(&env, CURSOR_CATEGORY, end, property_ids.len() as u64),
+            total: None,
+        }
+    }
+    
+    // View function to get the address that registered a property
+    pub fn get_registrant(env: Env, property_id: u64) -> Address {
+        env.storage().instance().get(&PropertyRegistry::Registrant(property_id)).expect("Property not found")
+    }
+    
+    // View function to check whether an address may view a property's holder-level data.
+    // Public properties are open to everyone; private ones only to the registrant, the admin,
+    // verifiers and current holders.
+    pub fn can_view_cap_table(env: Env, property_id: u64, viewer: Address) -> bool {
+        let property = Self::get_property(env.clone(), property_id);
+        if !property.is_private {
+            return true;
+        }
+        
+        Self::holds_role(&env, &viewer, Role::Verifier)
+            || viewer == Self::get_registrant(env.clone(), property_id)
+            || Self::get_ownership(env, property_id, viewer).shares > 0
+    }
+    
+    // View function to page through a property's holders and their share counts in order of first
+    // acquisition; pass None for the first page. Former holders with no shares left are skipped, so a
+    // page can be shorter than limit. Private cap tables require an authorized viewer.
+    pub fn get_property_holders(env: Env, property_id: u64, viewer: Address, cursor: Option<Bytes>, limit: u32) -> Result<HolderPage, Error> {
+        Self::check_cap_table_access(&env, property_id, &viewer)?;
+        
+        let (items, next_cursor) = Self::page_holders(&env, property_id, CURSOR_HOLDERS, &cursor, limit);
+        Ok(HolderPage { items, next_cursor, total: None })
+    }
+    
+    // View function to page through a property's current holders; pass None for the first page.
+    // Private cap tables require an authorized viewer.
+    pub fn get_property_owners(env: Env, property_id: u64, viewer: Address, cursor: Option<Bytes>, limit: u32) -> Result<OwnerPage, Error> {
+        Self::check_cap_table_access(&env, property_id, &viewer)?;
+        
+        let (holders, next_cursor) = Self::page_holders(&env, property_id, CURSOR_OWNERS, &cursor, limit);
+        let mut owners = Vec::new(&env);
+        for (holder, _) in holders.iter() {
+            owners.push_back(holder);
+        }
+        Ok(OwnerPage { items: owners, next_cursor, total: None })
+    }
+    
+    // View function to page through a property's cap table: its current holders and their share
+    // counts; pass None for the first page. Private cap tables require an authorized viewer.
+    pub fn get_cap_table(env: Env, property_id: u64, viewer: Address, cursor: Option<Bytes>, limit: u32) -> Result<HolderPage, Error> {
+        Self::check_cap_table_access(&env, property_id, &viewer)?;
+        
+        let (items, next_cursor) = Self::page_holders(&env, property_id, CURSOR_CAP_TABLE, &cursor, limit);
+        Ok(HolderPage { items, next_cursor, total: None })
+    }
+    
+    // View function to export a page of a property's full ownership records, with acquisition and
+    // update times, for off-chain record keeping; pass None for the first page. Private cap tables
+    // require an authorized viewer.
+    pub fn export_cap_table(env: Env, property_id: u64, viewer: Address, cursor: Option<Bytes>, limit: u32) -> Result<CapTablePage, Error> {
+        Self::check_cap_table_access(&env, property_id, &viewer)?;
+        
+        let (holders, next_cursor) = Self::page_holders(&env, property_id, CURSOR_CAP_EXPORT, &cursor, limit);
+        let mut records = Vec::new(&env);
+        for (holder, _) in holders.iter() {
+            records.push_back(Self::get_ownership(env.clone(), property_id, holder));
+        }
+        Ok(CapTablePage { items: records, next_cursor, total: None })
+    }
+    
+    // View function to get a property's redemption terms, if enabled
+    pub fn get_redemption_config(env: Env, property_id: u64) -> Option<RedemptionConfig> {
+        env.storage().instance().get(&RedemptionRegistry::Config(property_id))
+    }
+    
+    // View function to get booking details
+    pub fn get_booking(env: Env, booking_id: u64) -> Booking {
+        Self::load_record(&env, &RedemptionRegistry::Booking(booking_id)).expect("Booking not found")
+    }
+    
+    // View function to get the bookings of a property that overlap [from_ts, to_ts)
+    pub fn get_bookings(env: Env, property_id: u64, from_ts: u64, to_ts: u64) -> Vec<Booking> {
+        let booking_ids: Vec<u64> = Self::load_record(&env, &RedemptionRegistry::PropertyBookings(property_id)).unwrap_or(Vec::new(&env));
+        let mut bookings = Vec::new(&env);
+        
+        for booking_id in booking_ids.iter() {
+            let booking = Self::get_booking(env.clone(), booking_id);
+            let start_ts = booking.start_day * SECONDS_PER_DAY;
+            let end_ts = (booking.start_day + booking.nights as u64) * SECONDS_PER_DAY;
+            if start_ts < to_ts && from_ts < end_ts {
+                bookings.push_back(booking);
+            }
+        }
+        
+        bookings
+    }
+    
+    // View function to get the nights an owner has booked at a property in a calendar year
+    pub fn get_nights_used(env: Env, property_id: u64, owner: Address, year: u32) -> u32 {
+        Self::load_record(&env, &RedemptionRegistry::NightsUsed(property_id, owner, year)).unwrap_or(0)
+    }
+    
+    // View function to get how many of a holder's shares are locked by bookings that have not ended
+    pub fn get_booked_shares(env: Env, property_id: u64, owner: Address) -> u64 {
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let booking_ids: Vec<u64> = Self::load_record(&env, &RedemptionRegistry::PropertyBookings(property_id)).unwrap_or(Vec::new(&env));
+        booking_ids.iter()
+            .map(|booking_id| Self::get_booking(env.clone(), booking_id))
+            .filter(|booking| booking.owner == owner && booking.start_day + booking.nights as u64 > today)
+            .map(|booking| booking.shares)
+            .sum()
+    }
+    
+    // View function to get ownership details
+    pub fn get_ownership(env: Env, property_id: u64, owner: Address) -> OwnershipShare {
+        Self::load_ownership(&env, property_id, &owner).unwrap_or(OwnershipShare {
+            property_id,
+            owner: owner.clone(),
+            shares: 0,
+            purchase_time: 0,
+            last_updated: 0,
+        })
+    }
+    
+    // View function to get properties owned by an address
+    pub fn get_user_properties(env: Env, owner: Address) -> Vec<u64> {
+        let key = UserProperties::Properties(owner);
+        if let Some(properties) = env.storage().persistent().get(&key) {
+            env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+            return properties;
+        }
+        env.storage().instance().get(&key).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to value each of an owner's positions, and their sum, at current share prices
+    pub fn get_portfolio(env: Env, owner: Address) -> Portfolio {
+        let mut entries = Vec::new(&env);
+        let mut total_value: Money = 0;
+        
+        for property_id in Self::get_user_properties(env.clone(), owner.clone()).iter() {
+            let shares = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+            if shares == 0 {
+                continue;
+            }
+            let price_per_share = Self::get_property(env.clone(), property_id).price_per_share;
+            let value = (shares as Money).saturating_mul(price_per_share);
+            total_value = total_value.saturating_add(value);
+            entries.push_back(PortfolioEntry { property_id, shares, price_per_share, value });
+        }
+        
+        Portfolio { entries, total_value }
+    }
+    
+    // View function to get property statistics
+    pub fn get_property_stats(env: Env) -> PropertyStats {
+        env.storage().instance().get(&PROPERTY_STATS).unwrap_or(PropertyStats {
+            total_properties: 0,
+            verified_properties: 0,
+            total_owners: 0,
+            total_transactions: 0,
+        })
+    }
+    
+    // View function to get the total and verified market capitalization
+    pub fn get_value_stats(env: Env) -> ValueStats {
+        env.storage().instance().get(&VALUE_STATS).unwrap_or(ValueStats {
+            total_market_cap: 0,
+            verified_market_cap: 0,
+        })
+    }
+    
+    // Function to get total shares owned across all properties by an address
+    pub fn get_total_shares_owned(env: Env, owner: Address) -> u64 {
+        let properties = Self::get_user_properties(env.clone(), owner.clone());
+        let mut total_shares = 0;
+        
+        for property_id in properties.iter() {
+            let ownership = Self::get_ownership(env.clone(), property_id, owner.clone());
+            total_shares += ownership.shares;
+        }
+        
+        total_shares
+    }
+    
+    // View function to get a property's daily volume for day_count days starting at day_start
+    pub fn get_daily_volume(env: Env, property_id: u64, day_start: u64, day_count: u64) -> Vec<DailyVolume> {
+        Self::collect_volume(&env, Some(property_id), day_start, day_count)
+    }
+    
+    // View function to get the platform-wide daily volume for day_count days starting at day_start
+    pub fn get_global_daily_volume(env: Env, day_start: u64, day_count: u64) -> Vec<DailyVolume> {
+        Self::collect_volume(&env, None, day_start, day_count)
+    }
+    
+    // View function to get the number of days volume buckets are retained
+    pub fn get_volume_retention(env: Env) -> u64 {
+        env.storage().instance().get(&VOLUME_RETENTION).unwrap_or(DEFAULT_VOLUME_RETENTION_DAYS)
+    }
+    
+    // Function to set the keeper reward for a maintenance function: a flat amount per item
+    // processed plus value_bps of the value processed (admin only)
+    pub fn set_keeper_rate(env: Env, function: Symbol, per_item: Money, value_bps: u32) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if value_bps > 10000 {
+            panic!("Invalid keeper rate");
+        }
+        
+        env.storage().instance().set(&KeeperRegistry::Rate(function.clone()), &KeeperRate { per_item, value_bps });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Keeper rate for {} set", function);
+    }
+    
+    // Function to set the most keeper rewards paid across all keepers in one day (admin only)
+    pub fn set_keeper_daily_cap(env: Env, cap: Money) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        env.storage().instance().set(&KEEPER_DAILY_CAP, &cap);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Keeper daily cap set to {}", cap);
+    }
+    
+    // Function to distribute rental or other income to a property's holders pro rata (property manager or admin).
+    // The tokens are pulled from the depositor into the contract and, after the management fee,
+    // credited per share; any remainder below one unit per share stays in the contract.
+    pub fn deposit_income(env: Env, depositor: Address, property_id: u64, amount: Money) {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &depositor) {
+            panic!("Not authorized to deposit income");
+        }
+        depositor.require_auth();
+        
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        // Claims of income already deposited stay open while the property is paused
+        Self::require_unpaused_property(&env, property_id);
+        
+        Self::pay(&env, &depositor, &env.current_contract_address(), amount);
+        Self::credit_income(&env, property_id, amount);
+        
+        let count_key = DistributionRegistry::Count(property_id);
+        let distribution_id: u32 = env.storage().instance().get(&count_key).unwrap_or(0) + 1;
+        let distribution = Distribution {
+            distribution_id,
+            amount,
+            deposited_at: env.ledger().timestamp(),
+            next_holder: 0,
+        };
+        Self::store_record(&env, &DistributionRegistry::Distribution(property_id, distribution_id), &distribution);
+        env.storage().instance().set(&count_key, &distribution_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("income"), property_id, (depositor, amount, distribution_id));
+        log!(&env, "Income of {} deposited for property {}", amount, property_id);
+    }
+    
+    // Function to freeze the current holder balances of a property under a new snapshot ID,
+    // e.g. ahead of an income round or a vote (registrant or admin)
+    pub fn create_snapshot(env: Env, caller: Address, property_id: u64) -> u32 {
+        // Check registrant or admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        if caller != admin && caller != Self::get_registrant(env.clone(), property_id) {
+            panic!("Not authorized to create snapshot");
+        }
+        caller.require_auth();
+        
+        let snapshot_id = Self::take_snapshot(&env, property_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Snapshot {} created for property {}", snapshot_id, property_id);
+        snapshot_id
+    }
+    
+    // View function to get a snapshot's details
+    pub fn get_snapshot(env: Env, property_id: u64, snapshot_id: u32) -> Snapshot {
+        Self::load_record(&env, &SnapshotRegistry::Snapshot(property_id, snapshot_id)).expect("Snapshot not found")
+    }
+    
+    // View function to get the number of snapshots taken of a property
+    pub fn get_snapshot_count(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&SnapshotRegistry::Count(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get a holder's balance as of a snapshot
+    pub fn get_balance_at(env: Env, property_id: u64, snapshot_id: u32, owner: Address) -> u64 {
+        if snapshot_id == 0 || snapshot_id > Self::get_snapshot_count(env.clone(), property_id) {
+            panic!("Snapshot not found");
+        }
+        
+        // The first entry recorded at or after the snapshot holds the balance it saw; with none,
+        // the balance has not changed since and the current one applies
+        let history: Vec<(u32, u64)> = env.storage().persistent().get(&SnapshotRegistry::History(property_id, owner.clone())).unwrap_or(Vec::new(&env));
+        for (recorded_at, balance) in history.iter() {
+            if recorded_at >= snapshot_id {
+                return balance;
+            }
+        }
+        Self::get_ownership(env, property_id, owner).shares
+    }
+    
+    // Function to put a decision to a property's co-owners (holders, the registrant or the manager).
+    // Returns the proposal ID.
+    pub fn create_proposal(env: Env, proposer: Address, property_id: u64, action: ProposalAction, description_hash: BytesN<32>) -> u64 {
+        // Authentication
+        proposer.require_auth();
+        Self::record_activity(&env, &proposer);
+        
+        Self::get_property(env.clone(), property_id);
+        if Self::get_ownership(env.clone(), property_id, proposer.clone()).shares == 0
+            && proposer != Self::get_registrant(env.clone(), property_id)
+            && Some(proposer.clone()) != Self::get_property_manager(env.clone(), property_id)
+        {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+        if action == ProposalAction::ApproveBudget(0) {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if action == ProposalAction::SetSalePrice(0) {
+            panic!("Price must be positive");
+        }
+        if let ProposalAction::SetSqueezeOutThreshold(threshold_bps) = action {
+            if !(MIN_SQUEEZE_OUT_BPS..=10000).contains(&threshold_bps) {
+                panic!("Invalid squeeze-out threshold");
+            }
+        }
+        if let ProposalAction::IssueShares(extra_shares, price_per_share) = action {
+            if extra_shares == 0 || price_per_share == 0 {
+                panic_with_error!(&env, Error::ZeroAmount);
+            }
+        }
+        if let ProposalAction::SetMilestones(milestones) = &action {
+            Self::build_milestones(&env, milestones.clone());
+        }
+        
+        let rules = Self::get_governance_rules(env.clone(), property_id);
+        let proposal_id: u64 = env.storage().instance().get(&PROPOSAL_COUNTER).unwrap_or(0) + 1;
+        let created_at = env.ledger().timestamp();
+        let proposal = Proposal {
+            proposal_id,
+            property_id,
+            proposer: proposer.clone(),
+            action,
+            description_hash,
+            snapshot_id: Self::take_snapshot(&env, property_id),
+            quorum_bps: rules.quorum_bps,
+            threshold_bps: rules.threshold_bps,
+            created_at,
+            voting_ends: created_at + VOTING_PERIOD_SECONDS,
+            status: ProposalStatus::Active,
+            votes_for: 0,
+            votes_against: 0,
+        };
+        
+        let list_key = GovernanceRegistry::Proposals(property_id);
+        let mut proposals: Vec<u64> = env.storage().instance().get(&list_key).unwrap_or(Vec::new(&env));
+        proposals.push_back(proposal_id);
+        env.storage().instance().set(&list_key, &proposals);
+        Self::store_record(&env, &GovernanceRegistry::Proposal(proposal_id), &proposal);
+        env.storage().instance().set(&PROPOSAL_COUNTER, &proposal_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("proposal"), property_id, proposal);
+        log!(&env, "Proposal {} created for property {} by {}", proposal_id, property_id, proposer);
+        proposal_id
+    }
+    
+    // Function to withdraw a proposal while voting is still open (proposer only)
+    pub fn cancel_proposal(env: Env, proposal_id: u64) {
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id);
+        
+        // Check proposer authorization
+        proposal.proposer.require_auth();
+        
+        if proposal.status != ProposalStatus::Active {
+            panic!("Proposal is not active");
+        }
+        proposal.status = ProposalStatus::Cancelled;
+        Self::store_record(&env, &GovernanceRegistry::Proposal(proposal_id), &proposal);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("prop_cncl"), proposal.property_id, proposal_id);
+        log!(&env, "Proposal {} cancelled", proposal_id);
+    }
+    
+    // Function to carry out a proposal that passed once its voting has ended. Anyone may call it.
+    pub fn execute_proposal(env: Env, proposal_id: u64) {
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id);
+        if proposal.status != ProposalStatus::Succeeded {
+            panic!("Proposal has not passed");
+        }
+        let property_id = proposal.property_id;
+        
+        match proposal.action.clone() {
+            ProposalAction::Liquidate => {
+                env.storage().instance().set(&LiquidationRegistry::Approved(property_id), &proposal_id);
+            }
+            ProposalAction::ChangeManager(manager) => {
+                Self::assign_manager(&env, property_id, manager);
+            }
+            ProposalAction::SetSalePrice(price_per_share) => {
+                Self::set_sale_price(&env, property_id, price_per_share);
+            }
+            ProposalAction::SetSqueezeOutThreshold(threshold_bps) => {
+                env.storage().instance().set(&BuyoutRegistry::Threshold(property_id), &threshold_bps);
+            }
+            ProposalAction::IssueShares(extra_shares, price_per_share) => {
+                let issuance = ShareIssuance { proposal_id, extra_shares, price_per_share };
+                env.storage().instance().set(&SupplyRegistry::Issuance(property_id), &issuance);
+            }
+            ProposalAction::SplitProperty(units_hash) => {
+                env.storage().instance().set(&SubdivisionRegistry::Approved(property_id), &units_hash);
+            }
+            ProposalAction::SetMilestones(milestones) => {
+                Self::configure_milestones(&env, property_id, milestones);
+            }
+            ProposalAction::ReleaseMilestone(milestone_index) => {
+                Self::release_escrowed_milestone(&env, property_id, milestone_index);
+            }
+            ProposalAction::ApproveBudget(approved) => {
+                let budget = Budget {
+                    proposal_id,
+                    property_id,
+                    approved,
+                    drawn: 0,
+                    approved_at: env.ledger().timestamp(),
+                };
+                env.storage().instance().set(&BudgetRegistry::Budget(proposal_id), &budget);
+                let list_key = BudgetRegistry::Budgets(property_id);
+                let mut budgets: Vec<u64> = env.storage().instance().get(&list_key).unwrap_or(Vec::new(&env));
+                budgets.push_back(proposal_id);
+                env.storage().instance().set(&list_key, &budgets);
+            }
+            ProposalAction::Other => {}
+        }
+        proposal.status = ProposalStatus::Executed;
+        Self::store_record(&env, &GovernanceRegistry::Proposal(proposal_id), &proposal);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("executed"), property_id, proposal_id);
+        log!(&env, "Proposal {} executed", proposal_id);
+    }
+    
+    // Function to set the quorum and pass threshold of a property's future proposals, in basis
+    // points (registrant only). The threshold is at least a majority.
+    pub fn set_governance_rules(env: Env, property_id: u64, rules: GovernanceRules) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if rules.quorum_bps > 10000 || !(DEFAULT_THRESHOLD_BPS..10000).contains(&rules.threshold_bps) {
+            panic!("Invalid governance rules");
+        }
+        env.storage().instance().set(&GovernanceRegistry::Rules(property_id), &rules);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("gov_rules"), property_id, rules.clone());
+        log!(&env, "Governance rules of property {} set to {} quorum, {} threshold", property_id, rules.quorum_bps, rules.threshold_bps);
+    }
+    
+    // View function to get a property's governance rules
+    pub fn get_governance_rules(env: Env, property_id: u64) -> GovernanceRules {
+        env.storage().instance().get(&GovernanceRegistry::Rules(property_id)).unwrap_or(GovernanceRules {
+            quorum_bps: DEFAULT_QUORUM_BPS,
+            threshold_bps: DEFAULT_THRESHOLD_BPS,
+        })
+    }
+    
+    // Function to record an operating expense of a property for holders to audit (property manager
+    // or admin). The expense is paid off-chain; it counts against the month it is recorded in.
+    // Returns the expense's index within the property.
+    pub fn record_expense(env: Env, manager: Address, property_id: u64, category: Symbol, amount: Money, doc_hash: BytesN<32>) -> u32 {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to record expenses");
+        }
+        manager.require_auth();
+        
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        
+        let expense_id = Self::get_expense_count(env.clone(), property_id);
+        let expense = Expense {
+            expense_id,
+            category,
+            amount,
+            doc_hash,
+            recorded_by: manager,
+            recorded_at: env.ledger().timestamp(),
+        };
+        Self::store_record(&env, &AccountingRegistry::Expense(property_id, expense_id), &expense);
+        env.storage().instance().set(&AccountingRegistry::ExpenseCount(property_id), &(expense_id + 1));
+        Self::add_to_period(&env, property_id, AccountingRegistry::Expenses(property_id, Self::current_period(&env)), amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("expense"), property_id, expense);
+        log!(&env, "Expense of {} recorded for property {}", amount, property_id);
+        
+        expense_id
+    }
+    
+    // View function to get one of a property's recorded expenses
+    pub fn get_expense(env: Env, property_id: u64, expense_id: u32) -> Expense {
+        Self::load_record(&env, &AccountingRegistry::Expense(property_id, expense_id)).expect("Expense not found")
+    }
+    
+    // View function to get the number of expenses recorded for a property
+    pub fn get_expense_count(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&AccountingRegistry::ExpenseCount(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get a property's income, expenses, management fees and net income for a
+    // month given as YYYYMM
+    pub fn get_period_statement(env: Env, property_id: u64, period: u32) -> PeriodStatement {
+        let income: Money = env.storage().instance().get(&AccountingRegistry::Income(property_id, period)).unwrap_or(0);
+        let expenses: Money = env.storage().instance().get(&AccountingRegistry::Expenses(property_id, period)).unwrap_or(0);
+        let management_fees: Money = env.storage().instance().get(&AccountingRegistry::Fees(property_id, period)).unwrap_or(0);
+        PeriodStatement {
+            period,
+            income,
+            expenses,
+            management_fees,
+            net_income: income as i128 - expenses as i128 - management_fees as i128,
+        }
+    }
+    
+    // Function to set the fee a property's manager earns on each income deposit (registrant only)
+    pub fn set_management_fee(env: Env, property_id: u64, flat_fee: Money, fee_bps: u32) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        if fee_bps > MAX_MANAGEMENT_FEE_BPS {
+            panic!("Management fee exceeds maximum");
+        }
+        let terms = ManagementFee { flat_fee, fee_bps };
+        env.storage().instance().set(&AccountingRegistry::FeeTerms(property_id), &terms);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("fee_terms"), property_id, terms);
+        log!(&env, "Management fee of property {} set to {} flat plus {} bps", property_id, flat_fee, fee_bps);
+    }
+    
+    // Function to pay a property's manager the fees accrued on its income deposits (the manager,
+    // or the registrant while none is appointed). Returns the amount paid.
+    pub fn claim_management_fee(env: Env, property_id: u64) -> Money {
+        // Check manager authorization
+        let manager = Self::get_property_manager(env.clone(), property_id).unwrap_or_else(|| Self::get_registrant(env.clone(), property_id));
+        manager.require_auth();
+        
+        let amount = Self::pay_management_fee(&env, property_id, &manager);
+        if amount == 0 {
+            panic!("Nothing to claim");
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "{} claimed {} in management fees from property {}", manager, amount, property_id);
+        
+        amount
+    }
+    
+    // View function to get the fee terms of a property's manager
+    pub fn get_management_fee(env: Env, property_id: u64) -> ManagementFee {
+        env.storage().instance().get(&AccountingRegistry::FeeTerms(property_id)).unwrap_or(ManagementFee { flat_fee: 0, fee_bps: 0 })
+    }
+    
+    // View function to get the management fees accrued on a property and not yet claimed
+    pub fn get_accrued_management_fee(env: Env, property_id: u64) -> Money {
+        env.storage().instance().get(&AccountingRegistry::FeesAccrued(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get the months (as YYYYMM) with income or expenses recorded for a property, oldest first
+    pub fn get_accounting_periods(env: Env, property_id: u64) -> Vec<u32> {
+        env.storage().instance().get(&AccountingRegistry::Periods(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to add funds to a property's reserve, from which approved budgets are drawn. Anyone may fund it.
+    pub fn fund_reserve(env: Env, funder: Address, property_id: u64, amount: Money) {
+        // Authentication
+        funder.require_auth();
+        Self::record_activity(&env, &funder);
+        
+        Self::get_property(env.clone(), property_id);
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        
+        Self::pay(&env, &funder, &env.current_contract_address(), amount);
+        let reserve = Self::get_reserve_balance(env.clone(), property_id);
+        env.storage().instance().set(&BudgetRegistry::Reserve(property_id), &(reserve + amount));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("reserve"), property_id, (funder.clone(), amount));
+        log!(&env, "{} added {} to the reserve of property {}", funder, amount, property_id);
+    }
+    
+    // Function to draw funds from a property's reserve against an approved budget (property manager
+    // or admin). Draws are paid to the caller and cannot exceed what the budget has left.
+    pub fn draw_budget(env: Env, manager: Address, proposal_id: u64, amount: Money) {
+        let mut budget = Self::get_budget(env.clone(), proposal_id).expect("Budget not found");
+        let property_id = budget.property_id;
+        
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to draw budget");
+        }
+        manager.require_auth();
+        
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if budget.drawn + amount > budget.approved {
+            panic!("Draw exceeds approved budget");
+        }
+        let reserve = Self::get_reserve_balance(env.clone(), property_id);
+        if amount > reserve {
+            panic!("Insufficient reserve");
+        }
+        
+        budget.drawn += amount;
+        env.storage().instance().set(&BudgetRegistry::Budget(proposal_id), &budget);
+        env.storage().instance().set(&BudgetRegistry::Reserve(property_id), &(reserve - amount));
+        Self::pay(&env, &env.current_contract_address(), &manager, amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("budget_dr"), property_id, (proposal_id, manager.clone(), amount, budget.approved - budget.drawn));
+        log!(&env, "{} drew {} from budget {} of property {}", manager, amount, proposal_id, property_id);
+    }
+    
+    // View function to get the funds held in a property's reserve
+    pub fn get_reserve_balance(env: Env, property_id: u64) -> Money {
+        env.storage().instance().get(&BudgetRegistry::Reserve(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get a budget approved by a proposal, with how much has been drawn
+    pub fn get_budget(env: Env, proposal_id: u64) -> Option<Budget> {
+        env.storage().instance().get(&BudgetRegistry::Budget(proposal_id))
+    }
+    
+    // View function to get the proposal IDs of a property's approved budgets, oldest first
+    pub fn get_budgets(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&BudgetRegistry::Budgets(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to record a new lease of a property, pending until signed (property manager or
+    // admin). A lessee needs an address or an off-chain reference. Returns the lease ID.
+    pub fn create_lease(env: Env, manager: Address, property_id: u64, terms: LeaseTerms) -> u64 {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to manage leases");
+        }
+        manager.require_auth();
+        
+        if terms.lessee.is_none() && terms.lessee_ref.is_none() {
+            panic!("Lessee required");
+        }
+        if terms.rent == 0 || terms.period_seconds == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if terms.end <= terms.start {
+            panic!("Lease must end after it starts");
+        }
+        
+        let lease_id: u64 = env.storage().instance().get(&LEASE_COUNTER).unwrap_or(0) + 1;
+        let lease = Lease {
+            lease_id,
+            property_id,
+            lessee: terms.lessee,
+            lessee_ref: terms.lessee_ref,
+            rent: terms.rent,
+            period_seconds: terms.period_seconds,
+            deposit: terms.deposit,
+            deposit_held: 0,
+            rent_paid: 0,
+            doc_hash: terms.doc_hash,
+            start: terms.start,
+            end: terms.end,
+            status: LeaseStatus::Pending,
+            created_at: env.ledger().timestamp(),
+            activated_at: None,
+            ended_at: None,
+        };
+        
+        let list_key = LeaseRegistry::Leases(property_id);
+        let mut leases: Vec<u64> = env.storage().instance().get(&list_key).unwrap_or(Vec::new(&env));
+        leases.push_back(lease_id);
+        env.storage().instance().set(&list_key, &leases);
+        Self::store_record(&env, &LeaseRegistry::Lease(lease_id), &lease);
+        env.storage().instance().set(&LEASE_COUNTER, &lease_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("lease"), property_id, lease);
+        log!(&env, "Lease {} created for property {}", lease_id, property_id);
+        lease_id
+    }
+    
+    // Function to sign a pending lease, making it active. A lessee with an address signs it
+    // themself and pays the security deposit into the contract; otherwise the property manager
+    // signs it on the lessee's behalf and the deposit is handled off-chain.
+    pub fn activate_lease(env: Env, lease_id: u64) {
+        let mut lease = Self::get_lease(env.clone(), lease_id);
+        if lease.status != LeaseStatus::Pending {
+            panic!("Lease is not pending");
+        }
+        
+        // Check lessee or manager authorization
+        match lease.lessee.clone() {
+            Some(lessee) => {
+                lessee.require_auth();
+                Self::record_activity(&env, &lessee);
+                if lease.deposit > 0 {
+                    Self::pay(&env, &lessee, &env.current_contract_address(), lease.deposit);
+                    lease.deposit_held = lease.deposit;
+                }
+            }
+            None => {
+                let manager = Self::get_property_manager(env.clone(), lease.property_id).unwrap_or_else(|| Self::get_registrant(env.clone(), lease.property_id));
+                manager.require_auth();
+            }
+        }
+        lease.status = LeaseStatus::Active;
+        lease.activated_at = Some(env.ledger().timestamp());
+        Self::store_record(&env, &LeaseRegistry::Lease(lease_id), &lease);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("lease_on"), lease.property_id, lease_id);
+        log!(&env, "Lease {} activated", lease_id);
+    }
+    
+    // Function to end a lease (property manager or admin): a pending or active lease is terminated,
+    // and one past its end date expires. The deposit held is returned to the lessee, less the
+    // amount withheld, which goes to the property's reserve.
+    pub fn end_lease(env: Env, manager: Address, lease_id: u64, withheld: Money) {
+        let mut lease = Self::get_lease(env.clone(), lease_id);
+        
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, lease.property_id, &manager) {
+            panic!("Not authorized to manage leases");
+        }
+        manager.require_auth();
+        
+        let stored: Lease = Self::load_record(&env, &LeaseRegistry::Lease(lease_id)).unwrap();
+        if stored.status != LeaseStatus::Pending && stored.status != LeaseStatus::Active {
+            panic!("Lease already ended");
+        }
+        if withheld > lease.deposit_held {
+            panic!("Withheld amount exceeds deposit");
+        }
+        
+        if lease.status != LeaseStatus::Expired {
+            lease.status = LeaseStatus::Terminated;
+        }
+        if let Some(lessee) = lease.lessee.clone().filter(|_| lease.deposit_held > withheld) {
+            Self::pay(&env, &env.current_contract_address(), &lessee, lease.deposit_held - withheld);
+        }
+        if withheld > 0 {
+            let reserve = Self::get_reserve_balance(env.clone(), lease.property_id);
+            env.storage().instance().set(&BudgetRegistry::Reserve(lease.property_id), &(reserve + withheld));
+        }
+        lease.deposit_held = 0;
+        lease.ended_at = Some(env.ledger().timestamp());
+        Self::store_record(&env, &LeaseRegistry::Lease(lease_id), &lease);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("lease_end"), lease.property_id, (lease_id, lease.status, withheld));
+        log!(&env, "Lease {} ended", lease_id);
+    }
+    
+    // View function to get a lease with its current status
+    pub fn get_lease(env: Env, lease_id: u64) -> Lease {
+        let mut lease: Lease = Self::load_record(&env, &LeaseRegistry::Lease(lease_id)).expect("Lease not found");
+        if (lease.status == LeaseStatus::Pending || lease.status == LeaseStatus::Active) && env.ledger().timestamp() >= lease.end {
+            lease.status = LeaseStatus::Expired;
+        }
+        lease
+    }
+    
+    // Function for a lessee to pay rent on their lease. The tokens are pulled into the contract
+    // and credited to the property's holders like an income deposit; payments count against the
+    // rent schedule oldest period first, and can run ahead of it up to the rent for the whole
+    // term. Once a lease has ended, only its remaining arrears can be paid.
+    pub fn pay_rent(env: Env, lease_id: u64, amount: Money) {
+        let mut lease: Lease = Self::load_record(&env, &LeaseRegistry::Lease(lease_id)).expect("Lease not found");
+        if lease.activated_at.is_none() {
+            panic!("Lease is not active");
+        }
+        let lessee = lease.lessee.clone().unwrap_or_else(|| panic!("Lessee pays off-chain"));
+        
+        // Check lessee authorization
+        lessee.require_auth();
+        Self::record_activity(&env, &lessee);
+        
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        let (payable_periods, _) = Self::rent_periods(&lease, lease.ended_at.unwrap_or(u64::MAX));
+        if lease.rent_paid + amount > lease.rent * payable_periods as Money {
+            panic!("Payment exceeds rent for the lease term");
+        }
+        
+        Self::pay(&env, &lessee, &env.current_contract_address(), amount);
+        Self::credit_income(&env, lease.property_id, amount);
+        lease.rent_paid += amount;
+        Self::store_record(&env, &LeaseRegistry::Lease(lease_id), &lease);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("rent"), lease.property_id, (lease_id, lessee, amount));
+        log!(&env, "Rent of {} paid on lease {}", amount, lease_id);
+    }
+    
+    // View function to get how a lease stands against its rent schedule, including arrears and
+    // when the next period's rent falls due
+    pub fn get_rent_status(env: Env, lease_id: u64) -> RentStatus {
+        let lease = Self::get_lease(env.clone(), lease_id);
+        let now = env.ledger().timestamp();
+        let (periods_due, total_periods) = match lease.activated_at {
+            Some(_) => Self::rent_periods(&lease, lease.ended_at.unwrap_or(now).min(now)),
+            None => (0, 0),
+        };
+        let rent_due = lease.rent * periods_due as Money;
+        let next_due_at = if lease.status == LeaseStatus::Active && periods_due < total_periods {
+            Some(lease.start + periods_due * lease.period_seconds)
+        } else {
+            None
+        };
+        RentStatus {
+            lease_id,
+            periods_due,
+            rent_due,
+            rent_paid: lease.rent_paid,
+            arrears: rent_due.saturating_sub(lease.rent_paid),
+            next_due_at,
+        }
+    }
+    
+    // View function to get the IDs of a property's leases, oldest first
+    pub fn get_leases(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&LeaseRegistry::Leases(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to report a property's occupancy (property manager or admin). Moving into or out
+    // of Vacant starts or closes a vacancy, whose length is added to the property's vacant time.
+    pub fn set_occupancy(env: Env, manager: Address, property_id: u64, occupancy: Occupancy) {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to report occupancy");
+        }
+        manager.require_auth();
+        
+        let now = env.ledger().timestamp();
+        let key = PropertyRegistry::Occupancy(property_id);
+        let previous: Option<OccupancyRecord> = env.storage().instance().get(&key);
+        let (mut vacant_since, mut vacant_seconds) = previous.as_ref().map_or((None, 0), |record| (record.vacant_since, record.vacant_seconds));
+        match (vacant_since, occupancy) {
+            (None, Occupancy::Vacant) => vacant_since = Some(now),
+            (Some(since), Occupancy::Occupied | Occupancy::PartiallyOccupied) => {
+                vacant_seconds += now - since;
+                vacant_since = None;
+            }
+            _ => {}
+        }
+        env.storage().instance().set(&key, &OccupancyRecord {
+            occupancy,
+            vacant_since,
+            vacant_seconds,
+            updated_by: manager,
+            updated_at: now,
+        });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("occupancy"), property_id, (previous.map(|record| record.occupancy), occupancy));
+        log!(&env, "Occupancy of property {} updated", property_id);
+    }
+    
+    // View function to get a property's occupancy, with vacant_seconds counting any ongoing vacancy
+    // up to now; None until the manager first reports it
+    pub fn get_occupancy(env: Env, property_id: u64) -> Option<OccupancyRecord> {
+        let mut record: OccupancyRecord = env.storage().instance().get(&PropertyRegistry::Occupancy(property_id))?;
+        if let Some(since) = record.vacant_since {
+            record.vacant_seconds += env.ledger().timestamp() - since;
+        }
+        Some(record)
+    }
+    
+    // View function to get how long a property has been vacant in its current vacancy; 0 when it
+    // is occupied or its occupancy has not been reported
+    pub fn get_vacancy_duration(env: Env, property_id: u64) -> u64 {
+        let record: Option<OccupancyRecord> = env.storage().instance().get(&PropertyRegistry::Occupancy(property_id));
+        record.and_then(|record| record.vacant_since).map_or(0, |since| env.ledger().timestamp() - since)
+    }
+    
+    // Function to record an insurance policy on a property (property manager or admin). Returns
+    // the policy ID.
+    pub fn add_insurance_policy(env: Env, manager: Address, property_id: u64, terms: PolicyTerms) -> u32 {
+        let policy_id = Self::get_insurance_policy_count(env.clone(), property_id);
+        Self::store_insurance_policy(&env, &manager, property_id, policy_id, terms);
+        env.storage().instance().set(&InsuranceRegistry::PolicyCount(property_id), &(policy_id + 1));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Insurance policy {} added to property {}", policy_id, property_id);
+        policy_id
+    }
+    
+    // Function to amend or renew one of a property's insurance policies (property manager or admin)
+    pub fn update_insurance_policy(env: Env, manager: Address, property_id: u64, policy_id: u32, terms: PolicyTerms) {
+        Self::get_insurance_policy(env.clone(), property_id, policy_id);
+        Self::store_insurance_policy(&env, &manager, property_id, policy_id, terms);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Insurance policy {} of property {} updated", policy_id, property_id);
+    }
+    
+    // View function to get one of a property's insurance policies with its current status
+    pub fn get_insurance_policy(env: Env, property_id: u64, policy_id: u32) -> InsurancePolicy {
+        let mut policy: InsurancePolicy = Self::load_record(&env, &InsuranceRegistry::Policy(property_id, policy_id)).expect("Policy not found");
+        let now = env.ledger().timestamp();
+        policy.status = if now >= policy.expires_at {
+            PolicyStatus::Expired
+        } else if now + INSURANCE_WARNING_SECONDS >= policy.expires_at {
+            PolicyStatus::ExpiringSoon
+        } else {
+            PolicyStatus::Active
+        };
+        policy
+    }
+    
+    // View function to get the number of insurance policies recorded for a property
+    pub fn get_insurance_policy_count(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&InsuranceRegistry::PolicyCount(property_id)).unwrap_or(0)
+    }
+    
+    // View function to get the IDs of a property's insurance policies that have expired or expire
+    // within the warning window
+    pub fn get_insurance_warnings(env: Env, property_id: u64) -> Vec<u32> {
+        let mut warnings = Vec::new(&env);
+        for policy_id in 0..Self::get_insurance_policy_count(env.clone(), property_id) {
+            if Self::get_insurance_policy(env.clone(), property_id, policy_id).status != PolicyStatus::Active {
+                warnings.push_back(policy_id);
+            }
+        }
+        warnings
+    }
+    
+    // Function to file a claim against one of a property's unexpired insurance policies, for at most
+    // its coverage (property manager or admin). Returns the claim ID.
+    pub fn file_insurance_claim(env: Env, manager: Address, property_id: u64, policy_id: u32, amount: Money, doc_hash: BytesN<32>) -> u32 {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to manage insurance");
+        }
+        manager.require_auth();
+        
+        let policy = Self::get_insurance_policy(env.clone(), property_id, policy_id);
+        if policy.status == PolicyStatus::Expired {
+            panic!("Policy has expired");
+        }
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if amount > policy.coverage {
+            panic!("Claim exceeds coverage");
+        }
+        
+        let claim_id = Self::get_insurance_claim_count(env.clone(), property_id);
+        let claim = InsuranceClaim {
+            claim_id,
+            policy_id,
+            filed_amount: amount,
+            approved_amount: 0,
+            paid_amount: 0,
+            doc_hash,
+            status: ClaimStatus::Filed,
+            filed_at: env.ledger().timestamp(),
+        };
+        Self::store_record(&env, &InsuranceRegistry::Claim(property_id, claim_id), &claim);
+        env.storage().instance().set(&InsuranceRegistry::ClaimCount(property_id), &(claim_id + 1));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("claim"), property_id, claim);
+        log!(&env, "Insurance claim {} filed for property {}", claim_id, property_id);
+        claim_id
+    }
+    
+    // Function to record the insurer's decision on a filed claim (property manager or admin). An
+    // approved amount of zero rejects the claim; otherwise it cannot exceed the amount filed.
+    pub fn settle_insurance_claim(env: Env, manager: Address, property_id: u64, claim_id: u32, approved_amount: Money) {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(&env, property_id, &manager) {
+            panic!("Not authorized to manage insurance");
+        }
+        manager.require_auth();
+        
+        let mut claim = Self::get_insurance_claim(env.clone(), property_id, claim_id);
+        if claim.status != ClaimStatus::Filed {
+            panic!("Claim already settled");
+        }
+        if approved_amount > claim.filed_amount {
+            panic!("Approved amount exceeds claim");
+        }
+        claim.approved_amount = approved_amount;
+        claim.status = if approved_amount == 0 { ClaimStatus::Rejected } else { ClaimStatus::Approved };
+        Self::store_record(&env, &InsuranceRegistry::Claim(property_id, claim_id), &claim);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("claim_set"), property_id, (claim_id, claim.status, approved_amount));
+        log!(&env, "Insurance claim {} of property {} settled", claim_id, property_id);
+    }
+    
+    // Function to pay out an approved claim into the property's reserve, in one or more payments
+    // up to the approved amount. Anyone can pay, e.g. an insurer paying on-chain or the manager
+    // forwarding an off-chain payout; the claim is Paid once the approved amount has arrived.
+    pub fn pay_insurance_claim(env: Env, payer: Address, property_id: u64, claim_id: u32, amount: Money) {
+        // Authentication
+        payer.require_auth();
+        Self::record_activity(&env, &payer);
+        
+        let mut claim = Self::get_insurance_claim(env.clone(), property_id, claim_id);
+        if claim.status != ClaimStatus::Approved {
+            panic!("Claim is not approved");
+        }
+        if amount == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if claim.paid_amount + amount > claim.approved_amount {
+            panic!("Payment exceeds approved amount");
+        }
+        
+        Self::pay(&env, &payer, &env.current_contract_address(), amount);
+        let reserve = Self::get_reserve_balance(env.clone(), property_id);
+        env.storage().instance().set(&BudgetRegistry::Reserve(property_id), &(reserve + amount));
+        claim.paid_amount += amount;
+        if claim.paid_amount == claim.approved_amount {
+            claim.status = ClaimStatus::Paid;
+        }
+        Self::store_record(&env, &InsuranceRegistry::Claim(property_id, claim_id), &claim);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("claim_pay"), property_id, (claim_id, payer, amount));
+        log!(&env, "Payout of {} received on insurance claim {}", amount, claim_id);
+    }
+    
+    // View function to get one of a property's insurance claims
+    pub fn get_insurance_claim(env: Env, property_id: u64, claim_id: u32) -> InsuranceClaim {
+        Self::load_record(&env, &InsuranceRegistry::Claim(property_id, claim_id)).expect("Claim not found")
+    }
+    
+    // View function to get the number of insurance claims filed for a property
+    pub fn get_insurance_claim_count(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&InsuranceRegistry::ClaimCount(property_id)).unwrap_or(0)
+    }
+    
+    // Function to appoint a property's manager (registrant only; co-owners can also replace the
+    // manager through a ChangeManager proposal). Once appointed, the manager rather than the
+    // registrant deposits income and runs the property's day-to-day operations.
+    pub fn set_property_manager(env: Env, property_id: u64, manager: Address) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        Self::assign_manager(&env, property_id, manager.clone());
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Manager of property {} set to {}", property_id, manager);
+    }
+    
+    // View function to get a property's manager, if one has been appointed
+    pub fn get_property_manager(env: Env, property_id: u64) -> Option<Address> {
+        env.storage().instance().get(&PropertyRegistry::Manager(property_id))
+    }
+    
+    // View function to check whether co-owners have approved liquidating a property
+    pub fn is_liquidation_approved(env: Env, property_id: u64) -> bool {
+        env.storage().instance().has(&LiquidationRegistry::Approved(property_id))
+    }
+    
+    // Function to record the sale of a property whose liquidation co-owners approved (admin or
+    // property manager). The caller deposits the proceeds, the shares freeze and holders redeem them.
+    pub fn record_liquidation(env: Env, caller: Address, property_id: u64, proceeds: Money) {
+        // Check admin or manager authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        if caller != admin && Some(caller.clone()) != Self::get_property_manager(env.clone(), property_id) {
+            panic!("Not authorized to record liquidation");
+        }
+        caller.require_auth();
+        
+        if !Self::is_liquidation_approved(env.clone(), property_id) {
+            panic!("Liquidation not approved");
+        }
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            panic_with_error!(&env, Error::PropertyLiquidated);
+        }
+        if proceeds == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if Self::get_reserved_shares(env.clone(), property_id) > 0 {
+            panic!("Installment plans outstanding");
+        }
+        let shares_outstanding = Self::get_shares_sold(env.clone(), property_id);
+        if shares_outstanding == 0 {
+            panic!("No shares outstanding");
+        }
+        
+        Self::pay(&env, &caller, &env.current_contract_address(), proceeds);
+        let liquidation = Liquidation {
+            proceeds,
+            shares_outstanding,
+            shares_redeemed: 0,
+            amount_paid: 0,
+            recorded_by: caller.clone(),
+            recorded_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&LiquidationRegistry::Sale(property_id), &liquidation);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("liquidate"), property_id, liquidation);
+        log!(&env, "Property {} liquidated for {} by {}", property_id, proceeds, caller);
+    }
+    
+    // Function to burn all of a holder's shares in a liquidated property for their pro-rata part
+    // of the proceeds. The last holder to redeem also receives any rounding remainder.
+    pub fn redeem_shares(env: Env, owner: Address, property_id: u64) -> Money {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let mut liquidation = Self::get_liquidation(env.clone(), property_id).expect("Property not liquidated");
+        let shares = Self::burn_position(&env, property_id, &owner);
+        if shares == 0 {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+        
+        liquidation.shares_redeemed += shares;
+        let payout = if liquidation.shares_redeemed == liquidation.shares_outstanding {
+            liquidation.proceeds - liquidation.amount_paid
+        } else {
+            liquidation.proceeds * shares as Money / liquidation.shares_outstanding as Money
+        };
+        liquidation.amount_paid += payout;
+        env.storage().instance().set(&LiquidationRegistry::Sale(property_id), &liquidation);
+        Self::pay(&env, &env.current_contract_address(), &owner, payout);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("redeem"), property_id, (owner.clone(), shares, payout));
+        log!(&env, "{} redeemed {} shares of property {} for {}", owner, shares, property_id, payout);
+        
+        payout
+    }
+    
+    // View function to get the recorded sale of a liquidated property, if any
+    pub fn get_liquidation(env: Env, property_id: u64) -> Option<Liquidation> {
+        env.storage().instance().get(&LiquidationRegistry::Sale(property_id))
+    }
+    
+    // Function to offer to buy every outstanding share of a property at one price. The bidder
+    // escrows the cost of all shares sold that they do not already hold; one offer per property.
+    pub fn make_buyout_offer(env: Env, bidder: Address, property_id: u64, price_per_share: Money) {
+        // Authentication
+        bidder.require_auth();
+        Self::record_activity(&env, &bidder);
+        
+        Self::get_property(env.clone(), property_id);
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            panic_with_error!(&env, Error::PropertyLiquidated);
+        }
+        if Self::get_buyout(env.clone(), property_id).is_some() {
+            panic!("Buyout offer already open");
+        }
+        if price_per_share == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        let held = Self::get_ownership(env.clone(), property_id, bidder.clone()).shares;
+        let outstanding = Self::get_shares_sold(env.clone(), property_id) - held;
+        if outstanding == 0 {
+            panic!("No shares outstanding");
+        }
+        let escrow = price_per_share.checked_mul(outstanding as Money).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        
+        Self::pay(&env, &bidder, &env.current_contract_address(), escrow);
+        let offer = BuyoutOffer {
+            bidder: bidder.clone(),
+            price_per_share,
+            escrow,
+            shares_acquired: 0,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&BuyoutRegistry::Offer(property_id), &offer);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyout"), property_id, offer);
+        log!(&env, "{} offered to buy out property {} at {} per share", bidder, property_id, price_per_share);
+    }
+    
+    // Function to sell all of a holder's shares to the open buyout at its price. Transfer
+    // restrictions apply; wallet caps do not, since a buyout exists to consolidate ownership.
+    pub fn accept_buyout(env: Env, holder: Address, property_id: u64) -> Money {
+        // Authentication
+        holder.require_auth();
+        Self::record_activity(&env, &holder);
+        
+        let mut offer = Self::get_buyout(env.clone(), property_id).expect("No open buyout offer");
+        let shares = Self::get_ownership(env.clone(), property_id, holder.clone()).shares;
+        if shares == 0 || holder == offer.bidder {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+        let proceeds = offer.price_per_share * shares as Money;
+        if proceeds > offer.escrow {
+            panic!("Buyout escrow exhausted");
+        }
+        
+        Self::execute_transfer(&env, property_id, &holder, &offer.bidder, shares);
+        offer.escrow -= proceeds;
+        offer.shares_acquired += shares;
+        env.storage().instance().set(&BuyoutRegistry::Offer(property_id), &offer);
+        Self::pay_sale_proceeds(&env, property_id, &env.current_contract_address(), &holder, proceeds);
+        Self::record_trade_price(&env, property_id, shares, offer.price_per_share, false);
+        Self::record_lot(&env, property_id, &offer.bidder, shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyout_ok"), property_id, (holder.clone(), shares, proceeds));
+        log!(&env, "{} sold {} shares of property {} into the buyout", holder, shares, property_id);
+        
+        proceeds
+    }
+    
+    // Function to close a buyout once the bidder holds the squeeze-out threshold of the shares
+    // sold (bidder only). Remaining holders are bought out at the offer price regardless of
+    // transfer restrictions; the bidder tops up the escrow if later sales outgrew it, and any
+    // escrow left over is returned. Shares in contract custody are left where they are.
+    pub fn squeeze_out(env: Env, property_id: u64) {
+        let offer = Self::get_buyout(env.clone(), property_id).expect("No open buyout offer");
+        
+        // Check bidder authorization
+        offer.bidder.require_auth();
+        
+        let held = Self::get_ownership(env.clone(), property_id, offer.bidder.clone()).shares as u128;
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id) as u128;
+        if held * 10000 < shares_sold * Self::get_squeeze_out_threshold(env.clone(), property_id) as u128 {
+            panic!("Squeeze-out threshold not reached");
+        }
+        
+        let contract = env.current_contract_address();
+        let holders: Vec<Address> = Self::load_record(&env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(&env));
+        let mut remaining = Vec::new(&env);
+        let mut cost: Money = 0;
+        for holder in holders.iter() {
+            let ownership = match Self::load_ownership(&env, property_id, &holder) {
+                Some(ownership) if ownership.shares > 0 && holder != offer.bidder && holder != contract => ownership,
+                _ => continue,
+            };
+            cost += offer.price_per_share * ownership.shares as Money;
+            remaining.push_back(ownership);
+        }
+        if cost > offer.escrow {
+            Self::pay(&env, &offer.bidder, &contract, cost - offer.escrow);
+        }
+        
+        for ownership in remaining.iter() {
+            let shares = ownership.shares;
+            let plan = Self::plan_transfer(&env, property_id, ownership.clone(), &offer.bidder, shares).unwrap_or_else(|error| panic_with_error!(&env, error));
+            Self::apply_transfer(&env, &plan, shares);
+            Self::pay_sale_proceeds(&env, property_id, &contract, &ownership.owner, offer.price_per_share * shares as Money);
+            Self::publish_property_event(&env, symbol_short!("squeezed"), property_id, (ownership.owner, shares));
+        }
+        if offer.escrow > cost {
+            Self::pay(&env, &contract, &offer.bidder, offer.escrow - cost);
+        }
+        env.storage().instance().remove(&BuyoutRegistry::Offer(property_id));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyout_in"), property_id, offer.bidder.clone());
+        log!(&env, "Buyout of property {} completed by {}", property_id, offer.bidder);
+    }
+    
+    // Function to withdraw an open buyout offer and the rest of its escrow (bidder only)
+    pub fn cancel_buyout(env: Env, property_id: u64) {
+        let offer = Self::get_buyout(env.clone(), property_id).expect("No open buyout offer");
+        
+        // Check bidder authorization
+        offer.bidder.require_auth();
+        
+        env.storage().instance().remove(&BuyoutRegistry::Offer(property_id));
+        if offer.escrow > 0 {
+            Self::pay(&env, &env.current_contract_address(), &offer.bidder, offer.escrow);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyout_cx"), property_id, offer.bidder.clone());
+        log!(&env, "Buyout of property {} cancelled", property_id);
+    }
+    
+    // Function to open a buyback of up to a number of a property's shares at a fixed price
+    // (registrant or property manager). The caller escrows the full cost; one buyback per property.
+    pub fn buyback_shares(env: Env, caller: Address, property_id: u64, shares: u64, price_per_share: Money) {
+        // Check registrant or manager authorization
+        if caller != Self::get_registrant(env.clone(), property_id) && Some(caller.clone()) != Self::get_property_manager(env.clone(), property_id) {
+            panic!("Not authorized to buy back shares");
+        }
+        caller.require_auth();
+        
+        if shares == 0 || price_per_share == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if Self::get_buyback(env.clone(), property_id).is_some() {
+            panic!("Buyback already open");
+        }
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            panic_with_error!(&env, Error::PropertyLiquidated);
+        }
+        if shares > Self::get_shares_sold(env.clone(), property_id) {
+            panic_with_error!(&env, Error::InsufficientShares);
+        }
+        let escrow = price_per_share.checked_mul(shares as Money).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        
+        Self::pay(&env, &caller, &env.current_contract_address(), escrow);
+        let buyback = Buyback {
+            sponsor: caller.clone(),
+            shares,
+            price_per_share,
+            shares_tendered: 0,
+            escrow,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&BuybackRegistry::Offer(property_id), &buyback);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyback"), property_id, buyback);
+        log!(&env, "Buyback of {} shares of property {} opened at {}", shares, property_id, price_per_share);
+    }
+    
+    // Function to sell shares into a property's open buyback. They are burned, reducing the
+    // property's total shares; shares that could not be transferred cannot be tendered. The
+    // buyback closes once fully tendered. Returns the amount paid.
+    pub fn tender_shares(env: Env, holder: Address, property_id: u64, shares: u64) -> Money {
+        // Authentication
+        holder.require_auth();
+        Self::record_activity(&env, &holder);
+        
+        let mut buyback = Self::get_buyback(env.clone(), property_id).expect("No open buyback");
+        if shares == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        if shares > buyback.shares - buyback.shares_tendered {
+            panic!("Tender exceeds the shares sought");
+        }
+        let previous = Self::get_property(env.clone(), property_id);
+        let balance = Self::get_ownership(env.clone(), property_id, holder.clone()).shares;
+        if let Some(reason) = Self::transfer_blocker(&env, &previous, &holder, &env.current_contract_address(), shares, balance) {
+            panic_with_error!(&env, Self::transfer_error(&reason));
+        }
+        
+        Self::burn_shares(&env, property_id, &holder, shares);
+        let mut property = previous.clone();
+        property.total_shares -= shares;
+        Self::store_repriced_property(&env, &previous, &property);
+        
+        let payout = buyback.price_per_share * shares as Money;
+        buyback.shares_tendered += shares;
+        buyback.escrow -= payout;
+        let tendered_key = BuybackRegistry::Tendered(property_id, holder.clone());
+        let tendered: u64 = Self::load_record(&env, &tendered_key).unwrap_or(0);
+        Self::store_record(&env, &tendered_key, &(tendered + shares));
+        if buyback.shares_tendered == buyback.shares {
+            env.storage().instance().remove(&BuybackRegistry::Offer(property_id));
+        } else {
+            env.storage().instance().set(&BuybackRegistry::Offer(property_id), &buyback);
+        }
+        Self::pay(&env, &env.current_contract_address(), &holder, payout);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("tender"), property_id, (holder.clone(), shares, payout));
+        log!(&env, "{} tendered {} shares of property {} for {}", holder, shares, property_id, payout);
+        
+        payout
+    }
+    
+    // Function to close a property's open buyback and return the unspent escrow (sponsor only)
+    pub fn close_buyback(env: Env, property_id: u64) {
+        let buyback = Self::get_buyback(env.clone(), property_id).expect("No open buyback");
+        
+        // Check sponsor authorization
+        buyback.sponsor.require_auth();
+        
+        env.storage().instance().remove(&BuybackRegistry::Offer(property_id));
+        if buyback.escrow > 0 {
+            Self::pay(&env, &env.current_contract_address(), &buyback.sponsor, buyback.escrow);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("buyback_x"), property_id, buyback.shares_tendered);
+        log!(&env, "Buyback of property {} closed with {} shares tendered", property_id, buyback.shares_tendered);
+    }
+    
+    // View function to get a property's open buyback with its tender progress, if any
+    pub fn get_buyback(env: Env, property_id: u64) -> Option<Buyback> {
+        env.storage().instance().get(&BuybackRegistry::Offer(property_id))
+    }
+    
+    // View function to get how many shares of a property a holder has tendered into buybacks
+    pub fn get_tendered_shares(env: Env, property_id: u64, holder: Address) -> u64 {
+        Self::load_record(&env, &BuybackRegistry::Tendered(property_id, holder)).unwrap_or(0)
+    }
+    
+    // Function to split a property into units registered as child properties (registrant only).
+    // Co-owners must first pass a proposal naming the SHA-256 of the units' XDR encoding. The
+    // parent's shares are burned and it is archived; each unit inherits its verification, and
+    // holders receive unit shares per the unit's allocations or pro-rata. Returns the unit IDs.
+    pub fn split_property(env: Env, property_id: u64, units: Vec<UnitSpec>) -> Vec<u64> {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let approved: BytesN<32> = env.storage().instance().get(&SubdivisionRegistry::Approved(property_id)).expect("Split not approved");
+        let units_hash: BytesN<32> = env.crypto().sha256(&units.clone().to_xdr(&env)).into();
+        if approved != units_hash {
+            panic!("Units do not match the approved split");
+        }
+        if units.is_empty() {
+            panic!("No units");
+        }
+        Self::require_restructurable(&env, property_id);
+        env.storage().instance().remove(&SubdivisionRegistry::Approved(property_id));
+        
+        // Burn the parent's positions, keeping the balances for the pro-rata allocation
+        let parent = Self::get_property(env.clone(), property_id);
+        let holders: Vec<Address> = Self::load_record(&env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(&env));
+        let mut owners = Vec::new(&env);
+        let mut weights = Vec::new(&env);
+        for holder in holders.iter() {
+            let shares = Self::burn_position(&env, property_id, &holder);
+            if shares > 0 {
+                owners.push_back(holder);
+                weights.push_back(shares);
+            }
+        }
+        let held: u64 = weights.iter().sum();
+        weights.push_back(parent.total_shares - held);
+        Self::withdraw_listing(&env, property_id, PropertyStatus::Archived);
+        
+        let tenant = if parent.tenant == DEFAULT_TENANT { None } else { Some(parent.tenant.clone()) };
+        let property_type = Self::get_property_type(env.clone(), property_id).unwrap_or(PropertyType::Residential);
+        let mut children = Vec::new(&env);
+        for unit in units.iter() {
+            let input = PropertyInput {
+                title: unit.title.clone(),
+                location: parent.location.clone(),
+                description: unit.description.clone(),
+                total_shares: unit.total_shares,
+                price_per_share: unit.price_per_share,
+                image_url: unit.image_url.clone(),
+                founder_shares: 0,
+                founder_lockup_seconds: 0,
+                is_private: parent.is_private,
+                tenant: tenant.clone(),
+                rounding_policy: parent.rounding_policy,
+                unsold_shares_recipient: None,
+                milestone_escrow: false,
+                property_type,
+                royalty_bps: 0,
+                funding_deadline: None,
+            };
+            let child_id = Self::register(env.clone(), registrant.clone(), input);
+            if parent.is_verified {
+                Self::inherit_verification(&env, child_id);
+            }
+            
+            if unit.allocations.is_empty() {
+                let parts = Self::split_pro_rata(&env, unit.total_shares as u128, &weights, parent.rounding_policy);
+                for (owner, part) in owners.iter().zip(parts.iter()) {
+                    if part > 0 {
+                        Self::credit_shares(&env, child_id, &owner, part as u64);
+                    }
+                }
+            } else {
+                let allocated: u64 = unit.allocations.values().iter().sum();
+                if allocated > unit.total_shares {
+                    panic_with_error!(&env, Error::SupplyExceeded);
+                }
+                for (owner, shares) in unit.allocations.iter() {
+                    if shares > 0 {
+                        Self::credit_shares(&env, child_id, &owner, shares);
+                    }
+                }
+            }
+            
+            env.storage().instance().set(&SubdivisionRegistry::Parent(child_id), &property_id);
+            children.push_back(child_id);
+        }
+        env.storage().instance().set(&SubdivisionRegistry::Children(property_id), &children);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("split"), property_id, children.clone());
+        log!(&env, "Property {} split into {} units", property_id, children.len());
+        
+        children
+    }
+    
+    // Function to combine properties that were legally merged into one new property (admin only).
+    // Every constituent must share a registrant, who registers the merged property. Each
+    // constituent's portion of the new shares follows its appraised value, and its holders receive
+    // that portion pro-rata to their holdings, rounded down; shares not allocated stay on sale. The
+    // constituents' shares are burned and they are archived. Returns the merged property's ID.
+    pub fn merge_properties(env: Env, ids: Vec<u64>, new_metadata: MergeSpec) -> u64 {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if ids.len() < 2 {
+            panic!("At least two properties are needed");
+        }
+        if new_metadata.appraised_values.len() != ids.len() {
+            panic!("One appraised value per property is needed");
+        }
+        let total_value: Money = new_metadata.appraised_values.iter().sum();
+        if total_value == 0 {
+            panic_with_error!(&env, Error::ZeroAmount);
+        }
+        
+        let registrant = Self::get_registrant(env.clone(), ids.get(0).unwrap());
+        let mut all_verified = true;
+        for (index, property_id) in ids.iter().enumerate() {
+            if ids.first_index_of(property_id) != Some(index as u32) {
+                panic!("Duplicate property");
+            }
+            if Self::get_registrant(env.clone(), property_id) != registrant {
+                panic!("Properties have different registrants");
+            }
+            Self::require_restructurable(&env, property_id);
+            all_verified &= Self::get_property(env.clone(), property_id).is_verified;
+        }
+        
+        let input = PropertyInput {
+            title: new_metadata.title.clone(),
+            location: new_metadata.location.clone(),
+            description: new_metadata.description.clone(),
+            total_shares: new_metadata.total_shares,
+            price_per_share: new_metadata.price_per_share,
+            image_url: new_metadata.image_url.clone(),
+            founder_shares: 0,
+            founder_lockup_seconds: 0,
+            is_private: false,
+            tenant: None,
+            rounding_policy: RoundingPolicy::Down,
+            unsold_shares_recipient: None,
+            milestone_escrow: false,
+            property_type: new_metadata.property_type,
+            royalty_bps: 0,
+            funding_deadline: None,
+        };
+        let merged_id = Self::register(env.clone(), registrant, input);
+        if all_verified {
+            Self::inherit_verification(&env, merged_id);
+        }
+        
+        for (property_id, value) in ids.iter().zip(new_metadata.appraised_values.iter()) {
+            let constituent = Self::get_property(env.clone(), property_id);
+            let holders: Vec<Address> = Self::load_record(&env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(&env));
+            for holder in holders.iter() {
+                let shares = Self::burn_position(&env, property_id, &holder);
+                let allocated = (new_metadata.total_shares as u128)
+                    .checked_mul(value)
+                    .and_then(|product| product.checked_mul(shares as u128))
+                    .unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow))
+                    / (total_value * constituent.total_shares as u128);
+                if allocated > 0 {
+                    Self::credit_shares(&env, merged_id, &holder, allocated as u64);
+                }
+            }
+            Self::withdraw_listing(&env, property_id, PropertyStatus::Archived);
+            env.storage().instance().set(&SubdivisionRegistry::MergedInto(property_id), &merged_id);
+        }
+        env.storage().instance().set(&SubdivisionRegistry::Constituents(merged_id), &ids);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("merged"), merged_id, ids.clone());
+        log!(&env, "{} properties merged into property {}", ids.len(), merged_id);
+        
+        merged_id
+    }
+    
+    // View function to get the properties a merged property was formed from, if any
+    pub fn get_constituent_properties(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&SubdivisionRegistry::Constituents(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the property a property was merged into, if any
+    pub fn get_merged_into(env: Env, property_id: u64) -> Option<u64> {
+        env.storage().instance().get(&SubdivisionRegistry::MergedInto(property_id))
+    }
+    
+    // View function to get the units a property was split into, if any
+    pub fn get_child_properties(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&SubdivisionRegistry::Children(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // View function to get the property a unit was split from, if any
+    pub fn get_parent_property(env: Env, property_id: u64) -> Option<u64> {
+        env.storage().instance().get(&SubdivisionRegistry::Parent(property_id))
+    }
+    
+    // Function to burn every share of a property held by its single owner and mint them its
+    // deed, a whole-ownership token that transfers on its own. Shares must not be listed for sale.
+    pub fn consolidate(env: Env, owner: Address, property_id: u64) {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if Self::get_ownership(env.clone(), property_id, owner.clone()).shares != property.total_shares {
+            panic!("Owner does not hold every share");
+        }
+        if Self::get_listed_shares(env.clone(), property_id, owner.clone()) > 0 {
+            panic_with_error!(&env, Error::SharesLocked);
+        }
+        if Self::get_buyout(env.clone(), property_id).is_some() {
+            panic!("Buyout offer open");
+        }
+        
+        Self::burn_position(&env, property_id, &owner);
+        let deed = Deed {
+            property_id,
+            owner: owner.clone(),
+            shares: property.total_shares,
+            minted_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&DeedRegistry::Deed(property_id), &deed);
+        Self::index_deed(&env, &owner, property_id);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("deed_mint"), property_id, owner.clone());
+        log!(&env, "Property {} consolidated into a deed held by {}", property_id, owner);
+    }
+    
+    // Function to hand a property's deed to a new owner (current owner only). The recipient must be
+    // eligible to hold the property, as for shares.
+    pub fn transfer_deed(env: Env, property_id: u64, to: Address) {
+        let mut deed = Self::get_deed(env.clone(), property_id).expect("Property has no deed");
+        
+        // Check deed owner authorization
+        deed.owner.require_auth();
+        
+        if deed.owner == to {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(&env, Error::ContractPaused);
+        }
+        if Self::get_property(env.clone(), property_id).is_paused {
+            panic_with_error!(&env, Error::PropertyPaused);
+        }
+        if let Some(reason) = Self::eligibility_blocker(&env, property_id, &to) {
+            panic_with_error!(&env, Self::transfer_error(&reason));
+        }
+        
+        let from = deed.owner.clone();
+        Self::unindex_deed(&env, &from, property_id);
+        Self::index_deed(&env, &to, property_id);
+        deed.owner = to.clone();
+        env.storage().instance().set(&DeedRegistry::Deed(property_id), &deed);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("deed_xfer"), property_id, (from.clone(), to.clone()));
+        log!(&env, "Deed of property {} transferred from {} to {}", property_id, from, to);
+    }
+    
+    // Function to burn a property's deed and credit its owner with the shares it stands for (deed owner only)
+    pub fn re_fractionalize(env: Env, property_id: u64) {
+        let deed = Self::get_deed(env.clone(), property_id).expect("Property has no deed");
+        
+        // Check deed owner authorization
+        deed.owner.require_auth();
+        
+        env.storage().instance().remove(&DeedRegistry::Deed(property_id));
+        Self::unindex_deed(&env, &deed.owner, property_id);
+        Self::credit_shares(&env, property_id, &deed.owner, deed.shares);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("deed_burn"), property_id, (deed.owner.clone(), deed.shares));
+        log!(&env, "Deed of property {} split back into {} shares", property_id, deed.shares);
+    }
+    
+    // View function to get a property's deed, if it is held whole
+    pub fn get_deed(env: Env, property_id: u64) -> Option<Deed> {
+        env.storage().instance().get(&DeedRegistry::Deed(property_id))
+    }
+    
+    // View function to get the IDs of the properties whose deeds an address holds
+    pub fn get_deeds(env: Env, owner: Address) -> Vec<u64> {
+        Self::load_record(&env, &DeedRegistry::Owned(owner)).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to add the new shares co-owners approved to a property's supply (registrant only).
+    // The terms must match the approved issuance; the new shares go on primary sale at its price.
+    pub fn issue_shares(env: Env, property_id: u64, extra_shares: u64, price_per_share: Money) {
+        // Check registrant authorization
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        registrant.require_auth();
+        
+        let issuance = Self::get_approved_issuance(env.clone(), property_id).expect("No approved issuance");
+        if issuance.extra_shares != extra_shares || issuance.price_per_share != price_per_share {
+            panic!("Issuance terms do not match the approved proposal");
+        }
+        if Self::get_deed(env.clone(), property_id).is_some() {
+            panic!("Property is held as a deed");
+        }
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            panic_with_error!(&env, Error::PropertyLiquidated);
+        }
+        
+        let previous = Self::get_property(env.clone(), property_id);
+        let mut property = previous.clone();
+        property.total_shares = previous.total_shares.checked_add(extra_shares).unwrap_or_else(|| panic_with_error!(&env, Error::AmountOverflow));
+        property.price_per_share = price_per_share;
+        Self::store_repriced_property(&env, &previous, &property);
+        env.storage().instance().remove(&SupplyRegistry::Issuance(property_id));
+        env.storage().instance().remove(&SupplyRegistry::OfferingClosed(property_id));
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("dilution"), property_id, DilutionEvent {
+            extra_shares,
+            price_per_share,
+            previous_total_shares: previous.total_shares,
+            total_shares: property.total_shares,
+        });
+        log!(&env, "{} new shares of property {} issued at {}", extra_shares, property_id, price_per_share);
+    }
+    
+    // View function to get the share issuance co-owners approved for a property, if not yet issued
+    pub fn get_approved_issuance(env: Env, property_id: u64) -> Option<ShareIssuance> {
+        env.storage().instance().get(&SupplyRegistry::Issuance(property_id))
+    }
+    
+    // View function to get a property's open buyout offer, if any
+    pub fn get_buyout(env: Env, property_id: u64) -> Option<BuyoutOffer> {
+        env.storage().instance().get(&BuyoutRegistry::Offer(property_id))
+    }
+    
+    // View function to get the share of a property (in basis points) a buyout bidder must hold to squeeze out the rest
+    pub fn get_squeeze_out_threshold(env: Env, property_id: u64) -> u32 {
+        env.storage().instance().get(&BuyoutRegistry::Threshold(property_id)).unwrap_or(DEFAULT_SQUEEZE_OUT_BPS)
+    }
+    
+    // Function to route a holder's votes on a property to a representative, who votes with them on
+    // proposals whose snapshot is taken while the delegation stands. Delegating to oneself ends it.
+    pub fn delegate_votes(env: Env, owner: Address, property_id: u64, delegate: Address) {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let current = Self::get_delegate(env.clone(), property_id, owner.clone());
+        if current == delegate {
+            panic!("Already delegated to this address");
+        }
+        
+        let balance = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+        if current != owner {
+            let power = Self::get_delegated_votes(env.clone(), property_id, current.clone());
+            Self::set_delegated_votes(&env, property_id, &current, power - balance);
+        }
+        if delegate != owner {
+            let power = Self::get_delegated_votes(env.clone(), property_id, delegate.clone());
+            Self::set_delegated_votes(&env, property_id, &delegate, power + balance);
+        }
+        
+        Self::record_snapshot_value(&env, property_id, GovernanceRegistry::DelegateHistory(property_id, owner.clone()), current);
+        let key = GovernanceRegistry::Delegate(property_id, owner.clone());
+        if delegate == owner {
+            Self::remove_record(&env, &key);
+        } else {
+            Self::store_record(&env, &key, &delegate);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("delegate"), property_id, (owner.clone(), delegate.clone()));
+        log!(&env, "{} delegated votes on property {} to {}", owner, property_id, delegate);
+    }
+    
+    // View function to get who votes with a holder's shares; the holder themself without a delegation
+    pub fn get_delegate(env: Env, property_id: u64, owner: Address) -> Address {
+        Self::load_record(&env, &GovernanceRegistry::Delegate(property_id, owner.clone())).unwrap_or(owner)
+    }
+    
+    // View function to get the votes other holders currently delegate to an address
+    pub fn get_delegated_votes(env: Env, property_id: u64, delegate: Address) -> u64 {
+        Self::load_record(&env, &GovernanceRegistry::Delegated(property_id, delegate)).unwrap_or(0)
+    }
+    
+    // View function to get an address's voting power at a snapshot: its own balance unless it had
+    // delegated it, plus the balances delegated to it
+    pub fn get_votes_at(env: Env, property_id: u64, snapshot_id: u32, account: Address) -> u64 {
+        let delegate = Self::value_at_snapshot(&env, GovernanceRegistry::DelegateHistory(property_id, account.clone()), snapshot_id)
+            .unwrap_or_else(|| Self::get_delegate(env.clone(), property_id, account.clone()));
+        let own = if delegate == account {
+            Self::get_balance_at(env.clone(), property_id, snapshot_id, account.clone())
+        } else {
+            0
+        };
+        let delegated = Self::value_at_snapshot(&env, GovernanceRegistry::DelegatedHistory(property_id, account.clone()), snapshot_id)
+            .unwrap_or_else(|| Self::get_delegated_votes(env.clone(), property_id, account.clone()));
+        own + delegated
+    }
+    
+    // Function to vote for or against an active proposal with the voting power held at its
+    // snapshot. Each address votes once. Returns the weight counted.
+    pub fn vote(env: Env, voter: Address, proposal_id: u64, support: bool) -> u64 {
+        // Authentication
+        voter.require_auth();
+        Self::record_activity(&env, &voter);
+        
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id);
+        if proposal.status != ProposalStatus::Active {
+            panic!("Proposal is not active");
+        }
+        let voted_key = GovernanceRegistry::Voted(proposal_id, voter.clone());
+        if Self::has_record(&env, &voted_key) {
+            panic!("Already voted");
+        }
+        let weight = Self::get_votes_at(env.clone(), proposal.property_id, proposal.snapshot_id, voter.clone());
+        if weight == 0 {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+        
+        if support {
+            proposal.votes_for += weight;
+        } else {
+            proposal.votes_against += weight;
+        }
+        Self::store_record(&env, &voted_key, &support);
+        Self::store_record(&env, &GovernanceRegistry::Proposal(proposal_id), &proposal);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("vote"), proposal.property_id, (proposal_id, voter.clone(), support, weight));
+        log!(&env, "{} voted on proposal {} with {} shares", voter, proposal_id, weight);
+        weight
+    }
+    
+    // View function to get how an address voted on a proposal, if it has
+    pub fn get_vote(env: Env, proposal_id: u64, voter: Address) -> Option<bool> {
+        Self::load_record(&env, &GovernanceRegistry::Voted(proposal_id, voter))
+    }
+    
+    // View function to get a proposal with its current status and tallies
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Proposal {
+        let mut proposal: Proposal = Self::load_record(&env, &GovernanceRegistry::Proposal(proposal_id)).expect("Proposal not found");
+        proposal.status = Self::proposal_status(&env, &proposal);
+        proposal
+    }
+    
+    // View function to get the IDs of a property's proposals, oldest first
+    pub fn get_proposals(env: Env, property_id: u64) -> Vec<u64> {
+        env.storage().instance().get(&GovernanceRegistry::Proposals(property_id)).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to switch a property to time-weighted income (registrant or admin, before any income
+    // is deposited). Each deposit is then shared by the share-seconds held since the previous one,
+    // so shares bought just before a deposit earn only for the time they were held.
+    pub fn enable_time_weighted_income(env: Env, caller: Address, property_id: u64) {
+        // Check registrant or admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        if caller != admin && caller != Self::get_registrant(env.clone(), property_id) {
+            panic!("Not authorized to configure income");
+        }
+        caller.require_auth();
+        
+        if Self::is_time_weighted(env.clone(), property_id) {
+            panic!("Time-weighted income already enabled");
+        }
+        if env.storage().instance().has(&DividendRegistry::AccPerShare(property_id)) {
+            panic!("Income already deposited");
+        }
+        
+        env.storage().instance().set(&DividendRegistry::TimeWeighted(property_id), &env.ledger().timestamp());
+        env.storage().instance().set(&DividendRegistry::Clock(property_id), &ShareClock {
+            period: 0,
+            share_seconds: 0,
+            last_update: env.ledger().timestamp(),
+        });
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Time-weighted income enabled for property {}", property_id);
+    }
+    
+    // View function to check whether a property shares income by holding time
+    pub fn is_time_weighted(env: Env, property_id: u64) -> bool {
+        env.storage().instance().has(&DividendRegistry::TimeWeighted(property_id))
+    }
+    
+    // Function to pay out a holder's share of a property's deposited income
+    pub fn claim_dividends(env: Env, property_id: u64, owner: Address) -> Money {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let amount = Self::take_dividends(&env, property_id, &owner);
+        Self::pay(&env, &env.current_contract_address(), &owner, amount);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("dividend"), property_id, (owner.clone(), amount));
+        log!(&env, "{} claimed {} in income from property {}", owner, amount, property_id);
+        amount
+    }
+    
+    // Function to turn a holder's claimable income into more shares of the same property at the
+    // current price and fee. Whatever does not cover a whole share stays claimable.
+    pub fn claim_and_reinvest(env: Env, property_id: u64, owner: Address) -> u64 {
+        // Authentication
+        owner.require_auth();
+        Self::record_activity(&env, &owner);
+        
+        let amount = Self::take_dividends(&env, property_id, &owner);
+        let property = Self::get_property(env.clone(), property_id);
+        let cost_per_share = Self::current_price(&env, &property) * (10000 + Self::get_platform_fee(env.clone()) as Money) / 10000;
+        let affordable = amount.checked_div(cost_per_share).unwrap_or(0);
+        let mut shares = (affordable.min(u64::MAX as Money) as u64).min(Self::get_available_shares(env.clone(), property_id));
+        
+        // Fee rounding can leave the estimate one share too high
+        let plan = loop {
+            if shares == 0 {
+                panic!("Income does not cover a share");
+            }
+            let intent = PurchaseIntent {
+                property_id,
+                buyer: owner.clone(),
+                shares,
+                locked_price: None,
+            };
+            let plan = Self::validate_purchase(env.clone(), intent).unwrap_or_else(|error| panic_with_error!(&env, error));
+            if plan.total_price + plan.platform_fee <= amount {
+                break plan;
+            }
+            shares -= 1;
+        };
+        
+        // The contract already holds the income, so it pays for the purchase
+        Self::apply_purchase(&env, &plan);
+        Self::settle_purchase(&env, &plan, &env.current_contract_address());
+        
+        let leftover = amount - plan.total_price - plan.platform_fee;
+        if leftover > 0 {
+            env.storage().persistent().set(&DividendRegistry::Pending(property_id, owner.clone()), &leftover);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, symbol_short!("reinvest"), property_id, (owner.clone(), amount - leftover, shares));
+        log!(&env, "{} reinvested income into {} shares of property {}", owner, shares, property_id);
+        shares
+    }
+    
+    // View function to get the income a holder can currently claim from a property
+    pub fn get_claimable(env: Env, property_id: u64, owner: Address) -> Money {
+        if Self::is_time_weighted(env.clone(), property_id) {
+            let pending: Money = env.storage().persistent().get(&DividendRegistry::Pending(property_id, owner.clone())).unwrap_or(0);
+            let shares = Self::earning_shares(&env, property_id, &owner);
+            return pending + Self::time_weighted_accrual(&env, property_id, &owner, shares).0;
+        }
+        
+        let acc_per_share: u128 = env.storage().instance().get(&DividendRegistry::AccPerShare(property_id)).unwrap_or(0);
+        let checkpoint: u128 = env.storage().persistent().get(&DividendRegistry::Checkpoint(property_id, owner.clone())).unwrap_or(0);
+        let pending: Money = env.storage().persistent().get(&DividendRegistry::Pending(property_id, owner.clone())).unwrap_or(0);
+        let shares = Self::earning_shares(&env, property_id, &owner);
+        pending + (shares as u128) * (acc_per_share - checkpoint) / DIVIDEND_PRECISION
+    }
+    
+    // Function to set the platform fee charged on top of every primary purchase, in basis points (admin only)
+    pub fn set_platform_fee(env: Env, fee_bps: u32) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if fee_bps > MAX_PLATFORM_FEE_BPS {
+            panic!("Platform fee too high");
+        }
+        env.storage().instance().set(&PLATFORM_FEE_BPS, &fee_bps);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((symbol_short!("fee_rate"),), fee_bps);
+        log!(&env, "Platform fee set to {} bps", fee_bps);
+    }
+    
+    // Function to send all collected platform fees to an address (admin only)
+    pub fn withdraw_fees(env: Env, to: Address) -> Money {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        let fees = Self::get_fee_balance(env.clone());
+        if fees == 0 {
+            panic!("No fees to withdraw");
+        }
+        env.storage().instance().set(&FEE_BALANCE, &0u128);
+        Self::pay(&env, &env.current_contract_address(), &to, fees);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((symbol_short!("fee_wdraw"), to.clone()), fees);
+        log!(&env, "Withdrew {} in platform fees to {}", fees, to);
+        fees
+    }
+    
+    // Function for a keeper to withdraw the rewards they have accrued
+    pub fn claim_keeper_rewards(env: Env, keeper: Address) -> Money {
+        keeper.require_auth();
+        
+        let key = KeeperRegistry::Balance(keeper.clone());
+        let reward: Money = Self::load_record(&env, &key).unwrap_or(0);
+        if reward == 0 {
+            panic!("Nothing to claim");
+        }
+        Self::remove_record(&env, &key);
+        Self::pay(&env, &env.current_contract_address(), &keeper, reward);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        env.events().publish((symbol_short!("keep_clm"), keeper.clone()), reward);
+        log!(&env, "Keeper {} claimed {} in rewards", keeper, reward);
+        reward
+    }
+
+    // View function to get the platform fee in basis points
+    pub fn get_platform_fee(env: Env) -> u32 {
+        env.storage().instance().get(&PLATFORM_FEE_BPS).unwrap_or(0)
+    }
+    
+    // View function to get the platform fees collected and not yet withdrawn
+    pub fn get_fee_balance(env: Env) -> Money {
+        env.storage().instance().get(&FEE_BALANCE).unwrap_or(0)
+    }
+    
+    // View function to get a keeper's accrued rewards
+    pub fn get_keeper_balance(env: Env, keeper: Address) -> Money {
+        Self::load_record(&env, &KeeperRegistry::Balance(keeper)).unwrap_or(0)
+    }
+    
+    // View function to get the keeper rate of a maintenance function, if set
+    pub fn get_keeper_rate(env: Env, function: Symbol) -> Option<KeeperRate> {
+        env.storage().instance().get(&KeeperRegistry::Rate(function))
+    }
+    
+    // Function to set the volume bucket retention in days (admin only)
+    pub fn set_volume_retention(env: Env, days: u64) {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        if days == 0 {
+            panic!("Retention must be at least one day");
+        }
+        
+        env.storage().instance().set(&VOLUME_RETENTION, &days);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Volume retention set to {} days", days);
+    }
+    
+    // Function to evict volume buckets older than the retention window, at most max_days per call.
+    // Pass a property ID to clean that property's buckets or None for the global buckets, and a
+    // keeper address to collect the keeper reward for the days evicted.
+    // Returns the number of days evicted.
+    pub fn cleanup_daily_volume(env: Env, property_id: Option<u64>, max_days: u64, keeper: Option<Address>) -> u64 {
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let retention = Self::get_volume_retention(env.clone());
+        if today < retention {
+            return 0;
+        }
+        let cutoff = today - retention;
+        
+        let first_day_key = match property_id {
+            Some(id) => VolumeRegistry::PropertyOldest(id),
+            None => VolumeRegistry::GlobalOldest,
+        };
+        let mut day: u64 = match env.storage().instance().get(&first_day_key) {
+            Some(day) => day,
+            None => return 0,
+        };
+        
+        let mut evicted = 0;
+        while day < cutoff && evicted < max_days {
+            match property_id {
+                Some(id) => env.storage().instance().remove(&VolumeRegistry::Property(id, day)),
+                None => env.storage().instance().remove(&VolumeRegistry::Global(day)),
+            }
+            day += 1;
+            evicted += 1;
+        }
+        
+        env.storage().instance().set(&first_day_key, &day);
+        if let Some(keeper) = keeper {
+            Self::reward_keeper(&env, &keeper, symbol_short!("cleanup"), evicted, 0);
+        }
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Evicted {} days of volume buckets", evicted);
+        
+        evicted
+    }
+    
+    // Function to sum an owner's shares over a window of their property list, resumable across calls.
+    // Returns the partial sum and the cursor to pass next, or None once the list is exhausted.
+    pub fn get_total_shares_owned_page(env: Env, owner: Address, cursor: u32, max_items: u32) -> (u64, Option<u32>) {
+        let properties = Self::get_user_properties(env.clone(), owner.clone());
+        let end = properties.len().min(cursor.saturating_add(max_items));
+        let mut partial_sum = 0;
+        
+        for index in cursor..end {
+            let ownership = Self::get_ownership(env.clone(), properties.get(index).unwrap(), owner.clone());
+            partial_sum += ownership.shares;
+        }
+        
+        let next_cursor = if end < properties.len() { Some(end) } else { None };
+        (partial_sum, next_cursor)
+    }
+    
+    // View function to get an owner's total shares from the incrementally maintained cache
+    pub fn get_total_shares_owned_cached(env: Env, owner: Address) -> u64 {
+        Self::load_record(&env, &HoldingsRegistry::TotalShares(owner)).unwrap_or(0)
+    }
+    
+    // Function to add holders that predate the holder list to a property's list, in batches (admin only).
+    // Candidates without shares or already listed are skipped. Returns the number of holders added.
+    pub fn backfill_holders(env: Env, property_id: u64, owners: Vec<Address>) -> u32 {
+        // Check admin authorization
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        admin.require_auth();
+        
+        Self::get_property(env.clone(), property_id);
+        let holders_key = SupplyRegistry::Holders(property_id);
+        let mut holders: Vec<Address> = Self::load_record(&env, &holders_key).unwrap_or(Vec::new(&env));
+        let mut added = 0;
+        for owner in owners.iter() {
+            if holders.contains(&owner) || Self::get_ownership(env.clone(), property_id, owner.clone()).shares == 0 {
+                continue;
+            }
+            holders.push_back(owner.clone());
+            Self::track_user_property(&env, &owner, property_id);
+            added += 1;
+        }
+        Self::store_record(&env, &holders_key, &holders);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        log!(&env, "Backfilled {} holders of property {}", added, property_id);
+        added
+    }
+
+    // View function to check a property against the protocol invariants. Returns the codes of
+    // the violated ones, empty when healthy:
+    // oversold - more shares allocated than exist
+    // holders  - holder balances do not add up to the shares allocated
+    // founder  - the founder allocation exceeds the shares allocated
+    // stats    - platform counters cannot account for this property
+    // escrow   - bundle custody does not cover outstanding bundle shares
+    pub fn check_invariants(env: Env, property_id: u64) -> Vec<Symbol> {
+        let property = Self::get_property(env.clone(), property_id);
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id);
+        let mut violations = Vec::new(&env);
+        
+        if shares_sold > property.total_shares {
+            violations.push_back(symbol_short!("oversold"));
+        }
+        
+        let holders: Vec<Address> = Self::load_record(&env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(&env));
+        let mut held: u64 = 0;
+        for holder in holders.iter() {
+            held = held.saturating_add(Self::get_ownership(env.clone(), property_id, holder).shares);
+        }
+        if held != shares_sold {
+            violations.push_back(symbol_short!("holders"));
+        }
+        
+        if let Some(allocation) = Self::get_founder_allocation(env.clone(), property_id) {
+            if allocation.shares > shares_sold {
+                violations.push_back(symbol_short!("founder"));
+            }
+        }
+        
+        let stats = Self::get_property_stats(env.clone());
+        let value_stats = Self::get_value_stats(env.clone());
+        let market_cap = if Self::is_withdrawn(&env, property_id) { 0 } else { Self::market_cap(&property) };
+        if stats.total_properties == 0
+            || stats.verified_properties > stats.total_properties
+            || (property.is_verified && stats.verified_properties == 0)
+            || market_cap > value_stats.total_market_cap
+            || (property.is_verified && market_cap > value_stats.verified_market_cap)
+        {
+            violations.push_back(symbol_short!("stats"));
+        }
+        
+        let bundle_counter: u64 = env.storage().instance().get(&BUNDLE_COUNTER).unwrap_or(0);
+        let mut owed: u64 = 0;
+        for bundle_id in 1..=bundle_counter {
+            if let Some(bundle) = env.storage().instance().get::<BundleRegistry, Bundle>(&BundleRegistry::Bundle(bundle_id)) {
+                let outstanding: u64 = env.storage().instance().get(&BundleRegistry::Outstanding(bundle_id)).unwrap_or(0);
+                for (component_id, weight) in bundle.components.iter() {
+                    if component_id == property_id {
+                        owed = owed.saturating_add(weight.saturating_mul(outstanding));
+                    }
+                }
+            }
+        }
+        if Self::get_ownership(env.clone(), property_id, env.current_contract_address()).shares < owed {
+            violations.push_back(symbol_short!("escrow"));
+        }
+        
+        violations
+    }
+    
+    // View function to run check_invariants over limit properties after the first start,
+    // returning (property ID, violation code) pairs
+    pub fn check_all(env: Env, start: u64, limit: u64) -> Vec<(u64, Symbol)> {
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let end_idx = property_counter.min(start.saturating_add(limit));
+        let mut violations = Vec::new(&env);
+        
+        for i in (start + 1)..=end_idx {
+            let property_id = Self::property_id_at(&env, i);
+            if Self::load_property(&env, property_id).is_none() {
+                continue;
+            }
+            for code in Self::check_invariants(env.clone(), property_id).iter() {
+                violations.push_back((property_id, code));
+            }
+        }
+        
+        violations
+    }
+    
+    // Function to list compact summaries of the properties matching a filter, a page at a time;
+    // pass None for the first page. Scans at most MAX_FILTER_SCAN positions per call, so a page
+    // can hold fewer than limit items while a next_cursor is still returned.
+    pub fn list_properties(env: Env, filter: PropertyFilter, cursor: Option<Bytes>, limit: u64) -> PropertyPage {
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let mut properties = Vec::new(&env);
+        
+        let mut position = Self::decode_cursor(&env, CURSOR_FILTERED, &cursor);
+        let scan_end = property_counter.min(position.saturating_add(MAX_FILTER_SCAN));
+        while position < scan_end && (properties.len() as u64) < limit {
+            position += 1;
+            let property_id = Self::property_id_at(&env, position);
+            if let Some(property) = Self::load_property(&env, property_id) {
+                if Self::matches_filter(&env, &property, &filter) {
+                    properties.push_back(Self::reduce_property(&env, &property));
+                }
+            }
+        }
+        
+        PropertyPage {
+            items: properties,
+            next_cursor: Self::next_cursor(&env, CURSOR_FILTERED, position, property_counter),
+            total: None,
+        }
+    }
+    
+    // Function to list compact property summaries a page at a time; pass None for the first page
+    pub fn list_properties_v2(env: Env, cursor: Option<Bytes>, limit: u64) -> PropertyPage {
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let mut properties = Vec::new(&env);
+        
+        let start_idx = Self::decode_cursor(&env, CURSOR_PROPERTIES, &cursor);
+        let end_idx = property_counter.min(start_idx.saturating_add(limit));
+        
+        for i in (start_idx + 1)..=end_idx {
+            let property_id = Self::property_id_at(&env, i);
+            if Self::is_withdrawn(&env, property_id) {
+                continue;
+            }
+            if let Some(property) = Self::load_property(&env, property_id) {
+                properties.push_back(Self::reduce_property(&env, &property));
+            }
+        }
+        
+        let withdrawn: u64 = env.storage().instance().get(&WITHDRAWN_COUNT).unwrap_or(0);
+        PropertyPage {
+            items: properties,
+            next_cursor: Self::next_cursor(&env, CURSOR_PROPERTIES, end_idx, property_counter),
+            total: Some(Self::get_property_stats(env.clone()).total_properties - withdrawn),
+        }
+    }
+    
+    // Function to list an owner's positions a page at a time; pass None for the first page
+    pub fn get_user_holdings(env: Env, owner: Address, cursor: Option<Bytes>, limit: u64) -> HoldingsPage {
+        let properties = Self::get_user_properties(env.clone(), owner.clone());
+        let mut holdings = Vec::new(&env);
+        
+        let start = Self::decode_cursor(&env, CURSOR_HOLDINGS, &cursor);
+        let end = (properties.len() as u64).min(start.saturating_add(limit));
+        
+        for index in start..end {
+            holdings.push_back(Self::get_ownership(env.clone(), properties.get(index as u32).unwrap(), owner.clone()));
+        }
+        
+        HoldingsPage {
+            items: holdings,
+            next_cursor: Self::next_cursor(&env, CURSOR_HOLDINGS, end, properties.len() as u64),
+            total: Some(properties.len() as u64),
+        }
+    }
+}
+
+impl RealEstateNFT {
+    // Read a per-user or per-entity record, bumping its TTL, falling back to a record still in
+    // instance storage
+    fn load_record<K: IntoVal<Env, Val>, V: TryFromVal<Env, Val>>(env: &Env, key: &K) -> Option<V> {
+        if let Some(record) = env.storage().persistent().get(key) {
+            env.storage().persistent().extend_ttl(key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+            return Some(record);
+        }
+        env.storage().instance().get(key)
+    }
+    
+    // Write a per-user or per-entity record to persistent storage, retiring any instance copy
+    fn store_record<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(env: &Env, key: &K, record: &V) {
+        env.storage().persistent().set(key, record);
+        env.storage().persistent().extend_ttl(key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+        env.storage().instance().remove(key);
+    }
+    
+    // Whether a per-user or per-entity record exists in either storage
+    fn has_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+        env.storage().persistent().has(key) || env.storage().instance().has(key)
+    }
+    
+    // Delete a per-user or per-entity record from both storages
+    fn remove_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+        env.storage().persistent().remove(key);
+        env.storage().instance().remove(key);
+    }
+    
+    // Read a property record, falling back to records still in instance storage or the legacy layout
+    fn load_property(env: &Env, property_id: u64) -> Option<PropertyV2> {
+        let key = PropertyRegistry::PropertyV2(property_id);
+        if let Some(property) = env.storage().persistent().get(&key) {
+            env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+            return Some(property);
+        }
+        if let Some(property) = env.storage().instance().get(&key) {
+            return Some(property);
+        }
+        env.storage().instance().get::<PropertyRegistry, Property>(&PropertyRegistry::Property(property_id)).map(PropertyV2::from)
+    }
+    
+    // Write a property record to persistent storage, retiring any instance or legacy copy
+    fn store_property(env: &Env, property: &PropertyV2) {
+        let key = PropertyRegistry::PropertyV2(property.property_id);
+        env.storage().persistent().set(&key, property);
+        env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+        env.storage().instance().remove(&key);
+        env.storage().instance().remove(&PropertyRegistry::Property(property.property_id));
+    }
+    
+    // Read an ownership record, falling back to a record still in instance storage
+    fn load_ownership(env: &Env, property_id: u64, owner: &Address) -> Option<OwnershipShare> {
+        let key = OwnershipRegistry::Ownership(property_id, owner.clone());
+        if let Some(ownership) = env.storage().persistent().get(&key) {
+            env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+            return Some(ownership);
+        }
+        env.storage().instance().get(&key)
+    }
+    
+    // Write an ownership record to persistent storage, retiring any instance copy. Income accrued
+    // on the previous balance is credited first, so every balance change settles dividends.
+    fn store_ownership(env: &Env, ownership: &OwnershipShare) {
+        if ownership.owner == env.current_contract_address() {
+            // Custody balances are left out of the share-second clock, which has to catch up first
+            Self::advance_income_clock(env, ownership.property_id);
+        }
+        let previous = Self::load_ownership(env, ownership.property_id, &ownership.owner).map_or(0, |previous| previous.shares);
+        Self::settle_dividends(env, ownership.property_id, &ownership.owner, previous);
+        Self::record_snapshot_balance(env, ownership.property_id, &ownership.owner, previous);
+        Self::follow_delegation(env, ownership.property_id, &ownership.owner, previous, ownership.shares);
+        
+        let key = OwnershipRegistry::Ownership(ownership.property_id, ownership.owner.clone());
+        env.storage().persistent().set(&key, ownership);
+        env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+        env.storage().instance().remove(&key);
+    }
+    
+    // Credit an owner with new shares of a property, adding them to the shares sold
+    fn credit_shares(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        let timestamp = env.ledger().timestamp();
+        let existing = Self::load_ownership(env, property_id, owner);
+        let ownership = match existing.clone() {
+            Some(existing) => OwnershipShare {
+                shares: existing.shares + shares,
+                last_updated: timestamp,
+                ..existing
+            },
+            None => OwnershipShare {
+                property_id,
+                owner: owner.clone(),
+                shares,
+                purchase_time: timestamp,
+                last_updated: timestamp,
+            },
+        };
+        if existing.is_none() {
+            Self::add_user_property(env, owner, property_id);
+            let mut stats = Self::get_property_stats(env.clone());
+            stats.total_owners += 1;
+            env.storage().instance().set(&PROPERTY_STATS, &stats);
+        }
+        Self::store_ownership(env, &ownership);
+        Self::advance_income_clock(env, property_id);
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id);
+        env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &(shares_sold + shares));
+        Self::add_cached_total(env, owner, shares);
+    }
+    
+    // Whether an account may run a property's operations: the admin, and the appointed manager or,
+    // until one is appointed, the registrant
+    fn is_property_manager(env: &Env, property_id: u64, account: &Address) -> bool {
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        let manager = Self::get_property_manager(env.clone(), property_id).unwrap_or_else(|| Self::get_registrant(env.clone(), property_id));
+        *account == admin || *account == manager
+    }
+    
+    // Store a property's new manager and announce the change
+    fn assign_manager(env: &Env, property_id: u64, manager: Address) {
+        let previous = Self::get_property_manager(env.clone(), property_id);
+        // Fees accrued so far were earned by the outgoing manager, or the registrant if there was none
+        let outgoing = previous.clone().unwrap_or_else(|| Self::get_registrant(env.clone(), property_id));
+        Self::pay_management_fee(env, property_id, &outgoing);
+        env.storage().instance().set(&PropertyRegistry::Manager(property_id), &manager);
+        Self::publish_property_event(env, symbol_short!("manager"), property_id, (previous, manager));
+    }
+    
+    // Panic unless a property can be split or merged: it must still be listed, not liquidated or
+    // deeded, with no buyout, buyback or installment plan open and no shares in custody
+    fn require_restructurable(env: &Env, property_id: u64) {
+        if Self::is_withdrawn(env, property_id) {
+            panic_with_error!(env, Error::PropertyDelisted);
+        }
+        if Self::get_liquidation(env.clone(), property_id).is_some() {
+            panic_with_error!(env, Error::PropertyLiquidated);
+        }
+        if Self::get_deed(env.clone(), property_id).is_some() {
+            panic!("Property is held as a deed");
+        }
+        if Self::get_buyout(env.clone(), property_id).is_some() || Self::get_buyback(env.clone(), property_id).is_some() {
+            panic!("Buyout or buyback open");
+        }
+        if Self::get_reserved_shares(env.clone(), property_id) > 0 {
+            panic!("Installment plans outstanding");
+        }
+        if Self::get_ownership(env.clone(), property_id, env.current_contract_address()).shares > 0 {
+            panic!("Shares held in custody");
+        }
+    }
+    
+    // Mark a property split or merged from verified properties as verified, without its own checklist review
+    fn inherit_verification(env: &Env, property_id: u64) {
+        let mut property = Self::get_property(env.clone(), property_id);
+        property.is_verified = true;
+        
+        let mut stats = Self::get_property_stats(env.clone());
+        stats.verified_properties += 1;
+        let mut value_stats = Self::get_value_stats(env.clone());
+        value_stats.verified_market_cap += Self::market_cap(&property);
+        
+        Self::store_property(env, &property);
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+    }
+    
+    // Check milestone definitions, which must each be positive and add up to 100
+    fn build_milestones(env: &Env, milestones: Vec<(String, u32)>) -> Vec<Milestone> {
+        let mut total_percentage: u32 = 0;
+        let mut stored = Vec::new(env);
+        for (description, percentage) in milestones.iter() {
+            if percentage == 0 {
+                panic!("Milestone percentage must be positive");
+            }
+            total_percentage += percentage;
+            stored.push_back(Milestone {
+                description,
+                percentage,
+                is_released: false,
+                released_amount: 0,
+                released_at: 0,
+            });
+        }
+        if total_percentage != 100 {
+            panic!("Milestone percentages must add up to 100");
+        }
+        stored
+    }
+    
+    // Replace an escrowed property's milestones, as long as none has been released
+    fn configure_milestones(env: &Env, property_id: u64, milestones: Vec<(String, u32)>) {
+        if !Self::is_milestone_escrow(env.clone(), property_id) {
+            panic!("Property does not escrow proceeds");
+        }
+        if Self::get_milestones(env.clone(), property_id).iter().any(|milestone| milestone.is_released) {
+            panic!("Milestones cannot change after a release");
+        }
+        
+        let stored = Self::build_milestones(env, milestones);
+        env.storage().instance().set(&EscrowRegistry::Milestones(property_id), &stored);
+        log!(env, "Configured {} milestones for property {}", stored.len(), property_id);
+    }
+    
+    // Pay a milestone's percentage of the proceeds escrowed so far to the registrant, capped by
+    // what is left in escrow. Returns the amount released.
+    fn release_escrowed_milestone(env: &Env, property_id: u64, milestone_index: u32) -> Money {
+        if env.storage().instance().has(&EscrowRegistry::Failed(property_id)) {
+            panic!("Offering has failed");
+        }
+        
+        let mut milestones = Self::get_milestones(env.clone(), property_id);
+        let mut milestone = milestones.get(milestone_index).expect("Milestone not found");
+        if milestone.is_released {
+            panic!("Milestone already released");
+        }
+        
+        let proceeds: Money = env.storage().instance().get(&EscrowRegistry::Proceeds(property_id)).unwrap_or(0);
+        if proceeds == 0 {
+            panic!("No proceeds escrowed");
+        }
+        let released: Money = env.storage().instance().get(&EscrowRegistry::Released(property_id)).unwrap_or(0);
+        let amount = (proceeds * milestone.percentage as Money / 100).min(Self::get_escrow_balance(env.clone(), property_id));
+        
+        milestone.is_released = true;
+        milestone.released_amount = amount;
+        milestone.released_at = env.ledger().timestamp();
+        milestones.set(milestone_index, milestone);
+        
+        env.storage().instance().set(&EscrowRegistry::Milestones(property_id), &milestones);
+        env.storage().instance().set(&EscrowRegistry::Released(property_id), &(released + amount));
+        
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        Self::pay(env, &env.current_contract_address(), &registrant, amount);
+        
+        Self::publish_property_event(env, symbol_short!("release"), property_id, (registrant, milestone_index, amount));
+        log!(env, "Milestone {} of property {} released {}", milestone_index, property_id, amount);
+        amount
+    }
+    
+    // Burn all of an owner's shares in a property, returning how many there were
+    fn burn_position(env: &Env, property_id: u64, owner: &Address) -> u64 {
+        let shares = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+        if shares > 0 {
+            Self::burn_shares(env, property_id, owner, shares);
+        }
+        shares
+    }
+    
+    // Burn all of an owner's shares in a property's additional classes, returning them to each class's supply
+    fn burn_class_positions(env: &Env, property_id: u64, owner: &Address) {
+        for share_class in Self::get_share_classes(env.clone(), property_id).iter() {
+            let balance = Self::get_class_balance(env.clone(), property_id, share_class.class_id, owner.clone());
+            if balance == 0 {
+                continue;
+            }
+            Self::set_class_balance(env, property_id, share_class.class_id, owner, balance, 0);
+            let sold_key = ClassRegistry::Sold(property_id, share_class.class_id);
+            let sold: u64 = env.storage().instance().get(&sold_key).unwrap_or(0);
+            env.storage().instance().set(&sold_key, &(sold - balance));
+        }
+    }
+    
+    // Burn some of an owner's shares in a property, taking them out of the shares sold
+    fn burn_shares(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        let mut ownership = Self::get_ownership(env.clone(), property_id, owner.clone());
+        ownership.shares -= shares;
+        ownership.last_updated = env.ledger().timestamp();
+        Self::store_ownership(env, &ownership);
+        Self::sub_cached_total(env, owner, shares);
+        Self::advance_income_clock(env, property_id);
+        let shares_sold = Self::get_shares_sold(env.clone(), property_id);
+        env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &(shares_sold - shares));
+    }
+    
+    // Append a property to the deeds an address holds
+    fn index_deed(env: &Env, owner: &Address, property_id: u64) {
+        let key = DeedRegistry::Owned(owner.clone());
+        let mut deeds: Vec<u64> = Self::load_record(env, &key).unwrap_or(Vec::new(env));
+        deeds.push_back(property_id);
+        Self::store_record(env, &key, &deeds);
+    }
+    
+    // Remove a property from the deeds an address holds
+    fn unindex_deed(env: &Env, owner: &Address, property_id: u64) {
+        let key = DeedRegistry::Owned(owner.clone());
+        let mut deeds: Vec<u64> = Self::load_record(env, &key).unwrap_or(Vec::new(env));
+        if let Some(index) = deeds.first_index_of(property_id) {
+            deeds.remove(index);
+        }
+        if deeds.is_empty() {
+            Self::remove_record(env, &key);
+        } else {
+            Self::store_record(env, &key, &deeds);
+        }
+    }
+    
+    // Keep a delegate's received votes in step with a delegating holder's balance
+    fn follow_delegation(env: &Env, property_id: u64, owner: &Address, previous: u64, shares: u64) {
+        let delegate = Self::get_delegate(env.clone(), property_id, owner.clone());
+        if delegate == *owner || previous == shares {
+            return;
+        }
+        let power = Self::get_delegated_votes(env.clone(), property_id, delegate.clone());
+        Self::set_delegated_votes(env, property_id, &delegate, power + shares - previous);
+    }
+    
+    // Write a delegate's received votes, keeping the old value for snapshots taken since the last change
+    fn set_delegated_votes(env: &Env, property_id: u64, delegate: &Address, power: u64) {
+        let previous = Self::get_delegated_votes(env.clone(), property_id, delegate.clone());
+        Self::record_snapshot_value(env, property_id, GovernanceRegistry::DelegatedHistory(property_id, delegate.clone()), previous);
+        let key = GovernanceRegistry::Delegated(property_id, delegate.clone());
+        if power == 0 {
+            Self::remove_record(env, &key);
+        } else {
+            Self::store_record(env, &key, &power);
+        }
+    }
+    
+    // Before a governance value changes, keep the old one for every snapshot taken since it was
+    // last recorded, as record_snapshot_balance does for balances
+    fn record_snapshot_value<V>(env: &Env, property_id: u64, key: GovernanceRegistry, previous: V)
+    where
+        V: Clone + TryFromVal<Env, Val>,
+        Val: TryFromVal<Env, V>,
+    {
+        let latest: u32 = env.storage().instance().get(&SnapshotRegistry::Count(property_id)).unwrap_or(0);
+        if latest == 0 {
+            return;
+        }
+        let mut history: Vec<(u32, V)> = Self::load_record(env, &key).unwrap_or(Vec::new(env));
+        let recorded_up_to = history.last().map_or(0, |(snapshot_id, _)| snapshot_id);
+        if recorded_up_to < latest {
+            history.push_back((latest, previous));
+            Self::store_record(env, &key, &history);
+        }
+    }
+    
+    // Governance value a snapshot saw, or None when it has not changed since and the current one applies
+    fn value_at_snapshot<V>(env: &Env, key: GovernanceRegistry, snapshot_id: u32) -> Option<V>
+    where
+        V: Clone + TryFromVal<Env, Val>,
+        Val: TryFromVal<Env, V>,
+    {
+        let history: Vec<(u32, V)> = Self::load_record(env, &key).unwrap_or(Vec::new(env));
+        history.iter().find(|(recorded_at, _)| *recorded_at >= snapshot_id).map(|(_, value)| value)
+    }
+    
+    // Before a balance changes, keep the old balance for every snapshot taken since it was last
+    // recorded. Balances are only written when they change, so taking a snapshot is O(1).
+    fn record_snapshot_balance(env: &Env, property_id: u64, owner: &Address, previous: u64) {
+        let latest: u32 = env.storage().instance().get(&SnapshotRegistry::Count(property_id)).unwrap_or(0);
+        if latest == 0 {
+            return;
+        }
+        
+        let key = SnapshotRegistry::History(property_id, owner.clone());
+        let mut history: Vec<(u32, u64)> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        let recorded_up_to = history.last().map_or(0, |(snapshot_id, _)| snapshot_id);
+        if recorded_up_to < latest {
+            // The entry covers every snapshot after the previous entry, up to and including latest
+            history.push_back((latest, previous));
+            env.storage().persistent().set(&key, &history);
+            env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+        }
+    }
+    
+    // Record a new snapshot of a property's balances and return its ID
+    fn take_snapshot(env: &Env, property_id: u64) -> u32 {
+        let count_key = SnapshotRegistry::Count(property_id);
+        let snapshot_id: u32 = env.storage().instance().get(&count_key).unwrap_or(0) + 1;
+        let snapshot = Snapshot {
+            snapshot_id,
+            ledger: env.ledger().sequence(),
+            timestamp: env.ledger().timestamp(),
+            shares_sold: Self::get_shares_sold(env.clone(), property_id),
+        };
+        env.storage().instance().set(&count_key, &snapshot_id);
+        Self::store_record(env, &SnapshotRegistry::Snapshot(property_id, snapshot_id), &snapshot);
+        Self::publish_property_event(env, symbol_short!("snapshot"), property_id, snapshot);
+        snapshot_id
+    }
+    
+    // Status of a proposal as of now: an active one is decided once its voting period is over. It
+    // passes when the votes cast reach its quorum of the shares sold at its snapshot and the votes
+    // for exceed its threshold of the votes cast.
+    fn proposal_status(env: &Env, proposal: &Proposal) -> ProposalStatus {
+        if proposal.status != ProposalStatus::Active || env.ledger().timestamp() < proposal.voting_ends {
+            return proposal.status;
+        }
+        let cast = (proposal.votes_for + proposal.votes_against) as u128;
+        let eligible = Self::get_snapshot(env.clone(), proposal.property_id, proposal.snapshot_id).shares_sold as u128;
+        let quorum_met = cast * 10000 >= eligible * proposal.quorum_bps as u128;
+        if quorum_met && proposal.votes_for as u128 * 10000 > cast * proposal.threshold_bps as u128 {
+            ProposalStatus::Succeeded
+        } else {
+            ProposalStatus::Defeated
+        }
+    }
+    
+    // Credit a holder with the income accrued on their balance since their last checkpoint.
+    // Shares in the contract's custody earn nothing, so its checkpoint just moves forward.
+    fn settle_dividends(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        let shares = if *owner == env.current_contract_address() { 0 } else { shares };
+        
+        if Self::is_time_weighted(env.clone(), property_id) {
+            let (accrued, clock) = Self::time_weighted_accrual(env, property_id, owner, shares);
+            Self::credit_pending(env, property_id, owner, accrued);
+            let clock_key = DividendRegistry::HolderClock(property_id, owner.clone());
+            env.storage().persistent().set(&clock_key, &clock);
+            env.storage().persistent().extend_ttl(&clock_key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+            return;
+        }
+        
+        let acc_per_share: u128 = env.storage().instance().get(&DividendRegistry::AccPerShare(property_id)).unwrap_or(0);
+        let checkpoint_key = DividendRegistry::Checkpoint(property_id, owner.clone());
+        let checkpoint: u128 = env.storage().persistent().get(&checkpoint_key).unwrap_or(0);
+        if checkpoint == acc_per_share {
+            return;
+        }
+        
+        let accrued = (shares as u128) * (acc_per_share - checkpoint) / DIVIDEND_PRECISION;
+        Self::credit_pending(env, property_id, owner, accrued);
+        env.storage().persistent().set(&checkpoint_key, &acc_per_share);
+        env.storage().persistent().extend_ttl(&checkpoint_key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+    }
+    
+    // Look up an additional share class of a property
+    fn get_share_class(env: &Env, property_id: u64, class_id: u32) -> ShareClass {
+        let classes = Self::get_share_classes(env.clone(), property_id);
+        class_id.checked_sub(1).and_then(|index| classes.get(index)).expect("Share class not found")
+    }
+    
+    // Credit a class holder's income accrued since their last checkpoint
+    fn settle_class_dividends(env: &Env, property_id: u64, class_id: u32, owner: &Address, balance: u64) {
+        let acc_per_share: u128 = env.storage().instance().get(&ClassRegistry::AccPerShare(property_id, class_id)).unwrap_or(0);
+        let checkpoint_key = ClassRegistry::Checkpoint(property_id, class_id, owner.clone());
+        let checkpoint: u128 = Self::load_record(env, &checkpoint_key).unwrap_or(0);
+        let accrued = (balance as u128) * (acc_per_share - checkpoint) / DIVIDEND_PRECISION;
+        if accrued > 0 {
+            let pending_key = ClassRegistry::Pending(property_id, class_id, owner.clone());
+            let pending: Money = Self::load_record(env, &pending_key).unwrap_or(0);
+            Self::store_record(env, &pending_key, &(pending + accrued));
+        }
+        Self::store_record(env, &checkpoint_key, &acc_per_share);
+    }
+    
+    // Write a class balance after settling the income accrued on the previous one. The property is
+    // added to the owner's properties so that class-only holdings follow an account migration.
+    fn set_class_balance(env: &Env, property_id: u64, class_id: u32, owner: &Address, previous: u64, balance: u64) {
+        Self::settle_class_dividends(env, property_id, class_id, owner, previous);
+        if previous == 0 && balance > 0 {
+            Self::track_user_property(env, owner, property_id);
+        }
+        if previous == 0 {
+            let holders_key = ClassRegistry::Holders(property_id, class_id);
+            let mut holders: Vec<Address> = env.storage().instance().get(&holders_key).unwrap_or(Vec::new(env));
+            if !holders.contains(owner) {
+                holders.push_back(owner.clone());
+                env.storage().instance().set(&holders_key, &holders);
+            }
+        }
+        let key = ClassRegistry::Balance(property_id, class_id, owner.clone());
+        if balance == 0 {
+            Self::remove_record(env, &key);
+        } else {
+            Self::store_record(env, &key, &balance);
+        }
+    }
+    
+    fn credit_pending(env: &Env, property_id: u64, owner: &Address, accrued: Money) {
+        if accrued == 0 {
+            return;
+        }
+        let pending_key = DividendRegistry::Pending(property_id, owner.clone());
+        let pending: Money = env.storage().persistent().get(&pending_key).unwrap_or(0);
+        env.storage().persistent().set(&pending_key, &(pending + accrued));
+        env.storage().persistent().extend_ttl(&pending_key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+    }
+    
+    // A holder's current balance as far as income is concerned; custody shares earn nothing
+    fn earning_shares(env: &Env, property_id: u64, owner: &Address) -> u64 {
+        if *owner == env.current_contract_address() {
+            return 0;
+        }
+        Self::load_ownership(env, property_id, owner).map_or(0, |ownership| ownership.shares)
+    }
+    
+    // Shares of a property that earn income: those sold, less any in the contract's custody
+    fn income_shares(env: &Env, property_id: u64) -> u64 {
+        let custody = Self::load_ownership(env, property_id, &env.current_contract_address()).map_or(0, |ownership| ownership.shares);
+        Self::get_shares_sold(env.clone(), property_id) - custody
+    }
+    
+    // Bring a time-weighted property's share-second clock up to now; call before shares_sold changes
+    fn advance_income_clock(env: &Env, property_id: u64) {
+        if !Self::is_time_weighted(env.clone(), property_id) {
+            return;
+        }
+        let key = DividendRegistry::Clock(property_id);
+        let mut clock: ShareClock = env.storage().instance().get(&key).unwrap();
+        let now = env.ledger().timestamp();
+        clock.share_seconds += Self::income_shares(env, property_id) as u128 * (now - clock.last_update) as u128;
+        clock.last_update = now;
+        env.storage().instance().set(&key, &clock);
+    }
+    
+    // End the current income period of a time-weighted property, sharing scaled_amount across
+    // the share-seconds held during it
+    fn close_income_period(env: &Env, property_id: u64, scaled_amount: u128) {
+        Self::advance_income_clock(env, property_id);
+        let key = DividendRegistry::Clock(property_id);
+        let clock: ShareClock = env.storage().instance().get(&key).unwrap();
+        if clock.share_seconds == 0 {
+            panic!("No holding time accrued since the last deposit");
+        }
+        
+        let (start, cumulative) = match Self::income_period(env, property_id, clock.period.checked_sub(1)) {
+            Some(previous) => (previous.end, previous.cumulative + previous.rate * (previous.end - previous.start) as u128),
+            None => (env.storage().instance().get(&DividendRegistry::TimeWeighted(property_id)).unwrap(), 0),
+        };
+        let period = IncomePeriod {
+            start,
+            end: clock.last_update,
+            rate: scaled_amount / clock.share_seconds,
+            cumulative,
+        };
+        env.storage().instance().set(&DividendRegistry::Period(property_id, clock.period), &period);
+        env.storage().instance().set(&key, &ShareClock {
+            period: clock.period + 1,
+            share_seconds: 0,
+            last_update: clock.last_update,
+        });
+    }
+    
+    fn income_period(env: &Env, property_id: u64, period: Option<u32>) -> Option<IncomePeriod> {
+        env.storage().instance().get(&DividendRegistry::Period(property_id, period?))
+    }
+    
+    // Income a holder earned on a constant balance since their clock was last updated, and their
+    // updated clock. Holders without a clock have held their balance since time weighting began.
+    fn time_weighted_accrual(env: &Env, property_id: u64, owner: &Address, shares: u64) -> (Money, ShareClock) {
+        let now = env.ledger().timestamp();
+        let shares = shares as u128;
+        let current: ShareClock = env.storage().instance().get(&DividendRegistry::Clock(property_id)).unwrap();
+        let clock: ShareClock = env.storage().persistent().get(&DividendRegistry::HolderClock(property_id, owner.clone())).unwrap_or(ShareClock {
+            period: 0,
+            share_seconds: 0,
+            last_update: env.storage().instance().get(&DividendRegistry::TimeWeighted(property_id)).unwrap(),
+        });
+        
+        if clock.period == current.period {
+            let updated = ShareClock {
+                period: clock.period,
+                share_seconds: clock.share_seconds + shares * (now - clock.last_update) as u128,
+                last_update: now,
+            };
+            return (0, updated);
+        }
+        
+        // The holder's own period closed with a partial record; any later closed periods were held in full
+        let own = Self::income_period(env, property_id, Some(clock.period)).unwrap();
+        let mut scaled = (clock.share_seconds + shares * (own.end - clock.last_update) as u128) * own.rate;
+        let last = Self::income_period(env, property_id, Some(current.period - 1)).unwrap();
+        let through_last = last.cumulative + last.rate * (last.end - last.start) as u128;
+        let through_own = own.cumulative + own.rate * (own.end - own.start) as u128;
+        scaled += shares * (through_last - through_own);
+        
+        let updated = ShareClock {
+            period: current.period,
+            share_seconds: shares * (now - last.end) as u128,
+            last_update: now,
+        };
+        (scaled / DIVIDEND_PRECISION, updated)
+    }
+    
+    // Register a property for an already authorized registrant, crediting any founder allocation
+    fn register(env: Env, registrant: Address, input: PropertyInput) -> u64 {
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(&env, Error::ContractPaused);
+        }
+        Self::record_activity(&env, &registrant);
+        
+        // The founder allocation is carved out of the total supply
+        if input.founder_shares > input.total_shares {
+            panic!("Founder shares exceed total shares");
+        }
+        if input.royalty_bps > MAX_ROYALTY_BPS {
+            panic!("Royalty exceeds maximum");
+        }
+        if let Some(deadline) = input.funding_deadline {
+            if deadline <= env.ledger().timestamp() {
+                panic!("Funding deadline must be in the future");
+            }
+            if input.milestone_escrow {
+                panic!("Crowdfunded properties cannot also use milestone escrow");
+            }
+        }
+        
+        // Tenants must be registered by the admin; untagged properties use the default tenant
+        let tenant = match input.tenant {
+            Some(tenant) => {
+                if !Self::get_tenants(env.clone()).contains(&tenant) {
+                    panic!("Unknown tenant");
+                }
+                tenant
+            }
+            None => DEFAULT_TENANT,
+        };
+        
+        // Imported IDs are not final until the import is finalized
+        if Self::is_import_mode(env.clone()) {
+            panic!("Cannot register properties while an import is in progress");
+        }
+        
+        // Get next property ID under the configured ID mode
+        let property_counter = Self::allocate_property_id(&env, &registrant, &input.title, &input.location);
+        
+        // Get current timestamp
+        let timestamp = env.ledger().timestamp();
+        
+        // Create new property
+        let property = PropertyV2 {
+            property_id: property_counter,
+            title: input.title,
+            location: input.location,
+            description: input.description,
+            total_shares: input.total_shares,
+            price_per_share: input.price_per_share,
+            image_url: input.image_url,
+            registration_time: timestamp,
+            is_verified: false,
+            is_private: input.is_private,
+            tenant: tenant.clone(),
+            is_paused: false,
+            rounding_policy: input.rounding_policy,
+        };
+        
+        // Update property stats
+        let mut stats = Self::get_property_stats(env.clone());
+        stats.total_properties += 1;
+        
+        let mut value_stats = Self::get_value_stats(env.clone());
+        value_stats.total_market_cap += Self::market_cap(&property);
+        
+        // Credit the founder allocation directly to the registrant
+        let founder_shares = input.founder_shares;
+        let mut founder_event = None;
+        if founder_shares > 0 {
+            let ownership_share = OwnershipShare {
+                property_id: property_counter,
+                owner: registrant.clone(),
+                shares: founder_shares,
+                purchase_time: timestamp,
+                last_updated: timestamp,
+            };
+            Self::store_ownership(&env, &ownership_share);
+            
+            Self::add_user_property(&env, &registrant, property_counter);
+            
+            let allocation = FounderAllocation {
+                registrant: registrant.clone(),
+                shares: founder_shares,
+                unlock_time: timestamp + input.founder_lockup_seconds,
+            };
+            env.storage().instance().set(&SupplyRegistry::Founder(property_counter), &allocation);
+            env.storage().instance().set(&SupplyRegistry::SharesSold(property_counter), &founder_shares);
+            Self::add_cached_total(&env, &registrant, founder_shares);
+            
+            stats.total_owners += 1;
+            founder_event = Some(founder_shares);
+        }
+        
+        if input.milestone_escrow {
+            env.storage().instance().set(&EscrowRegistry::Enabled(property_counter), &true);
+        }
+        if let Some(deadline) = input.funding_deadline {
+            env.storage().instance().set(&FundingRegistry::Deadline(property_counter), &deadline);
+        }
+        if let Some(recipient) = input.unsold_shares_recipient {
+            env.storage().instance().set(&SupplyRegistry::UnsoldRecipient(property_counter), &recipient);
+        }
+        
+        // Store property data
+        Self::store_property(&env, &property);
+        env.storage().instance().set(&PropertyRegistry::Registrant(property_counter), &registrant);
+        Self::classify(&env, property_counter, input.property_type);
+        if input.royalty_bps > 0 {
+            let royalty = Royalty { recipient: registrant.clone(), bps: input.royalty_bps };
+            env.storage().instance().set(&MarketRegistry::Royalty(property_counter), &royalty);
+        }
+        Self::index_property_id(&env, property_counter);
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+        Self::index_tenant_property(&env, &tenant, property_counter);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "property_registered"), property_counter, PropertyRegisteredEvent {
+            registrant: registrant.clone(),
+            title: property.title.clone(),
+            location: property.location.clone(),
+            total_shares: property.total_shares,
+            price_per_share: property.price_per_share,
+            founder_shares,
+        });
+        if let Some(founder_shares) = founder_event {
+            Self::publish_property_event(&env, symbol_short!("founder"), property_counter, (registrant, founder_shares));
+        }
+        log!(&env, "New property registered with ID: {}", property_counter);
+        
+        property_counter
+    }
+    
+    // Verify a property once the caller's admin authorization has been checked
+    fn verify(env: Env, verifier: Address, property_id: u64) {
+        // Get property data
+        let mut property = Self::get_property(env.clone(), property_id);
+        
+        // Check if property is already verified
+        if property.is_verified {
+            panic!("Property already verified");
+        }
+        
+        // Rejection is final; a revocation is cleared by verifying again
+        if Self::get_review(env.clone(), property_id).is_some_and(|review| review.status == ReviewStatus::Rejected) {
+            panic!("Property rejected");
+        }
+        env.storage().instance().remove(&VerificationRegistry::Review(property_id));
+        
+        // Every checklist requirement must be completed first
+        for status in Self::get_verification_progress(env.clone(), property_id).iter() {
+            if !status.is_completed {
+                panic!("Verification checklist incomplete");
+            }
+        }
+        
+        // Update verification status
+        property.is_verified = true;
+        
+        // Update property stats
+        let mut stats = Self::get_property_stats(env.clone());
+        stats.verified_properties += 1;
+        
+        let mut value_stats = Self::get_value_stats(env.clone());
+        if !Self::is_withdrawn(&env, property_id) {
+            value_stats.verified_market_cap += Self::market_cap(&property);
+        }
+        
+        // Store updated data
+        Self::store_property(&env, &property);
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(&env, Symbol::new(&env, "property_verified"), property_id, PropertyVerifiedEvent {
+            verifier,
+            verified_at: env.ledger().timestamp(),
+        });
+        log!(&env, "Property ID: {} is now verified", property_id);
+    }
+    
+    // Record an explicit Delisted or Archived stage, counting each property once
+    fn withdraw_listing(env: &Env, property_id: u64, status: PropertyStatus) {
+        let key = PropertyRegistry::Withdrawn(property_id);
+        if !env.storage().instance().has(&key) {
+            let withdrawn: u64 = env.storage().instance().get(&WITHDRAWN_COUNT).unwrap_or(0);
+            env.storage().instance().set(&WITHDRAWN_COUNT, &(withdrawn + 1));
+            
+            // A withdrawn property no longer counts towards the market cap totals
+            let property = Self::get_property(env.clone(), property_id);
+            let mut value_stats = Self::get_value_stats(env.clone());
+            value_stats.total_market_cap -= Self::market_cap(&property);
+            if property.is_verified {
+                value_stats.verified_market_cap -= Self::market_cap(&property);
+            }
+            env.storage().instance().set(&VALUE_STATS, &value_stats);
+        }
+        env.storage().instance().set(&key, &status);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        Self::publish_property_event(env, symbol_short!("status"), property_id, status);
+    }
+    
+    // Whether a property has been delisted or archived
+    fn is_withdrawn(env: &Env, property_id: u64) -> bool {
+        env.storage().instance().has(&PropertyRegistry::Withdrawn(property_id))
+    }
+    
+    // Store a negative review and announce it
+    fn record_review(env: &Env, property_id: u64, status: ReviewStatus, reason: Symbol, reviewer: Address) {
+        let review = PropertyReview {
+            status,
+            reason,
+            reviewer,
+            reviewed_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&VerificationRegistry::Review(property_id), &review);
+        
+        env.storage().instance().extend_ttl(10000, 10000);
+        let name = match status {
+            ReviewStatus::Rejected => Symbol::new(env, "property_rejected"),
+            ReviewStatus::Revoked => Symbol::new(env, "verification_revoked"),
+        };
+        Self::publish_property_event(env, name, property_id, review);
+    }
+    
+    // Locate the phase whose [start_time, end_time) window covers the given timestamp
+    fn active_phase_index(phases: &Vec<SalePhase>, timestamp: u64) -> Option<u32> {
+        for (i, phase) in phases.iter().enumerate() {
+            if phase.start_time <= timestamp && timestamp < phase.end_time {
+                return Some(i as u32);
+            }
+        }
+        None
+    }
+    
+    // Pick the ID for a new property: the next counter value, or a salted content hash.
+    // Panics with PropertyIdCollision if the hashed ID is already taken.
+    fn allocate_property_id(env: &Env, registrant: &Address, title: &String, location: &String) -> u64 {
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        let config = match Self::get_id_config(env.clone()) {
+            Some(config) if config.mode == IdMode::ContentHash => config,
+            _ => return property_counter + 1,
+        };
         
-        // Get current timestamp
-        let timestamp = env.ledger().timestamp();
+        let mut preimage = Bytes::new(env);
+        preimage.append(&registrant.clone().to_xdr(env));
+        preimage.append(&title.clone().to_xdr(env));
+        preimage.append(&location.clone().to_xdr(env));
+        preimage.append(&config.salt.into());
         
-        // Create new property
-        let property = Property {
-            property_id: property_counter,
-            title,
-            location,
-            description,
-            total_shares,
-            price_per_share,
-            image_url,
-            registration_time: timestamp,
-            is_verified: false,
+        let digest = env.crypto().sha256(&preimage).to_array();
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest[..8]);
+        let property_id = u64::from_be_bytes(prefix) | HASHED_ID_FLAG;
+        
+        if Self::load_property(env, property_id).is_some() {
+            panic_with_error!(env, Error::PropertyIdCollision);
+        }
+        property_id
+    }
+    
+    // Record a newly stored property ID so list endpoints can walk properties by position.
+    // Sequential IDs are their own position; hashed IDs get an explicit position entry.
+    fn index_property_id(env: &Env, property_id: u64) {
+        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
+        if Self::is_content_hash_mode(env) {
+            env.storage().instance().set(&PropertyRegistry::IdAt(property_counter + 1), &property_id);
+            env.storage().instance().set(&PROPERTY_COUNTER, &(property_counter + 1));
+        } else {
+            env.storage().instance().set(&PROPERTY_COUNTER, &property_counter.max(property_id));
+        }
+    }
+    
+    // Property ID stored at a 1-based list position
+    fn property_id_at(env: &Env, position: u64) -> u64 {
+        if Self::is_content_hash_mode(env) {
+            env.storage().instance().get(&PropertyRegistry::IdAt(position)).unwrap_or(0)
+        } else {
+            position
+        }
+    }
+    
+    // Whether new property IDs are derived from content hashes
+    fn is_content_hash_mode(env: &Env) -> bool {
+        Self::get_id_config(env.clone()).map(|config| config.mode == IdMode::ContentHash).unwrap_or(false)
+    }
+    
+    // Add traded shares and value to the property and global buckets for the current day.
+    // Buckets keep u64 values, so a trade too large to fit saturates its bucket.
+    fn record_volume(env: &Env, property_id: u64, shares: u64, value: Money) {
+        let day = env.ledger().timestamp() / SECONDS_PER_DAY;
+        
+        let property_key = VolumeRegistry::Property(property_id, day);
+        let mut property_volume: DailyVolume = env.storage().instance().get(&property_key).unwrap_or(DailyVolume { shares: 0, value: 0 });
+        property_volume.shares += shares;
+        property_volume.value = property_volume.value.checked_add(value).unwrap_or_else(|| panic_with_error!(env, Error::AmountOverflow));
+        env.storage().instance().set(&property_key, &property_volume);
+        
+        let global_key = VolumeRegistry::Global(day);
+        let mut global_volume: DailyVolume = env.storage().instance().get(&global_key).unwrap_or(DailyVolume { shares: 0, value: 0 });
+        global_volume.shares += shares;
+        global_volume.value = global_volume.value.checked_add(value).unwrap_or_else(|| panic_with_error!(env, Error::AmountOverflow));
+        env.storage().instance().set(&global_key, &global_volume);
+        
+        // Remember the oldest tracked day so cleanup knows where to start
+        if !env.storage().instance().has(&VolumeRegistry::PropertyOldest(property_id)) {
+            env.storage().instance().set(&VolumeRegistry::PropertyOldest(property_id), &day);
+        }
+        if !env.storage().instance().has(&VolumeRegistry::GlobalOldest) {
+            env.storage().instance().set(&VolumeRegistry::GlobalOldest, &day);
+        }
+    }
+    
+    // Remember a purchased lot while the property has a lockup, dropping lots that are already free
+    fn record_lot(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        let lockup = Self::get_lockup_period(env.clone(), property_id);
+        if lockup == 0 {
+            return;
+        }
+        let now = env.ledger().timestamp();
+        let key = LockupRegistry::Lots(property_id, owner.clone());
+        let lots: Vec<ShareLot> = Self::load_record(env, &key).unwrap_or(Vec::new(env));
+        let mut kept = Vec::new(env);
+        for lot in lots.iter().filter(|lot| lot.acquired_at.saturating_add(lockup) > now) {
+            kept.push_back(lot);
+        }
+        kept.push_back(ShareLot { shares, acquired_at: now });
+        Self::store_record(env, &key, &kept);
+    }
+    
+    // Append a trade to a property's price history, dropping the oldest once it is full
+    fn record_trade_price(env: &Env, property_id: u64, shares: u64, price_per_share: Money, primary: bool) {
+        let key = MarketRegistry::PriceHistory(property_id);
+        let mut history: Vec<PricePoint> = env.storage().instance().get(&key).unwrap_or(Vec::new(env));
+        if history.len() >= PRICE_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(PricePoint { price_per_share, shares, timestamp: env.ledger().timestamp(), primary });
+        env.storage().instance().set(&key, &history);
+    }
+    
+    // Read a fixed-length run of daily buckets, filling days without activity with zeroes
+    fn collect_volume(env: &Env, property_id: Option<u64>, day_start: u64, day_count: u64) -> Vec<DailyVolume> {
+        if day_count > MAX_VOLUME_DAYS_PER_QUERY {
+            panic!("Too many days requested");
+        }
+        
+        let mut volumes = Vec::new(env);
+        for day in day_start..day_start + day_count {
+            let volume: Option<DailyVolume> = match property_id {
+                Some(id) => env.storage().instance().get(&VolumeRegistry::Property(id, day)),
+                None => env.storage().instance().get(&VolumeRegistry::Global(day)),
+            };
+            volumes.push_back(volume.unwrap_or(DailyVolume { shares: 0, value: 0 }));
+        }
+        volumes
+    }
+    
+    // Panic with DuplicateRequest if the caller already used this idempotency key
+    fn ensure_new_request(env: &Env, caller: &Address, idempotency_key: &Option<BytesN<32>>) {
+        if let Some(request_key) = idempotency_key {
+            let key = IdempotencyRegistry::Request(caller.clone(), request_key.clone());
+            if env.storage().temporary().has(&key) {
+                panic_with_error!(env, Error::DuplicateRequest);
+            }
+        }
+    }
+    
+    // Store the outcome of a keyed request so later replays can be rejected and reconciled
+    fn record_request(env: &Env, caller: &Address, idempotency_key: &Option<BytesN<32>>, record: IdempotencyRecord) {
+        if let Some(request_key) = idempotency_key {
+            let key = IdempotencyRegistry::Request(caller.clone(), request_key.clone());
+            env.storage().temporary().set(&key, &record);
+            env.storage().temporary().extend_ttl(&key, IDEMPOTENCY_TTL_LEDGERS, IDEMPOTENCY_TTL_LEDGERS);
+        }
+    }
+    
+    // Opaque pagination cursor: the issuing endpoint's tag followed by its position, big-endian
+    fn encode_cursor(env: &Env, endpoint: u32, position: u64) -> Bytes {
+        let mut cursor = Bytes::from_array(env, &endpoint.to_be_bytes());
+        cursor.extend_from_array(&position.to_be_bytes());
+        cursor
+    }
+    
+    // Position encoded in a cursor, or 0 for the first page. Panics with InvalidCursor if the
+    // cursor is malformed or was issued by another endpoint.
+    fn decode_cursor(env: &Env, endpoint: u32, cursor: &Option<Bytes>) -> u64 {
+        let cursor = match cursor {
+            Some(cursor) => cursor,
+            None => return 0,
         };
+        if cursor.len() != 12 {
+            panic_with_error!(env, Error::InvalidCursor);
+        }
         
-        // Update property stats
-        let mut stats = Self::get_property_stats(env.clone());
-        stats.total_properties += 1;
+        let mut buffer = [0u8; 12];
+        cursor.copy_into_slice(&mut buffer);
+        let mut tag = [0u8; 4];
+        let mut position = [0u8; 8];
+        tag.copy_from_slice(&buffer[..4]);
+        position.copy_from_slice(&buffer[4..]);
         
-        // Store property data
-        env.storage().instance().set(&PropertyRegistry::Property(property_counter), &property);
-        env.storage().instance().set(&PROPERTY_COUNTER, &property_counter);
-        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        if u32::from_be_bytes(tag) != endpoint {
+            panic_with_error!(env, Error::InvalidCursor);
+        }
+        u64::from_be_bytes(position)
+    }
+    
+    // One page of a property's current holders and their share counts, walking the holder list
+    // from the cursor's position
+    fn page_holders(env: &Env, property_id: u64, endpoint: u32, cursor: &Option<Bytes>, limit: u32) -> (Vec<(Address, u64)>, Option<Bytes>) {
+        let holders: Vec<Address> = Self::load_record(env, &SupplyRegistry::Holders(property_id)).unwrap_or(Vec::new(env));
+        let start = Self::decode_cursor(env, endpoint, cursor).min(holders.len() as u64) as u32;
+        let end = holders.len().min(start.saturating_add(limit));
+        let mut page = Vec::new(env);
+        for index in start..end {
+            let holder = holders.get(index).unwrap();
+            let shares = Self::get_ownership(env.clone(), property_id, holder.clone()).shares;
+            if shares > 0 {
+                page.push_back((holder, shares));
+            }
+        }
+        (page, Self::next_cursor(env, endpoint, end as u64, holders.len() as u64))
+    }
+    
+    // Cursor for the page after end, or None when end reached the last position
+    fn next_cursor(env: &Env, endpoint: u32, end: u64, last: u64) -> Option<Bytes> {
+        if end < last { Some(Self::encode_cursor(env, endpoint, end)) } else { None }
+    }
+    
+    // Whether a property meets every criterion of a listing filter
+    fn matches_filter(env: &Env, property: &PropertyV2, filter: &PropertyFilter) -> bool {
+        if filter.verified_only && !property.is_verified {
+            return false;
+        }
+        if filter.statuses.is_empty() {
+            if Self::is_withdrawn(env, property.property_id) {
+                return false;
+            }
+        } else if !filter.statuses.contains(Self::get_property_status(env.clone(), property.property_id)) {
+            return false;
+        }
+        if !filter.categories.is_empty() {
+            match Self::get_property_type(env.clone(), property.property_id) {
+                Some(property_type) if filter.categories.contains(property_type) => {}
+                _ => return false,
+            }
+        }
+        if !filter.occupancies.is_empty() {
+            match Self::get_occupancy(env.clone(), property.property_id) {
+                Some(record) if filter.occupancies.contains(record.occupancy) => {}
+                _ => return false,
+            }
+        }
+        if filter.min_price.is_some_and(|min_price| property.price_per_share < min_price) {
+            return false;
+        }
+        if filter.max_price.is_some_and(|max_price| property.price_per_share > max_price) {
+            return false;
+        }
+        true
+    }
+    
+    // Build the compact summary of a property for list endpoints
+    fn reduce_property(env: &Env, property: &PropertyV2) -> ReducedProperty {
+        ReducedProperty {
+            property_id: property.property_id,
+            title: property.title.clone(),
+            location: property.location.clone(),
+            price_per_share: property.price_per_share,
+            total_shares: property.total_shares,
+            shares_sold: Self::get_shares_sold(env.clone(), property.property_id),
+            is_verified: property.is_verified,
+            status: Self::get_property_status(env.clone(), property.property_id),
+        }
+    }
+    
+    // Shared validation for every share movement: amounts must be nonzero and,
+    // when there is a sender, shares must actually change hands
+    fn validate_movement(env: &Env, from: Option<&Address>, to: &Address, amount: u64) {
+        if amount == 0 {
+            panic_with_error!(env, Error::ZeroAmount);
+        }
+        if from == Some(to) {
+            panic_with_error!(env, Error::SelfTransfer);
+        }
+    }
+    
+    // Ask the compliance contract whether a transfer at or above the configured threshold may proceed.
+    // The hook must expose check(property_id, from, to, amount) -> bool; a hook that traps,
+    // is missing, or returns anything but true rejects the transfer.
+    fn compliance_approves(env: &Env, property_id: u64, from: &Address, to: &Address, amount: u64) -> bool {
+        let config: ComplianceHook = match env.storage().instance().get(&COMPLIANCE_HOOK) {
+            Some(config) => config,
+            None => return true,
+        };
+        if amount < config.threshold_shares {
+            return true;
+        }
         
-        env.storage().instance().extend_ttl(10000, 10000);
-        log!(&env, "New property registered with ID: {}", property_counter);
+        let args = (property_id, from.clone(), to.clone(), amount).into_val(env);
+        let approved = env.try_invoke_contract::<bool, soroban_sdk::Error>(&config.hook, &Symbol::new(env, "check"), args);
+        approved == Ok(Ok(true))
+    }
+    
+    // First restriction blocking a transfer of a property's shares, or None when it may proceed.
+    // Kept free of side effects so callers can skip restricted transfers instead of reverting.
+    fn transfer_blocker(env: &Env, property: &PropertyV2, from: &Address, to: &Address, shares: u64, from_balance: u64) -> Option<Symbol> {
+        // A contract-wide pause freezes every property
+        if Self::is_paused(env.clone()) {
+            return Some(symbol_short!("halted"));
+        }
         
-        property_counter
+        // Property-level pause blocks all movement of its shares
+        if property.is_paused {
+            return Some(symbol_short!("paused"));
+        }
+        
+        // Check if sender has enough shares
+        if from_balance < shares {
+            return Some(symbol_short!("balance"));
+        }
+        
+        // A liquidated property's shares can only be redeemed, though shares in contract
+        // custody can still be released to whoever is owed them
+        if *from != env.current_contract_address() && env.storage().instance().has(&LiquidationRegistry::Sale(property.property_id)) {
+            return Some(symbol_short!("frozen"));
+        }
+        
+        // Crowdfunded shares stay with their buyers until the goal is reached or refunded
+        if Self::is_funding_open(env, property.property_id) {
+            return Some(symbol_short!("locked"));
+        }
+        
+        // Shares listed in open sell orders stay put until filled or cancelled, recently
+        // purchased ones until the lockup ends, granted ones until they vest and booked ones
+        // until the stay ends
+        let listed = Self::get_listed_shares(env.clone(), property.property_id, from.clone());
+        let locked = Self::get_locked_shares(env.clone(), property.property_id, from.clone())
+            + Self::get_unvested_shares(env.clone(), property.property_id, from.clone())
+            + Self::get_booked_shares(env.clone(), property.property_id, from.clone());
+        if from_balance - shares < listed + locked {
+            return Some(symbol_short!("locked"));
+        }
+        
+        // Founder shares stay with the registrant until the lockup ends
+        if let Some(allocation) = Self::get_founder_allocation(env.clone(), property.property_id) {
+            if allocation.registrant == *from
+                && env.ledger().timestamp() < allocation.unlock_time
+                && from_balance - shares < allocation.shares
+            {
+                return Some(symbol_short!("locked"));
+            }
+        }
+        
+        // Large transfers need the compliance contract's approval
+        if !Self::compliance_approves(env, property.property_id, from, to, shares) {
+            return Some(symbol_short!("comply"));
+        }
+        
+        // Migrated accounts no longer receive shares; senders should use the new address
+        if Self::get_migrated_to(env.clone(), to.clone()).is_some() {
+            return Some(symbol_short!("migrated"));
+        }
+        
+        // Regulated deployments and properties only deliver shares to eligible investors
+        if let Some(reason) = Self::eligibility_blocker(env, property.property_id, to) {
+            return Some(reason);
+        }
+        
+        // The wallet cap holds for recipients too, except for shares taken into contract custody
+        let max_shares_per_wallet = Self::get_purchase_limits(env.clone(), property.property_id).max_shares_per_wallet;
+        if max_shares_per_wallet > 0 && *to != env.current_contract_address() {
+            let to_balance = Self::load_ownership(env, property.property_id, to).map_or(0, |ownership| ownership.shares);
+            if to_balance.saturating_add(shares) > max_shares_per_wallet {
+                return Some(symbol_short!("cap"));
+            }
+        }
+        
+        // Respect the recipient's receive policy
+        if !Self::can_receive(env.clone(), to.clone(), property.property_id) {
+            return Some(symbol_short!("opt_in"));
+        }
+        
+        None
     }
     
-    // Function to verify a property (admin only)
-    pub fn verify_property(env: Env, property_id: u64) {
-        // Check admin authorization
-        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
-        admin.require_auth();
+    // Error matching the restriction that blocked a transfer
+    fn transfer_error(reason: &Symbol) -> Error {
+        if *reason == symbol_short!("halted") {
+            Error::ContractPaused
+        } else if *reason == symbol_short!("paused") {
+            Error::PropertyPaused
+        } else if *reason == symbol_short!("comply") {
+            Error::ComplianceRejected
+        } else if *reason == symbol_short!("balance") {
+            Error::InsufficientShares
+        } else if *reason == symbol_short!("locked") {
+            Error::SharesLocked
+        } else if *reason == symbol_short!("migrated") {
+            Error::AccountMigrated
+        } else if *reason == symbol_short!("kyc") {
+            Error::InvestorNotApproved
+        } else if *reason == symbol_short!("tier") || *reason == symbol_short!("region") {
+            Error::InvestorNotEligible
+        } else if *reason == symbol_short!("frozen") {
+            Error::PropertyLiquidated
+        } else if *reason == symbol_short!("cap") {
+            Error::WalletCapExceeded
+        } else {
+            Error::RecipientNotOptedIn
+        }
+    }
+    
+    // Gregorian calendar year of a day counted from 1970-01-01
+    fn year_of_day(day: u64) -> u32 {
+        Self::civil_from_days(day).0
+    }
+    
+    // Gregorian calendar year and month (1-12) of a day counted from 1970-01-01 (civil-from-days algorithm)
+    fn civil_from_days(day: u64) -> (u32, u32) {
+        let z = day + 719468;
+        let era = z / 146097;
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let year = yoe + era * 400;
+        // Months are counted from March, so January and February belong to the next year
+        if mp >= 10 { ((year + 1) as u32, (mp - 9) as u32) } else { (year as u32, (mp + 3) as u32) }
+    }
+    
+    // Accounting period (calendar month as YYYYMM) the current ledger time falls in
+    fn current_period(env: &Env) -> u32 {
+        let (year, month) = Self::civil_from_days(env.ledger().timestamp() / SECONDS_PER_DAY);
+        year * 100 + month
+    }
+    
+    // Credit income already received by the contract to a property's holders, after the
+    // management fee, and record it in the current accounting period
+    fn credit_income(env: &Env, property_id: u64, amount: Money) {
+        let shares_sold = Self::income_shares(env, property_id);
+        if shares_sold == 0 {
+            panic!("Property has no holders");
+        }
         
-        // Get property data
-        let key = PropertyRegistry::Property(property_id);
-        let mut property: Property = env.storage().instance().get(&key).expect("Property not found");
+        let distributable = amount - Self::accrue_management_fee(env, property_id, amount);
+        let scaled = distributable.checked_mul(DIVIDEND_PRECISION).unwrap_or_else(|| panic_with_error!(env, Error::AmountOverflow));
+        if Self::is_time_weighted(env.clone(), property_id) {
+            Self::close_income_period(env, property_id, scaled);
+        } else {
+            let key = DividendRegistry::AccPerShare(property_id);
+            let acc_per_share: u128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(acc_per_share + scaled / shares_sold as u128));
+        }
         
-        // Check if property is already verified
+        Self::add_to_period(env, property_id, AccountingRegistry::Income(property_id, Self::current_period(env)), amount);
+    }
+    
+    // Validate and write an insurance policy on behalf of the property's manager
+    fn store_insurance_policy(env: &Env, manager: &Address, property_id: u64, policy_id: u32, terms: PolicyTerms) {
+        // Check manager or admin authorization
+        if !Self::is_property_manager(env, property_id, manager) {
+            panic!("Not authorized to manage insurance");
+        }
+        manager.require_auth();
+        
+        if terms.coverage == 0 {
+            panic_with_error!(env, Error::ZeroAmount);
+        }
+        if terms.expires_at <= env.ledger().timestamp() {
+            panic!("Policy must expire in the future");
+        }
+        
+        let policy = InsurancePolicy {
+            policy_id,
+            insurer_ref: terms.insurer_ref,
+            coverage: terms.coverage,
+            premium: terms.premium,
+            expires_at: terms.expires_at,
+            doc_hash: terms.doc_hash,
+            status: PolicyStatus::Active,
+            recorded_by: manager.clone(),
+            updated_at: env.ledger().timestamp(),
+        };
+        Self::store_record(env, &InsuranceRegistry::Policy(property_id, policy_id), &policy);
+        Self::publish_property_event(env, symbol_short!("insurance"), property_id, policy);
+    }
+    
+    // Number of rent periods of a lease that have fallen due by a time, and the total over its term
+    fn rent_periods(lease: &Lease, at: u64) -> (u64, u64) {
+        let total = (lease.end - lease.start).div_ceil(lease.period_seconds);
+        if at < lease.start {
+            return (0, total);
+        }
+        (((at - lease.start) / lease.period_seconds + 1).min(total), total)
+    }
+    
+    // Pay out a property's accrued management fees to a manager, returning the amount paid
+    fn pay_management_fee(env: &Env, property_id: u64, manager: &Address) -> Money {
+        let amount = Self::get_accrued_management_fee(env.clone(), property_id);
+        if amount > 0 {
+            env.storage().instance().remove(&AccountingRegistry::FeesAccrued(property_id));
+            Self::pay(env, &env.current_contract_address(), manager, amount);
+            Self::publish_property_event(env, symbol_short!("fee_claim"), property_id, (manager.clone(), amount));
+        }
+        amount
+    }
+    
+    // Set aside the management fee out of an income deposit, returning it
+    fn accrue_management_fee(env: &Env, property_id: u64, amount: Money) -> Money {
+        let terms = Self::get_management_fee(env.clone(), property_id);
+        let fee = terms.flat_fee.saturating_add(amount * terms.fee_bps as Money / 10000).min(amount);
+        if fee > 0 {
+            let accrued = Self::get_accrued_management_fee(env.clone(), property_id);
+            env.storage().instance().set(&AccountingRegistry::FeesAccrued(property_id), &(accrued + fee));
+            Self::add_to_period(env, property_id, AccountingRegistry::Fees(property_id, Self::current_period(env)), fee);
+            Self::publish_property_event(env, symbol_short!("mgmt_fee"), property_id, fee);
+        }
+        fee
+    }
+    
+    // Add an amount to one of a property's per-period totals, indexing the period on first use
+    fn add_to_period(env: &Env, property_id: u64, key: AccountingRegistry, amount: Money) {
+        let period = Self::current_period(env);
+        let total: Money = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(total + amount));
+        
+        let periods_key = AccountingRegistry::Periods(property_id);
+        let mut periods: Vec<u32> = env.storage().instance().get(&periods_key).unwrap_or(Vec::new(env));
+        if periods.last() != Some(period) {
+            periods.push_back(period);
+            env.storage().instance().set(&periods_key, &periods);
+        }
+    }
+    
+    // Market capitalization of a property at its listed price
+    fn market_cap(property: &PropertyV2) -> u128 {
+        (property.total_shares as Money).saturating_mul(property.price_per_share)
+    }
+    
+    // Reprice a property's shares, keeping the market cap totals in step
+    fn set_sale_price(env: &Env, property_id: u64, price_per_share: Money) {
+        let previous = Self::get_property(env.clone(), property_id);
+        let mut property = previous.clone();
+        property.price_per_share = price_per_share;
+        Self::store_repriced_property(env, &previous, &property);
+        Self::publish_property_event(env, symbol_short!("price"), property_id, price_per_share);
+    }
+    
+    // Store a property whose supply or price changed, moving its market cap in the totals
+    fn store_repriced_property(env: &Env, previous: &PropertyV2, property: &PropertyV2) {
+        Self::store_property(env, property);
+        if Self::is_withdrawn(env, property.property_id) {
+            return;
+        }
+        
+        let mut value_stats = Self::get_value_stats(env.clone());
+        value_stats.total_market_cap = value_stats.total_market_cap - Self::market_cap(previous) + Self::market_cap(property);
         if property.is_verified {
-            panic!("Property already verified");
+            value_stats.verified_market_cap = value_stats.verified_market_cap - Self::market_cap(previous) + Self::market_cap(property);
         }
+        env.storage().instance().set(&VALUE_STATS, &value_stats);
+    }
+    
+    // Validate, apply and pay for a purchase, optionally at a locked price; returns the applied
+    // plan. The payer funds it, which differs from the buyer when shares go into custody.
+    fn execute_purchase(env: &Env, property_id: u64, shares: u64, buyer: &Address, payer: &Address, locked_price: Option<Money>) -> PurchasePlan {
+        let intent = PurchaseIntent {
+            property_id,
+            buyer: buyer.clone(),
+            shares,
+            locked_price,
+        };
         
-        // Update verification status
-        property.is_verified = true;
+        match Self::validate_purchase(env.clone(), intent) {
+            Ok(plan) => {
+                Self::apply_purchase(env, &plan);
+                Self::settle_purchase(env, &plan, payer);
+                plan
+            }
+            Err(error) => panic_with_error!(env, error),
+        }
+    }
+    
+    // Compute a buyer's position after receiving purchased shares and whether they are a new owner;
+    // top-ups keep the original acquisition time
+    fn position_after_purchase(env: &Env, property_id: u64, buyer: &Address, shares: u64) -> Result<(OwnershipShare, bool), Error> {
+        let current_timestamp = env.ledger().timestamp();
+        let existing = Self::load_ownership(env, property_id, buyer);
+        let is_new_owner = existing.is_none();
+        let ownership = match existing {
+            Some(existing) => OwnershipShare {
+                property_id,
+                owner: buyer.clone(),
+                shares: existing.shares.checked_add(shares).ok_or(Error::AmountOverflow)?,
+                purchase_time: existing.purchase_time,
+                last_updated: current_timestamp,
+            },
+            None => OwnershipShare {
+                property_id,
+                owner: buyer.clone(),
+                shares,
+                purchase_time: current_timestamp,
+                last_updated: current_timestamp,
+            },
+        };
+        Ok((ownership, is_new_owner))
+    }
+    
+    // Write a validated purchase plan; performs no checks of its own
+    fn apply_purchase(env: &Env, plan: &PurchasePlan) {
+        let property_id = plan.property_id;
+        let buyer = &plan.buyer;
         
-        // Update property stats
+        // Update user's property list and owner stats
         let mut stats = Self::get_property_stats(env.clone());
-        stats.verified_properties += 1;
+        if plan.is_new_owner {
+            Self::add_user_property(env, buyer, property_id);
+            
+            stats.total_owners += 1;
+        }
+        stats.total_transactions += 1;
+        env.storage().instance().set(&PROPERTY_STATS, &stats);
         
         // Store updated data
-        env.storage().instance().set(&key, &property);
-        env.storage().instance().set(&PROPERTY_STATS, &stats);
+        if let Some(phase_index) = plan.phase_index {
+            env.storage().instance().set(&SalePhaseRegistry::PhaseSold(property_id, phase_index), &plan.phase_sold);
+        }
+        Self::store_ownership(env, &plan.ownership);
+        Self::advance_income_clock(env, property_id);
+        env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &plan.shares_sold);
+        Self::add_cached_total(env, buyer, plan.shares);
+        Self::record_volume(env, property_id, plan.shares, plan.total_price);
+        Self::record_trade_price(env, property_id, plan.shares, plan.price_per_share, true);
+        Self::record_lot(env, property_id, buyer, plan.shares);
+        
+        // Award activity badges
+        Self::award_badge(env, buyer, Badge::FirstPurchase);
+        let property = Self::get_property(env.clone(), property_id);
+        if env.ledger().timestamp() < property.registration_time + EARLY_BACKER_WINDOW_SECONDS {
+            Self::award_badge(env, buyer, Badge::EarlyBacker);
+        }
+        if plan.is_new_owner {
+            Self::check_portfolio_badge(env, buyer);
+        }
         
         env.storage().instance().extend_ttl(10000, 10000);
-        log!(&env, "Property ID: {} is now verified", property_id);
+        Self::publish_property_event(env, Symbol::new(env, "shares_purchased"), property_id, SharesPurchasedEvent {
+            buyer: buyer.clone(),
+            shares: plan.shares,
+            price_per_share: plan.price_per_share,
+            total_price: plan.total_price,
+            resulting_balance: plan.ownership.shares,
+        });
+        log!(env, "Address {} purchased {} shares of property {} at {} per share", buyer, plan.shares, property_id, plan.price_per_share);
     }
     
-    // Function to purchase property shares
-    pub fn purchase_shares(env: Env, property_id: u64, shares: u64, buyer: Address) {
-        // Authentication
-        buyer.require_auth();
+    // Move a purchase's price from the payer to the seller in the payment token, or into the
+    // contract when the property escrows its proceeds. A failed transfer reverts the purchase.
+    fn settle_purchase(env: &Env, plan: &PurchasePlan, payer: &Address) {
+        Self::route_proceeds(env, plan.property_id, &plan.buyer, payer, plan.total_price, plan.shares_sold);
         
-        // Get property data
-        let key = PropertyRegistry::Property(property_id);
-        let property: Property = env.storage().instance().get(&key).expect("Property not found");
+        // The platform fee is charged on top and held by the contract until withdrawn
+        if plan.platform_fee > 0 {
+            Self::pay(env, payer, &env.current_contract_address(), plan.platform_fee);
+            let fees = Self::get_fee_balance(env.clone());
+            env.storage().instance().set(&FEE_BALANCE, &(fees + plan.platform_fee));
+            Self::publish_property_event(env, symbol_short!("fee"), plan.property_id, (payer.clone(), plan.platform_fee));
+        }
+    }
+    
+    // Pay a primary sale's proceeds to the registrant, or into the contract while the property
+    // escrows them for milestones or holds them until its crowdfunding target is met
+    fn route_proceeds(env: &Env, property_id: u64, buyer: &Address, payer: &Address, amount: Money, shares_sold: u64) {
+        let escrowed = Self::is_milestone_escrow(env.clone(), property_id);
+        let crowdfunded = Self::is_funding_open(env, property_id);
+        let recipient = if escrowed {
+            Self::escrow_proceeds(env, property_id, payer, amount);
+            env.current_contract_address()
+        } else if crowdfunded {
+            env.current_contract_address()
+        } else {
+            Self::get_registrant(env.clone(), property_id)
+        };
+        Self::pay(env, payer, &recipient, amount);
+        if crowdfunded {
+            Self::hold_funding(env, property_id, buyer, amount, shares_sold);
+        }
+    }
+    
+    // Transfer an amount of the payment token, converting it to the token interface's i128
+    fn pay(env: &Env, from: &Address, to: &Address, amount: Money) {
+        if amount == 0 || from == to {
+            return;
+        }
+        let amount = i128::try_from(amount).unwrap_or_else(|_| panic_with_error!(env, Error::AmountOverflow));
+        let payment_token: Address = env.storage().instance().get(&PAYMENT_TOKEN).expect("Contract not initialized");
+        token::Client::new(env, &payment_token).transfer(from, to, &amount);
+    }
+    
+    // Pay a secondary sale's proceeds to the seller, less the property's royalty
+    fn pay_sale_proceeds(env: &Env, property_id: u64, from: &Address, seller: &Address, amount: Money) {
+        let mut proceeds = amount;
+        if let Some(royalty) = Self::get_royalty(env.clone(), property_id) {
+            let cut = amount.saturating_mul(royalty.bps as Money) / 10000;
+            Self::pay(env, from, &royalty.recipient, cut);
+            proceeds -= cut;
+            if cut > 0 {
+                Self::publish_property_event(env, symbol_short!("royalty"), property_id, (royalty.recipient, cut));
+            }
+        }
+        Self::pay(env, from, seller, proceeds);
+    }
+    
+    // Price per share currently on offer, taking the active sale phase into account
+    fn current_price(env: &Env, property: &PropertyV2) -> Money {
+        let phases = Self::get_sale_phases(env.clone(), property.property_id);
+        match Self::active_phase_index(&phases, env.ledger().timestamp()) {
+            Some(index) => phases.get(index).unwrap().price_per_share,
+            None => Self::list_price(env, property),
+        }
+    }
+    
+    // Price per share outside sale phases: the instantaneous Dutch auction price, if one runs
+    fn list_price(env: &Env, property: &PropertyV2) -> Money {
+        let auction = match Self::get_dutch_auction(env.clone(), property.property_id) {
+            Some(auction) => auction,
+            None => return property.price_per_share,
+        };
+        let elapsed = env.ledger().timestamp() - auction.start_time;
+        if elapsed >= auction.duration {
+            return auction.floor_price;
+        }
+        let decline = (auction.start_price - auction.floor_price) * elapsed as Money / auction.duration as Money;
+        auction.start_price - decline
+    }
+    
+    // The verification checklist of a property's type; unclassified properties are treated as residential
+    fn property_checklist(env: &Env, property_id: u64) -> Vec<Symbol> {
+        let property_type = Self::get_property_type(env.clone(), property_id).unwrap_or(PropertyType::Residential);
+        Self::get_verification_checklist(env.clone(), property_type)
+    }
+    
+    // Append a property to its tenant's index
+    fn classify(env: &Env, property_id: u64, property_type: PropertyType) {
+        env.storage().instance().set(&PropertyRegistry::Type(property_id), &property_type);
         
-        // Check if property is verified
-        if !property.is_verified {
-            panic!("Cannot purchase shares of unverified property");
+        let key = CategoryRegistry::Properties(property_type);
+        let mut property_ids: Vec<u64> = env.storage().instance().get(&key).unwrap_or(Vec::new(env));
+        property_ids.push_back(property_id);
+        env.storage().instance().set(&key, &property_ids);
+    }
+    
+    fn index_tenant_property(env: &Env, tenant: &Symbol, property_id: u64) {
+        let key = TenantRegistry::Properties(tenant.clone());
+        let mut property_ids: Vec<u64> = env.storage().instance().get(&key).unwrap_or(Vec::new(env));
+        property_ids.push_back(property_id);
+        env.storage().instance().set(&key, &property_ids);
+    }
+    
+    // The admin implicitly holds every role
+    fn holds_role(env: &Env, account: &Address, role: Role) -> bool {
+        let admin: Address = env.storage().instance().get(&CONTRACT_ADMIN).expect("Contract not initialized");
+        *account == admin || Self::has_role(env.clone(), role, account.clone())
+    }
+    
+    // Reason an address may not acquire a property's shares under the KYC requirement and the
+    // property's eligibility rules, if any; the contract itself always may, as it holds shares in custody
+    fn eligibility_blocker(env: &Env, property_id: u64, account: &Address) -> Option<Symbol> {
+        if *account == env.current_contract_address() {
+            return None;
+        }
+        let rules = Self::get_eligibility_rules(env.clone(), property_id);
+        if rules.is_none() && !Self::is_kyc_required(env.clone()) {
+            return None;
+        }
+        
+        let approval = match Self::get_investor_approval(env.clone(), account.clone()) {
+            Some(approval) => approval,
+            None => return Some(symbol_short!("kyc")),
+        };
+        let rules = rules?;
+        if approval.tier < rules.min_tier {
+            return Some(symbol_short!("tier"));
+        }
+        if !rules.jurisdictions.is_empty() && !rules.jurisdictions.contains(&approval.jurisdiction) {
+            return Some(symbol_short!("region"));
+        }
+        None
+    }
+    
+    // Check that the caller is the admin or holds the role, and authorized this call
+    fn require_role(env: &Env, account: &Address, role: Role) {
+        if !Self::holds_role(env, account, role) {
+            panic!("Missing role");
+        }
+        account.require_auth();
+    }
+    
+    // Check that the caller is the property's registrant or manager, and authorized this call
+    fn require_metadata_editor(env: &Env, caller: &Address, property_id: u64) {
+        if *caller != Self::get_registrant(env.clone(), property_id) && Some(caller.clone()) != Self::get_property_manager(env.clone(), property_id) {
+            panic!("Not authorized to edit metadata");
+        }
+        caller.require_auth();
+    }
+    
+    // Publish an event about a property; every property event carries the property's tenant
+    // as a topic so white-label frontends can route their own events, plus a sandbox topic
+    // before go-live so indexers can keep demo activity apart
+    fn publish_property_event<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, property_id: u64, data: D) {
+        let property = Self::get_property(env.clone(), property_id);
+        if Self::is_sandbox(env.clone()) {
+            env.events().publish((name, property_id, property.tenant, symbol_short!("sandbox")), data);
+        } else {
+            env.events().publish((name, property_id, property.tenant), data);
+        }
+    }
+    
+    // Keep the per-owner total shares cache in step with every balance increase
+    fn add_cached_total(env: &Env, owner: &Address, shares: u64) {
+        let key = HoldingsRegistry::TotalShares(owner.clone());
+        let total: u64 = Self::load_record(env, &key).unwrap_or(0);
+        Self::store_record(env, &key, &(total + shares));
+    }
+    
+    // Keep the per-owner total shares cache in step with every balance decrease
+    fn sub_cached_total(env: &Env, owner: &Address, shares: u64) {
+        let key = HoldingsRegistry::TotalShares(owner.clone());
+        let total: u64 = Self::load_record(env, &key).unwrap_or(0);
+        Self::store_record(env, &key, &(total - shares));
+    }
+    
+    // Flip a property's paused flag after checking admin authorization
+    fn set_contract_paused(env: &Env, paused: bool) {
+        if Self::is_paused(env.clone()) == paused {
+            panic!("Contract pause state unchanged");
         }
         
-        // Get current ownership if exists
-        let ownership_key = OwnershipRegistry::Ownership(property_id, buyer.clone());
-        let existing_ownership: Option<OwnershipShare> = env.storage().instance().get(&ownership_key);
+        env.storage().instance().set(&CONTRACT_PAUSED, &paused);
         
-        // Calculate total owned shares after purchase
-        let mut new_shares = shares;
-        let is_new_owner = existing_ownership.is_none();
-        
-        if let Some(existing) = existing_ownership {
-            new_shares += existing.shares;
+        env.storage().instance().extend_ttl(10000, 10000);
+    }
+    
+    fn set_property_paused(env: &Env, property_id: u64, paused: bool) {
+        let mut property = Self::get_property(env.clone(), property_id);
+        if property.is_paused == paused {
+            panic!("Property pause state unchanged");
         }
         
-        // Ensure there are enough shares available
-        let current_timestamp = env.ledger().timestamp();
-        let ownership_share = OwnershipShare {
-            property_id,
-            owner: buyer.clone(),
-            shares: new_shares,
-            purchase_time: current_timestamp,
-        };
+        property.is_paused = paused;
+        Self::store_property(env, &property);
         
-        // Update user's property list
-        let user_properties_key = UserProperties::Properties(buyer.clone());
-        let mut user_properties: Vec<u64> = env.storage().instance().get(&user_properties_key).unwrap_or(Vec::new(&env));
+        env.storage().instance().extend_ttl(10000, 10000);
+    }
+    
+    // Platform fee charged on top of a purchase's base cost, rounded down
+    fn platform_fee(env: &Env, base_cost: Money) -> Money {
+        base_cost.saturating_mul(Self::get_platform_fee(env.clone()) as Money) / 10000
+    }
+    
+    // Shared payout math: split total across weights under a rounding policy.
+    // The parts never sum to more than total under any policy.
+    fn split_pro_rata(env: &Env, total: u128, weights: &Vec<u64>, policy: RoundingPolicy) -> Vec<u128> {
+        let weight_sum: u128 = weights.iter().map(|weight| weight as u128).sum();
+        let mut parts = Vec::new(env);
+        if weight_sum == 0 {
+            for _ in weights.iter() {
+                parts.push_back(0u128);
+            }
+            return parts;
+        }
         
-        if is_new_owner {
-            user_properties.push_back(property_id);
+        let mut distributed: u128 = 0;
+        let mut rounded_up = Vec::new(env);
+        for (index, weight) in weights.iter().enumerate() {
+            let numerator = total.checked_mul(weight as u128).expect("Amount overflow");
+            let mut part = numerator / weight_sum;
+            let remainder = numerator % weight_sum;
             
-            // Update owner stats if this is a new owner
-            let mut stats = Self::get_property_stats(env.clone());
-            stats.total_owners += 1;
-            stats.total_transactions += 1;
-            env.storage().instance().set(&PROPERTY_STATS, &stats);
-        } else {
-            // Just increment transaction count
-            let mut stats = Self::get_property_stats(env.clone());
-            stats.total_transactions += 1;
-            env.storage().instance().set(&PROPERTY_STATS, &stats);
+            if policy == RoundingPolicy::Bankers
+                && (remainder * 2 > weight_sum || (remainder * 2 == weight_sum && part % 2 == 1))
+            {
+                part += 1;
+                rounded_up.push_back(index as u32);
+            }
+            
+            distributed += part;
+            parts.push_back(part);
         }
         
-        // Store updated data
-        env.storage().instance().set(&ownership_key, &ownership_share);
-        env.storage().instance().set(&user_properties_key, &user_properties);
+        match policy {
+            RoundingPolicy::Down => {}
+            RoundingPolicy::Bankers => {
+                // Rounding half to even can overshoot; take the excess back from rounded-up parts
+                for index in rounded_up.iter() {
+                    if distributed <= total {
+                        break;
+                    }
+                    parts.set(index, parts.get(index).unwrap() - 1);
+                    distributed -= 1;
+                }
+            }
+            RoundingPolicy::RemainderToLargest => {
+                let mut largest = 0;
+                for (index, weight) in weights.iter().enumerate() {
+                    if weight > weights.get(largest).unwrap() {
+                        largest = index as u32;
+                    }
+                }
+                parts.set(largest, parts.get(largest).unwrap() + (total - distributed));
+            }
+        }
         
-        env.storage().instance().extend_ttl(10000, 10000);
-        log!(&env, "Address {} purchased {} shares of property {}", buyer, shares, property_id);
+        parts
     }
     
-    // Function to transfer shares to another user
-    pub fn transfer_shares(env: Env, property_id: u64, from: Address, to: Address, shares: u64) {
-        // Authentication
-        from.require_auth();
+    
+    // Validate and apply a transfer between holders; returns the sender's remaining balance
+    fn execute_transfer(env: &Env, property_id: u64, from: &Address, to: &Address, shares: u64) -> u64 {
+        match Self::validate_transfer(env, property_id, from, to, shares) {
+            Ok(plan) => {
+                Self::apply_transfer(env, &plan, shares);
+                plan.from_ownership.shares
+            }
+            Err(error) => panic_with_error!(env, error),
+        }
+    }
+    
+    // Check every transfer restriction and compute the resulting positions without writing anything
+    fn validate_transfer(env: &Env, property_id: u64, from: &Address, to: &Address, shares: u64) -> Result<TransferPlan, Error> {
+        if shares == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if from == to {
+            return Err(Error::SelfTransfer);
+        }
+        
+        let property = Self::load_property(env, property_id).ok_or(Error::PropertyNotFound)?;
         
         // Get sender's current ownership
-        let from_key = OwnershipRegistry::Ownership(property_id, from.clone());
-        let mut from_ownership: OwnershipShare = env.storage().instance().get(&from_key)
-            .expect("You don't own shares of this property");
+        let from_ownership = Self::load_ownership(env, property_id, from).ok_or(Error::NotOwner)?;
         
-        // Check if sender has enough shares
-        if from_ownership.shares < shares {
-            panic!("Insufficient shares to transfer");
+        // Enforce every transfer restriction
+        if let Some(reason) = Self::transfer_blocker(env, &property, from, to, shares, from_ownership.shares) {
+            return Err(Self::transfer_error(&reason));
         }
         
-        // Update sender's shares
-        from_ownership.shares -= shares;
-        
-        // Get recipient's current ownership
-        let to_key = OwnershipRegistry::Ownership(property_id, to.clone());
+        Self::plan_transfer(env, property_id, from_ownership, to, shares)
+    }
+    
+    // Compute the sender's and recipient's positions after a transfer, without checking any restriction
+    fn plan_transfer(env: &Env, property_id: u64, mut from_ownership: OwnershipShare, to: &Address, shares: u64) -> Result<TransferPlan, Error> {
         let current_timestamp = env.ledger().timestamp();
+        from_ownership.shares -= shares;
+        from_ownership.last_updated = current_timestamp;
         
-        let to_ownership: Option<OwnershipShare> = env.storage().instance().get(&to_key);
-        let new_to_ownership: OwnershipShare;
-        
-        if let Some(mut existing) = to_ownership {
-            // Update existing ownership
-            new_to_ownership = OwnershipShare {
+        let existing = Self::load_ownership(env, property_id, to);
+        let is_new_owner = existing.is_none();
+        let to_ownership = match existing {
+            // Update existing ownership, keeping the original acquisition time
+            Some(existing) => OwnershipShare {
                 property_id,
                 owner: to.clone(),
-                shares: existing.shares + shares,
-                purchase_time: current_timestamp,
-            };
-        } else {
+                shares: existing.shares.checked_add(shares).ok_or(Error::AmountOverflow)?,
+                purchase_time: existing.purchase_time,
+                last_updated: current_timestamp,
+            },
             // Create new ownership record for recipient
-            new_to_ownership = OwnershipShare {
+            None => OwnershipShare {
                 property_id,
                 owner: to.clone(),
                 shares,
                 purchase_time: current_timestamp,
-            };
-            
-            // Add property to recipient's property list
-            let to_properties_key = UserProperties::Properties(to.clone());
-            let mut to_properties: Vec<u64> = env.storage().instance().get(&to_properties_key).unwrap_or(Vec::new(&env));
-            to_properties.push_back(property_id);
-            env.storage().instance().set(&to_properties_key, &to_properties);
+                last_updated: current_timestamp,
+            },
+        };
+        
+        Ok(TransferPlan {
+            property_id,
+            from_ownership,
+            to_ownership,
+            is_new_owner,
+        })
+    }
+    
+    // Write a validated transfer plan; performs no checks of its own
+    fn apply_transfer(env: &Env, plan: &TransferPlan, shares: u64) {
+        let property_id = plan.property_id;
+        let from = &plan.from_ownership.owner;
+        let to = &plan.to_ownership.owner;
+        
+        // Update recipient's property list and owner stats
+        let mut stats = Self::get_property_stats(env.clone());
+        if plan.is_new_owner {
+            Self::add_user_property(env, to, property_id);
             
-            // Update owner stats if this is a new owner
-            let mut stats = Self::get_property_stats(env.clone());
             stats.total_owners += 1;
-            env.storage().instance().set(&PROPERTY_STATS, &stats);
         }
-        
-        // Update transaction count
-        let mut stats = Self::get_property_stats(env.clone());
         stats.total_transactions += 1;
         env.storage().instance().set(&PROPERTY_STATS, &stats);
         
         // Store updated ownership data
-        env.storage().instance().set(&from_key, &from_ownership);
-        env.storage().instance().set(&to_key, &new_to_ownership);
+        Self::store_ownership(env, &plan.from_ownership);
+        Self::store_ownership(env, &plan.to_ownership);
+        Self::sub_cached_total(env, from, shares);
+        Self::add_cached_total(env, to, shares);
+        if plan.is_new_owner {
+            Self::check_portfolio_badge(env, to);
+        }
         
         env.storage().instance().extend_ttl(10000, 10000);
-        log!(&env, "{} transferred {} shares of property {} to {}", from, shares, property_id, to);
+        Self::publish_property_event(env, Symbol::new(env, "shares_transferred"), property_id, SharesTransferredEvent {
+            from: from.clone(),
+            to: to.clone(),
+            shares,
+            from_balance: plan.from_ownership.shares,
+            to_balance: plan.to_ownership.shares,
+        });
+        log!(env, "{} transferred {} shares of property {} to {}", from, shares, property_id, to);
     }
     
-    // View function to get property details
-    pub fn get_property(env: Env, property_id: u64) -> Property {
-        let key = PropertyRegistry::Property(property_id);
-        env.storage().instance().get(&key).expect("Property not found")
+    // Carry an account's inheritance plan, receive policy and badges over to its new address
+    fn migrate_account_settings(env: &Env, old: &Address, new: &Address) {
+        let plan_key = InheritanceRegistry::Plan(old.clone());
+        if let Some(plan) = Self::load_record::<InheritanceRegistry, InheritancePlan>(env, &plan_key) {
+            Self::remove_record(env, &plan_key);
+            if plan.beneficiary != *new {
+                Self::store_record(env, &InheritanceRegistry::Plan(new.clone()), &plan);
+            }
+        }
+        
+        let policy_key = ReceiveRegistry::Policy(old.clone());
+        if let Some(policy) = Self::load_record::<ReceiveRegistry, ReceivePolicy>(env, &policy_key) {
+            Self::store_record(env, &ReceiveRegistry::Policy(new.clone()), &policy);
+        }
+        
+        for badge in Self::get_badges(env.clone(), old.clone()).iter() {
+            Self::award_badge(env, new, badge);
+        }
     }
     
-    // View function to get ownership details
-    pub fn get_ownership(env: Env, property_id: u64, owner: Address) -> OwnershipShare {
-        let key = OwnershipRegistry::Ownership(property_id, owner.clone());
-        env.storage().instance().get(&key).unwrap_or(OwnershipShare {
-            property_id,
-            owner: owner.clone(),
-            shares: 0,
-            purchase_time: 0,
-        })
+    // Move one property position from a migrated account to its new address. This is a change of
+    // key for the same holder, not a trade, so transfer restrictions and stats are not applied;
+    // a founder lockup, locked lots and vesting grants follow the shares, as do share class balances.
+    fn migrate_position(env: &Env, property_id: u64, old: &Address, new: &Address) {
+        Self::migrate_class_balances(env, property_id, old, new);
+        let mut old_ownership = Self::get_ownership(env.clone(), property_id, old.clone());
+        let shares = old_ownership.shares;
+        if shares == 0 {
+            return;
+        }
+        if Self::get_listed_shares(env.clone(), property_id, old.clone()) > 0 {
+            panic!("Close open sell orders and auctions before migrating");
+        }
+        if Self::get_booked_shares(env.clone(), property_id, old.clone()) > 0 {
+            panic!("Cancel or finish open bookings before migrating");
+        }
+        
+        let timestamp = env.ledger().timestamp();
+        let new_ownership = match Self::load_ownership(env, property_id, new) {
+            Some(existing) => OwnershipShare {
+                shares: existing.shares + shares,
+                purchase_time: existing.purchase_time.min(old_ownership.purchase_time),
+                last_updated: timestamp,
+                ..existing
+            },
+            None => {
+                Self::add_user_property(env, new, property_id);
+                let mut stats = Self::get_property_stats(env.clone());
+                stats.total_owners += 1;
+                env.storage().instance().set(&PROPERTY_STATS, &stats);
+                OwnershipShare {
+                    property_id,
+                    owner: new.clone(),
+                    shares,
+                    purchase_time: old_ownership.purchase_time,
+                    last_updated: timestamp,
+                }
+            }
+        };
+        old_ownership.shares = 0;
+        old_ownership.last_updated = timestamp;
+        
+        if let Some(mut allocation) = Self::get_founder_allocation(env.clone(), property_id) {
+            if allocation.registrant == *old {
+                allocation.registrant = new.clone();
+                env.storage().instance().set(&SupplyRegistry::Founder(property_id), &allocation);
+            }
+        }
+        if Self::is_opted_in(env.clone(), old.clone(), property_id) {
+            Self::store_record(env, &ReceiveRegistry::OptIn(new.clone(), property_id), &true);
+        }
+        
+        Self::store_ownership(env, &old_ownership);
+        Self::store_ownership(env, &new_ownership);
+        Self::migrate_pending_dividends(env, property_id, old, new);
+        Self::migrate_lots(env, property_id, old, new);
+        Self::migrate_vesting(env, property_id, old, new);
+        Self::sub_cached_total(env, old, shares);
+        Self::add_cached_total(env, new, shares);
+        
+        Self::publish_property_event(env, symbol_short!("migrate"), property_id, (old.clone(), new.clone(), shares));
     }
     
-    // View function to get properties owned by an address
-    pub fn get_user_properties(env: Env, owner: Address) -> Vec<u64> {
-        let key = UserProperties::Properties(owner);
-        env.storage().instance().get(&key).unwrap_or(Vec::new(&env))
+    // Settle a holder's accrued income and clear it, returning the amount owed
+    fn take_dividends(env: &Env, property_id: u64, owner: &Address) -> Money {
+        let shares = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+        Self::settle_dividends(env, property_id, owner, shares);
+        
+        let pending_key = DividendRegistry::Pending(property_id, owner.clone());
+        let amount: Money = env.storage().persistent().get(&pending_key).unwrap_or(0);
+        if amount == 0 {
+            panic!("Nothing to claim");
+        }
+        env.storage().persistent().remove(&pending_key);
+        amount
     }
     
-    // View function to get property statistics
-    pub fn get_property_stats(env: Env) -> PropertyStats {
-        env.storage().instance().get(&PROPERTY_STATS).unwrap_or(PropertyStats {
-            total_properties: 0,
-            verified_properties: 0,
-            total_owners: 0,
-            total_transactions: 0,
-        })
+    // Check a purchase against the property's limits, given the buyer's resulting balance
+    fn check_purchase_limits(env: &Env, property_id: u64, shares: u64, resulting_balance: u64) -> Result<(), Error> {
+        let limits = Self::get_purchase_limits(env.clone(), property_id);
+        if shares < limits.min_shares_per_purchase {
+            return Err(Error::BelowMinimumPurchase);
+        }
+        if limits.max_shares_per_wallet > 0 && resulting_balance > limits.max_shares_per_wallet {
+            return Err(Error::WalletCapExceeded);
+        }
+        Ok(())
     }
     
-    // Function to get total shares owned across all properties by an address
-    pub fn get_total_shares_owned(env: Env, owner: Address) -> u64 {
-        let properties = Self::get_user_properties(env.clone(), owner.clone());
-        let mut total_shares = 0;
-        
-        for property_id in properties.iter() {
-            let ownership = Self::get_ownership(env.clone(), property_id, owner.clone());
-            total_shares += ownership.shares;
+    // Check a marketplace buyer's fill against the property's limits
+    fn check_buyer_limits(env: &Env, property_id: u64, buyer: &Address, shares: u64) -> Result<(), Error> {
+        let balance = Self::load_ownership(env, property_id, buyer).map(|ownership| ownership.shares).unwrap_or(0);
+        Self::check_purchase_limits(env, property_id, shares, balance.saturating_add(shares))
+    }
+    
+    // Amount of one installment; the last one also carries the rounding remainder
+    fn installment_amount(total_payable: Money, num_payments: u32, index: u32) -> Money {
+        let amount = total_payable / num_payments as Money;
+        if index + 1 == num_payments {
+            total_payable - amount * (num_payments - 1) as Money
+        } else {
+            amount
+        }
+    }
+    
+    // Close a plan and return its shares and phase allocation to the unsold supply
+    fn release_reservation(env: &Env, plan: &InstallmentPlan) {
+        Self::remove_record(env, &InstallmentRegistry::Plan(plan.plan_id));
+        let reserved = Self::get_reserved_shares(env.clone(), plan.property_id);
+        env.storage().instance().set(&InstallmentRegistry::Reserved(plan.property_id), &(reserved - plan.shares));
+        if let Some(phase_index) = plan.phase_index {
+            let phase_sold = Self::get_phase_sold(env.clone(), plan.property_id, phase_index);
+            env.storage().instance().set(&SalePhaseRegistry::PhaseSold(plan.property_id, phase_index), &(phase_sold - plan.shares));
         }
+    }
+    
+    // Credit a fully paid plan's shares to the buyer and pay the proceeds out of the installments held
+    fn complete_installments(env: &Env, plan: &InstallmentPlan) {
+        Self::remove_record(env, &InstallmentRegistry::Plan(plan.plan_id));
+        let reserved = Self::get_reserved_shares(env.clone(), plan.property_id);
+        env.storage().instance().set(&InstallmentRegistry::Reserved(plan.property_id), &(reserved - plan.shares));
         
-        total_shares
+        // The phase allocation was taken when the plan started, so the purchase carries no phase
+        let (ownership, is_new_owner) = Self::position_after_purchase(env, plan.property_id, &plan.buyer, plan.shares)
+            .unwrap_or_else(|error| panic_with_error!(env, error));
+        let purchase = PurchasePlan {
+            property_id: plan.property_id,
+            buyer: plan.buyer.clone(),
+            shares: plan.shares,
+            price_per_share: plan.price_per_share,
+            total_price: plan.total_payable - plan.platform_fee,
+            platform_fee: plan.platform_fee,
+            phase_index: None,
+            phase_sold: 0,
+            ownership,
+            is_new_owner,
+            shares_sold: Self::get_shares_sold(env.clone(), plan.property_id) + plan.shares,
+        };
+        Self::apply_purchase(env, &purchase);
+        Self::settle_purchase(env, &purchase, &env.current_contract_address());
     }
     
-    // Function to list all properties with pagination
-    pub fn list_properties(env: Env, start_idx: u64, limit: u64) -> Vec<Property> {
-        let property_counter: u64 = env.storage().instance().get(&PROPERTY_COUNTER).unwrap_or(0);
-        let mut properties = Vec::new(&env);
+    // Release shares from a sell order back to the seller, removing the order once nothing is left
+    fn close_sell_order(env: &Env, order: &SellOrder, shares: u64) {
+        Self::unlist_shares(env, order.property_id, &order.seller, shares);
         
-        let end_idx = if start_idx + limit > property_counter {
-            property_counter
+        if shares == order.shares {
+            Self::remove_record(env, &MarketRegistry::SellOrder(order.order_id));
+            let asks_key = MarketRegistry::Asks(order.property_id);
+            let mut asks = Self::get_sell_orders(env.clone(), order.property_id);
+            if let Some(index) = asks.first_index_of(order.order_id) {
+                asks.remove(index);
+            }
+            env.storage().instance().set(&asks_key, &asks);
+        }
+    }
+    
+    // Require a private property's viewer to authenticate and be allowed to see its cap table
+    fn check_cap_table_access(env: &Env, property_id: u64, viewer: &Address) -> Result<(), Error> {
+        if Self::get_property(env.clone(), property_id).is_private {
+            viewer.require_auth();
+        }
+        if !Self::can_view_cap_table(env.clone(), property_id, viewer.clone()) {
+            return Err(Error::NotAuthorizedToView);
+        }
+        Ok(())
+    }
+    
+    // A property's booking IDs with the stays that have ended dropped, along with their records
+    fn prune_bookings(env: &Env, property_id: u64) -> Vec<u64> {
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let bookings_key = RedemptionRegistry::PropertyBookings(property_id);
+        let booking_ids: Vec<u64> = Self::load_record(env, &bookings_key).unwrap_or(Vec::new(env));
+        let mut live = Vec::new(env);
+        for booking_id in booking_ids.iter() {
+            let booking = Self::get_booking(env.clone(), booking_id);
+            if booking.start_day + booking.nights as u64 > today {
+                live.push_back(booking_id);
+            } else {
+                Self::remove_record(env, &RedemptionRegistry::Booking(booking_id));
+            }
+        }
+        if live.len() != booking_ids.len() {
+            Self::store_record(env, &bookings_key, &live);
+        }
+        live
+    }
+    
+    // Lock shares a holder has put up for sale or escrowed, checking they are not already listed
+    fn list_shares(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        Self::require_unpaused_property(env, property_id);
+        let listed = Self::get_listed_shares(env.clone(), property_id, owner.clone());
+        let balance = Self::get_ownership(env.clone(), property_id, owner.clone()).shares;
+        if balance < listed + shares {
+            panic_with_error!(env, Error::InsufficientShares);
+        }
+        let locked = Self::get_locked_shares(env.clone(), property_id, owner.clone())
+            + Self::get_unvested_shares(env.clone(), property_id, owner.clone())
+            + Self::get_booked_shares(env.clone(), property_id, owner.clone());
+        if balance < listed + shares + locked {
+            panic_with_error!(env, Error::SharesLocked);
+        }
+        Self::store_record(env, &MarketRegistry::Listed(property_id, owner.clone()), &(listed + shares));
+    }
+    
+    // Panic with PropertyPaused while a property's trading is paused
+    fn require_unpaused_property(env: &Env, property_id: u64) {
+        if Self::get_property(env.clone(), property_id).is_paused {
+            panic_with_error!(env, Error::PropertyPaused);
+        }
+    }
+    
+    // Release shares a holder no longer has up for sale or in escrow
+    fn unlist_shares(env: &Env, property_id: u64, owner: &Address, shares: u64) {
+        let listed_key = MarketRegistry::Listed(property_id, owner.clone());
+        let listed = Self::get_listed_shares(env.clone(), property_id, owner.clone()) - shares;
+        if listed == 0 {
+            Self::remove_record(env, &listed_key);
         } else {
-            start_idx + limit
+            Self::store_record(env, &listed_key, &listed);
+        }
+    }
+    
+    // Highest live offer and lowest sell order of a property if they cross, oldest first among
+    // equal prices. Orders where the buyer is also the seller are passed over.
+    fn best_crossing(env: &Env, property_id: u64) -> Option<(BuyOffer, SellOrder)> {
+        let now = env.ledger().timestamp();
+        let mut best_offer: Option<BuyOffer> = None;
+        for offer_id in Self::get_offers(env.clone(), property_id).iter() {
+            let offer = Self::get_offer(env.clone(), offer_id);
+            if offer.expires_at > now && best_offer.as_ref().is_none_or(|best| offer.price_per_share > best.price_per_share) {
+                best_offer = Some(offer);
+            }
+        }
+        let offer = best_offer?;
+        
+        let mut best_order: Option<SellOrder> = None;
+        for order_id in Self::get_sell_orders(env.clone(), property_id).iter() {
+            let order = Self::get_sell_order(env.clone(), order_id);
+            if order.seller != offer.buyer
+                && order.price_per_share <= offer.price_per_share
+                && best_order.as_ref().is_none_or(|best| order.price_per_share < best.price_per_share)
+            {
+                best_order = Some(order);
+            }
+        }
+        Some((offer, best_order?))
+    }
+    
+    fn remove_offer(env: &Env, offer: &BuyOffer) {
+        Self::remove_record(env, &MarketRegistry::Offer(offer.offer_id));
+        let mut bids = Self::get_offers(env.clone(), offer.property_id);
+        if let Some(index) = bids.first_index_of(offer.offer_id) {
+            bids.remove(index);
+        }
+        env.storage().instance().set(&MarketRegistry::Bids(offer.property_id), &bids);
+    }
+    
+    // Move income credited to an old account over to its replacement
+    fn migrate_pending_dividends(env: &Env, property_id: u64, old: &Address, new: &Address) {
+        let old_key = DividendRegistry::Pending(property_id, old.clone());
+        let moved: Money = env.storage().persistent().get(&old_key).unwrap_or(0);
+        if moved == 0 {
+            return;
+        }
+        env.storage().persistent().remove(&old_key);
+        
+        let new_key = DividendRegistry::Pending(property_id, new.clone());
+        let pending: Money = env.storage().persistent().get(&new_key).unwrap_or(0);
+        env.storage().persistent().set(&new_key, &(pending + moved));
+        env.storage().persistent().extend_ttl(&new_key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+    }
+    
+    // Move a migrated account's balances and pending income in each share class of a property
+    fn migrate_class_balances(env: &Env, property_id: u64, old: &Address, new: &Address) {
+        for class_id in 1..=Self::get_share_classes(env.clone(), property_id).len() {
+            let balance = Self::get_class_balance(env.clone(), property_id, class_id, old.clone());
+            if balance > 0 {
+                let new_balance = Self::get_class_balance(env.clone(), property_id, class_id, new.clone());
+                Self::set_class_balance(env, property_id, class_id, old, balance, 0);
+                Self::set_class_balance(env, property_id, class_id, new, new_balance, new_balance + balance);
+            }
+            
+            let old_key = ClassRegistry::Pending(property_id, class_id, old.clone());
+            let moved: Money = Self::load_record(env, &old_key).unwrap_or(0);
+            if moved > 0 {
+                Self::remove_record(env, &old_key);
+                let new_key = ClassRegistry::Pending(property_id, class_id, new.clone());
+                let pending: Money = Self::load_record(env, &new_key).unwrap_or(0);
+                Self::store_record(env, &new_key, &(pending + moved));
+            }
+        }
+    }
+    
+    // Move a migrated account's lockup lots to its new address, keeping their acquisition times
+    fn migrate_lots(env: &Env, property_id: u64, old: &Address, new: &Address) {
+        let old_key = LockupRegistry::Lots(property_id, old.clone());
+        let moved: Vec<ShareLot> = Self::load_record(env, &old_key).unwrap_or(Vec::new(env));
+        if moved.is_empty() {
+            return;
+        }
+        Self::remove_record(env, &old_key);
+        
+        let new_key = LockupRegistry::Lots(property_id, new.clone());
+        let mut lots: Vec<ShareLot> = Self::load_record(env, &new_key).unwrap_or(Vec::new(env));
+        lots.append(&moved);
+        Self::store_record(env, &new_key, &lots);
+    }
+    
+    // Move a migrated account's vesting grants to its new address, keeping their schedules
+    fn migrate_vesting(env: &Env, property_id: u64, old: &Address, new: &Address) {
+        let moved = Self::get_vesting_schedules(env.clone(), property_id, old.clone());
+        if moved.is_empty() {
+            return;
+        }
+        Self::remove_record(env, &VestingRegistry::Grants(property_id, old.clone()));
+        
+        let mut grants = Self::get_vesting_schedules(env.clone(), property_id, new.clone());
+        grants.append(&moved);
+        Self::store_record(env, &VestingRegistry::Grants(property_id, new.clone()), &grants);
+    }
+    
+    // Award a badge unless the address already has it; returns whether it was newly awarded
+    fn award_badge(env: &Env, owner: &Address, badge: Badge) -> bool {
+        let mut badges = Self::get_badges(env.clone(), owner.clone());
+        if badges.contains(badge) {
+            return false;
+        }
+        badges.push_back(badge);
+        Self::store_record(env, &BadgeRegistry::Badges(owner.clone()), &badges);
+        env.events().publish((symbol_short!("badge"), owner.clone()), badge);
+        true
+    }
+    
+    // Award FivePropertiesOwned once an owner holds positions in enough properties
+    fn check_portfolio_badge(env: &Env, owner: &Address) {
+        let mut held = 0;
+        for property_id in Self::get_user_properties(env.clone(), owner.clone()).iter() {
+            if Self::get_ownership(env.clone(), property_id, owner.clone()).shares > 0 {
+                held += 1;
+            }
+        }
+        if held >= FIVE_PROPERTIES {
+            Self::award_badge(env, owner, Badge::FivePropertiesOwned);
+        }
+    }
+    
+    // Credit a keeper for maintenance that processed the given number of items and value.
+    // Rewards come out of the collected platform fees; no-op calls earn nothing, and payouts stop
+    // at the fee balance and the daily cap.
+    fn reward_keeper(env: &Env, keeper: &Address, function: Symbol, items: u64, value: Money) {
+        if items == 0 {
+            return;
+        }
+        let rate = match Self::get_keeper_rate(env.clone(), function) {
+            Some(rate) => rate,
+            None => return,
         };
         
-        for i in start_idx..=end_idx {
-            if i > 0 {
-                let key = PropertyRegistry::Property(i);
-                if let Some(property) = env.storage().instance().get::<PropertyRegistry, Property>(&key) {
-                    properties.push_back(property);
-                }
+        let day = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let paid_key = KeeperRegistry::PaidOnDay(day);
+        let paid_today: Money = env.storage().instance().get(&paid_key).unwrap_or(0);
+        let cap: Money = env.storage().instance().get(&KEEPER_DAILY_CAP).unwrap_or(0);
+        let fees = Self::get_fee_balance(env.clone());
+        
+        let earned = (items as Money).saturating_mul(rate.per_item)
+            .saturating_add(value.saturating_mul(rate.value_bps as Money) / 10000);
+        let reward = earned.min(cap.saturating_sub(paid_today)).min(fees);
+        if reward == 0 {
+            return;
+        }
+        
+        let balance = Self::get_keeper_balance(env.clone(), keeper.clone());
+        Self::store_record(env, &KeeperRegistry::Balance(keeper.clone()), &(balance + reward));
+        env.storage().instance().set(&paid_key, &(paid_today + reward));
+        env.storage().instance().set(&FEE_BALANCE, &(fees - reward));
+        env.events().publish((symbol_short!("keeper"), keeper.clone()), reward);
+    }
+    
+    // Whether a crowdfunded property is still selling towards its goal
+    fn is_funding_open(env: &Env, property_id: u64) -> bool {
+        env.storage().instance().has(&FundingRegistry::Deadline(property_id))
+            && !env.storage().instance().has(&FundingRegistry::Funded(property_id))
+    }
+    
+    // Whether a crowdfunded property's deadline passed before every share was sold
+    fn is_funding_failed(env: &Env, property_id: u64) -> bool {
+        match Self::get_funding_deadline(env.clone(), property_id) {
+            Some(deadline) => Self::is_funding_open(env, property_id) && env.ledger().timestamp() >= deadline,
+            None => false,
+        }
+    }
+    
+    // Hold a crowdfunded purchase's payment for the buyer; the sale of the last share releases
+    // everything raised to the registrant
+    fn hold_funding(env: &Env, property_id: u64, buyer: &Address, amount: Money, shares_sold: u64) {
+        let paid_key = FundingRegistry::Paid(property_id, buyer.clone());
+        let paid: Money = Self::load_record(env, &paid_key).unwrap_or(0);
+        Self::store_record(env, &paid_key, &(paid + amount));
+        let raised = Self::get_funds_raised(env.clone(), property_id) + amount;
+        
+        let property = Self::get_property(env.clone(), property_id);
+        if shares_sold < property.total_shares {
+            env.storage().instance().set(&FundingRegistry::Raised(property_id), &raised);
+            return;
+        }
+        
+        env.storage().instance().set(&FundingRegistry::Funded(property_id), &true);
+        env.storage().instance().remove(&FundingRegistry::Raised(property_id));
+        let registrant = Self::get_registrant(env.clone(), property_id);
+        Self::pay(env, &env.current_contract_address(), &registrant, raised);
+        Self::publish_property_event(env, symbol_short!("funded"), property_id, (registrant, raised));
+    }
+    
+    // Book a primary sale's proceeds into the property's milestone escrow, when it has one
+    fn escrow_proceeds(env: &Env, property_id: u64, buyer: &Address, amount: Money) {
+        if !Self::is_milestone_escrow(env.clone(), property_id) {
+            return;
+        }
+        let proceeds: Money = env.storage().instance().get(&EscrowRegistry::Proceeds(property_id)).unwrap_or(0);
+        let paid_key = EscrowRegistry::Paid(property_id, buyer.clone());
+        let paid: Money = Self::load_record(env, &paid_key).unwrap_or(0);
+        env.storage().instance().set(&EscrowRegistry::Proceeds(property_id), &(proceeds + amount));
+        Self::store_record(env, &paid_key, &(paid + amount));
+    }
+    
+    // Append a property to an owner's holdings list and the owner to the property's holder list;
+    // sandbox deployments also remember every account that ever held shares so a reset can find its records
+    fn add_user_property(env: &Env, owner: &Address, property_id: u64) {
+        let holders_key = SupplyRegistry::Holders(property_id);
+        let mut holders: Vec<Address> = Self::load_record(env, &holders_key).unwrap_or(Vec::new(env));
+        holders.push_back(owner.clone());
+        Self::store_record(env, &holders_key, &holders);
+        Self::track_user_property(env, owner, property_id);
+    }
+    
+    // Add a property to the list of properties an account has held, unless it is already there
+    fn track_user_property(env: &Env, owner: &Address, property_id: u64) {
+        let key = UserProperties::Properties(owner.clone());
+        let mut user_properties = Self::get_user_properties(env.clone(), owner.clone());
+        if user_properties.contains(property_id) {
+            return;
+        }
+        if user_properties.is_empty() && Self::is_sandbox(env.clone()) {
+            let mut accounts: Vec<Address> = env.storage().instance().get(&SANDBOX_ACCOUNTS).unwrap_or(Vec::new(env));
+            accounts.push_back(owner.clone());
+            env.storage().instance().set(&SANDBOX_ACCOUNTS, &accounts);
+        }
+        user_properties.push_back(property_id);
+        env.storage().persistent().set(&key, &user_properties);
+        env.storage().persistent().extend_ttl(&key, RECORD_TTL_THRESHOLD, RECORD_TTL_LEDGERS);
+        env.storage().instance().remove(&key);
+    }
+    
+    // Remove a property's record and every property-keyed entry hanging off it
+    fn wipe_sandbox_property(env: &Env, property_id: u64) {
+        let checklist = Self::property_checklist(env, property_id);
+        env.storage().instance().remove(&PropertyRegistry::Withdrawn(property_id));
+        env.storage().instance().remove(&PropertyRegistry::Type(property_id));
+        env.storage().instance().remove(&PropertyRegistry::Occupancy(property_id));
+        env.storage().instance().remove(&DividendRegistry::AccPerShare(property_id));
+        env.storage().instance().remove(&AuctionRegistry::Dutch(property_id));
+        env.storage().instance().remove(&MarketRegistry::Royalty(property_id));
+        env.storage().instance().remove(&MarketRegistry::PriceHistory(property_id));
+        env.storage().instance().remove(&FundingRegistry::Deadline(property_id));
+        env.storage().instance().remove(&FundingRegistry::Raised(property_id));
+        env.storage().instance().remove(&FundingRegistry::Funded(property_id));
+        env.storage().instance().remove(&InstallmentRegistry::Reserved(property_id));
+        env.storage().instance().remove(&SupplyRegistry::Limits(property_id));
+        env.storage().instance().remove(&KycRegistry::Eligibility(property_id));
+        env.storage().instance().remove(&LockupRegistry::Period(property_id));
+        for share_class in Self::get_share_classes(env.clone(), property_id).iter() {
+            let class_id = share_class.class_id;
+            let holders: Vec<Address> = env.storage().instance().get(&ClassRegistry::Holders(property_id, class_id)).unwrap_or(Vec::new(env));
+            for holder in holders.iter() {
+                Self::remove_record(env, &ClassRegistry::Balance(property_id, class_id, holder.clone()));
+                Self::remove_record(env, &ClassRegistry::Checkpoint(property_id, class_id, holder.clone()));
+                Self::remove_record(env, &ClassRegistry::Pending(property_id, class_id, holder));
+            }
+            env.storage().instance().remove(&ClassRegistry::Holders(property_id, class_id));
+            env.storage().instance().remove(&ClassRegistry::Sold(property_id, class_id));
+            env.storage().instance().remove(&ClassRegistry::AccPerShare(property_id, class_id));
+        }
+        env.storage().instance().remove(&ClassRegistry::Classes(property_id));
+        for proposal_id in Self::get_proposals(env.clone(), property_id).iter() {
+            Self::remove_record(env, &GovernanceRegistry::Proposal(proposal_id));
+        }
+        env.storage().instance().remove(&GovernanceRegistry::Proposals(property_id));
+        env.storage().instance().remove(&GovernanceRegistry::Rules(property_id));
+        env.storage().instance().remove(&LiquidationRegistry::Approved(property_id));
+        env.storage().instance().remove(&LiquidationRegistry::Sale(property_id));
+        env.storage().instance().remove(&BuyoutRegistry::Offer(property_id));
+        env.storage().instance().remove(&BuyoutRegistry::Threshold(property_id));
+        env.storage().instance().remove(&BuybackRegistry::Offer(property_id));
+        env.storage().instance().remove(&SubdivisionRegistry::Approved(property_id));
+        env.storage().instance().remove(&SubdivisionRegistry::Children(property_id));
+        env.storage().instance().remove(&SubdivisionRegistry::Parent(property_id));
+        env.storage().instance().remove(&SubdivisionRegistry::Constituents(property_id));
+        env.storage().instance().remove(&SubdivisionRegistry::MergedInto(property_id));
+        for period in Self::get_accounting_periods(env.clone(), property_id).iter() {
+            env.storage().instance().remove(&AccountingRegistry::Income(property_id, period));
+            env.storage().instance().remove(&AccountingRegistry::Expenses(property_id, period));
+            env.storage().instance().remove(&AccountingRegistry::Fees(property_id, period));
+        }
+        for expense_id in 0..Self::get_expense_count(env.clone(), property_id) {
+            Self::remove_record(env, &AccountingRegistry::Expense(property_id, expense_id));
+        }
+        env.storage().instance().remove(&AccountingRegistry::Periods(property_id));
+        env.storage().instance().remove(&AccountingRegistry::ExpenseCount(property_id));
+        env.storage().instance().remove(&AccountingRegistry::FeeTerms(property_id));
+        env.storage().instance().remove(&AccountingRegistry::FeesAccrued(property_id));
+        for proposal_id in Self::get_budgets(env.clone(), property_id).iter() {
+            env.storage().instance().remove(&BudgetRegistry::Budget(proposal_id));
+        }
+        env.storage().instance().remove(&BudgetRegistry::Budgets(property_id));
+        env.storage().instance().remove(&BudgetRegistry::Reserve(property_id));
+        for lease_id in Self::get_leases(env.clone(), property_id).iter() {
+            Self::remove_record(env, &LeaseRegistry::Lease(lease_id));
+        }
+        env.storage().instance().remove(&LeaseRegistry::Leases(property_id));
+        for policy_id in 0..Self::get_insurance_policy_count(env.clone(), property_id) {
+            Self::remove_record(env, &InsuranceRegistry::Policy(property_id, policy_id));
+        }
+        for claim_id in 0..Self::get_insurance_claim_count(env.clone(), property_id) {
+            Self::remove_record(env, &InsuranceRegistry::Claim(property_id, claim_id));
+        }
+        env.storage().instance().remove(&InsuranceRegistry::PolicyCount(property_id));
+        env.storage().instance().remove(&InsuranceRegistry::ClaimCount(property_id));
+        if let Some(deed) = Self::get_deed(env.clone(), property_id) {
+            Self::unindex_deed(env, &deed.owner, property_id);
+            env.storage().instance().remove(&DeedRegistry::Deed(property_id));
+        }
+        env.storage().instance().remove(&PropertyRegistry::Manager(property_id));
+        if let Some(clock) = env.storage().instance().get::<DividendRegistry, ShareClock>(&DividendRegistry::Clock(property_id)) {
+            for period in 0..clock.period {
+                env.storage().instance().remove(&DividendRegistry::Period(property_id, period));
             }
         }
+        env.storage().instance().remove(&DividendRegistry::Clock(property_id));
+        env.storage().instance().remove(&DividendRegistry::TimeWeighted(property_id));
+        let distributions: u32 = env.storage().instance().get(&DistributionRegistry::Count(property_id)).unwrap_or(0);
+        for distribution_id in 1..=distributions {
+            Self::remove_record(env, &DistributionRegistry::Distribution(property_id, distribution_id));
+        }
+        env.storage().instance().remove(&DistributionRegistry::Count(property_id));
+        for snapshot_id in 1..=Self::get_snapshot_count(env.clone(), property_id) {
+            Self::remove_record(env, &SnapshotRegistry::Snapshot(property_id, snapshot_id));
+        }
+        env.storage().instance().remove(&SnapshotRegistry::Count(property_id));
+        env.storage().instance().remove(&VerificationRegistry::Review(property_id));
+        let phases = Self::get_sale_phases(env.clone(), property_id);
+        for index in 0..phases.len() {
+            env.storage().instance().remove(&SalePhaseRegistry::PhaseSold(property_id, index));
+        }
         
-        properties
+        let bookings_key = RedemptionRegistry::PropertyBookings(property_id);
+        let booking_ids: Vec<u64> = Self::load_record(env, &bookings_key).unwrap_or(Vec::new(env));
+        for booking_id in booking_ids.iter() {
+            Self::remove_record(env, &RedemptionRegistry::Booking(booking_id));
+        }
+        
+        Self::wipe_recent_volume(env, Some(property_id));
+        
+        for code in checklist.iter() {
+            env.storage().instance().remove(&VerificationRegistry::Completed(property_id, code));
+        }
+        for key in Self::list_metadata_keys(env.clone(), property_id).iter() {
+            Self::remove_record(env, &MetadataRegistry::Value(property_id, key));
+        }
+        Self::remove_record(env, &MetadataRegistry::Keys(property_id));
+        Self::remove_record(env, &MetadataRegistry::TotalSize(property_id));
+        Self::remove_record(env, &bookings_key);
+        env.storage().instance().remove(&RedemptionRegistry::Config(property_id));
+        env.storage().instance().remove(&SalePhaseRegistry::Phases(property_id));
+        env.storage().instance().remove(&SupplyRegistry::SharesSold(property_id));
+        env.storage().instance().remove(&SupplyRegistry::Founder(property_id));
+        env.storage().instance().remove(&SupplyRegistry::UnsoldRecipient(property_id));
+        env.storage().instance().remove(&SupplyRegistry::Treasury(property_id));
+        env.storage().instance().remove(&SupplyRegistry::OfferingClosed(property_id));
+        env.storage().instance().remove(&SupplyRegistry::Issuance(property_id));
+        Self::remove_record(env, &SupplyRegistry::Holders(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Enabled(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Milestones(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Proceeds(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Released(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Refunded(property_id));
+        env.storage().instance().remove(&EscrowRegistry::Failed(property_id));
+        env.storage().instance().remove(&PropertyRegistry::Registrant(property_id));
+        env.storage().instance().remove(&PropertyRegistry::Property(property_id));
+        env.storage().instance().remove(&PropertyRegistry::PropertyV2(property_id));
+        env.storage().persistent().remove(&PropertyRegistry::PropertyV2(property_id));
+    }
+    
+    // Remove an account's positions and per-account indexes
+    fn wipe_sandbox_account(env: &Env, owner: &Address) {
+        for property_id in Self::get_user_properties(env.clone(), owner.clone()).iter() {
+            env.storage().instance().remove(&OwnershipRegistry::Ownership(property_id, owner.clone()));
+            env.storage().persistent().remove(&OwnershipRegistry::Ownership(property_id, owner.clone()));
+            env.storage().persistent().remove(&DividendRegistry::Checkpoint(property_id, owner.clone()));
+            env.storage().persistent().remove(&DividendRegistry::Pending(property_id, owner.clone()));
+            env.storage().persistent().remove(&SnapshotRegistry::History(property_id, owner.clone()));
+            env.storage().persistent().remove(&DividendRegistry::HolderClock(property_id, owner.clone()));
+            Self::remove_record(env, &ReceiveRegistry::OptIn(owner.clone(), property_id));
+            Self::remove_record(env, &SalePhaseRegistry::Whitelisted(property_id, owner.clone()));
+            Self::remove_record(env, &EscrowRegistry::Paid(property_id, owner.clone()));
+            Self::remove_record(env, &EscrowRegistry::RefundClaimed(property_id, owner.clone()));
+            Self::remove_record(env, &FundingRegistry::Paid(property_id, owner.clone()));
+            Self::remove_record(env, &LockupRegistry::Lots(property_id, owner.clone()));
+            Self::remove_record(env, &VestingRegistry::Grants(property_id, owner.clone()));
+            Self::remove_record(env, &GovernanceRegistry::Delegate(property_id, owner.clone()));
+            Self::remove_record(env, &GovernanceRegistry::DelegateHistory(property_id, owner.clone()));
+            Self::remove_record(env, &GovernanceRegistry::Delegated(property_id, owner.clone()));
+            Self::remove_record(env, &GovernanceRegistry::DelegatedHistory(property_id, owner.clone()));
+            Self::remove_record(env, &BuybackRegistry::Tendered(property_id, owner.clone()));
+        }
+        env.storage().instance().remove(&UserProperties::Properties(owner.clone()));
+        env.storage().persistent().remove(&UserProperties::Properties(owner.clone()));
+        Self::remove_record(env, &HoldingsRegistry::TotalShares(owner.clone()));
+        Self::remove_record(env, &KycRegistry::Investor(owner.clone()));
+        Self::remove_record(env, &BadgeRegistry::Badges(owner.clone()));
+    }
+    
+    // Remove the volume buckets still inside the retention window for a property, or the global ones
+    fn wipe_recent_volume(env: &Env, property_id: Option<u64>) {
+        let oldest_key = match property_id {
+            Some(id) => VolumeRegistry::PropertyOldest(id),
+            None => VolumeRegistry::GlobalOldest,
+        };
+        let oldest: u64 = match env.storage().instance().get(&oldest_key) {
+            Some(day) => day,
+            None => return,
+        };
+        
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let first_day = oldest.max(today.saturating_sub(Self::get_volume_retention(env.clone())));
+        for day in first_day..=today {
+            match property_id {
+                Some(id) => env.storage().instance().remove(&VolumeRegistry::Property(id, day)),
+                None => env.storage().instance().remove(&VolumeRegistry::Global(day)),
+            }
+        }
+        env.storage().instance().remove(&oldest_key);
     }
-}
\ No newline at end of file
+    
+    // Authorize a payment- or transfer-bearing call. Under strict auth the signature must cover
+    // exactly these terms, so an entry signed for other amounts or recipients is rejected.
+    fn require_auth_for_terms(env: &Env, address: &Address, terms: Vec<Val>) {
+        if Self::is_strict_auth(env.clone()) {
+            address.require_auth_for_args(terms);
+        } else {
+            address.require_auth();
+        }
+    }
+    
+    // Heartbeat for inheritance plans: any authenticated action by an owner resets their clock
+    fn record_activity(env: &Env, owner: &Address) {
+        let key = InheritanceRegistry::Plan(owner.clone());
+        if let Some(mut plan) = Self::load_record::<InheritanceRegistry, InheritancePlan>(env, &key) {
+            plan.last_active = env.ledger().timestamp();
+            Self::store_record(env, &key, &plan);
+        }
+    }
+}
@@ -0,0 +1,2925 @@
+#![cfg(test)]
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Events, MockAuth, MockAuthInvoke};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contract, contractimpl, symbol_short, vec, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, TryFromVal, Val, Vec};
+
+use crate::testutils::TestFixture;
+use crate::{AllowanceRegistry, Badge, ClaimStatus, DilutionEvent, Error, HoldingsRegistry, IdMode, KycRegistry, LeaseStatus, LeaseTerms, MarketRegistry, MergeSpec, MetadataRegistry, Money, Occupancy, OwnershipShare, PolicyStatus, PolicyTerms, Property, PropertyFilter, PropertyStatus, PropertyType, ProposalAction, PurchaseIntent, ReceivePolicy, RejectionCheck, Role, RoundingPolicy, SalePhase, SupplyRegistry, UnitSpec};
+
+// The host error a contract call fails with when it panics with one of our error codes
+fn contract_error(error: Error) -> soroban_sdk::Error {
+    soroban_sdk::Error::from_contract_error(error as u32)
+}
+
+// Run a scenario once under each property ID mode; indexes and views must behave identically
+fn for_each_id_mode(scenario: impl Fn(TestFixture)) {
+    scenario(TestFixture::with_id_mode(IdMode::Sequential));
+    scenario(TestFixture::with_id_mode(IdMode::ContentHash));
+}
+
+// Compliance hook that approves transfers of up to 200 shares
+#[contract]
+pub struct MockComplianceHook;
+
+#[contractimpl]
+impl MockComplianceHook {
+    pub fn check(_env: Env, _property_id: u64, _from: Address, _to: Address, amount: u64) -> bool {
+        amount <= 200
+    }
+}
+
+// Compliance hook whose check always traps, in its own module so both hooks can export `check`
+mod failing_hook {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+    
+    #[contract]
+    pub struct FailingComplianceHook;
+    
+    #[contractimpl]
+    impl FailingComplianceHook {
+        pub fn check(_env: Env, _property_id: u64, _from: Address, _to: Address, _amount: u64) -> bool {
+            panic!("Compliance backend unavailable")
+        }
+    }
+}
+
+#[test]
+fn test_sale_phases_cross_boundaries_and_exhaust_allocation() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_property(1000, 10);
+    let now = fixture.env.ledger().timestamp();
+    let presale = SalePhase { start_time: now, end_time: now + 100, price_per_share: 5, max_shares: 50, requires_whitelist: true };
+    let public = SalePhase { start_time: now + 100, end_time: now + 200, price_per_share: 8, max_shares: 100, requires_whitelist: false };
+    fixture.client.configure_sale_phases(&property_id, &vec![&fixture.env, presale, public]);
+    fixture.client.verify_property(&fixture.admin, &property_id);
+    let insider = fixture.buyer_with_tokens(10_000);
+    let outsider = fixture.buyer_with_tokens(10_000);
+    fixture.client.set_phase_whitelist(&property_id, &insider, &true);
+    
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &1, &outsider, &None), Err(Ok(contract_error(Error::NotWhitelisted))));
+    fixture.client.purchase_shares(&property_id, &50, &insider, &None);
+    assert_eq!(fixture.token_balance(&insider), 10_000 - 250);
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &1, &insider, &None), Err(Ok(contract_error(Error::PhaseAllocationExceeded))));
+    
+    // The public phase starts exactly at the boundary, at its own price and allocation
+    fixture.advance_time(100);
+    assert_eq!(fixture.client.get_active_phase(&property_id).unwrap().price_per_share, 8);
+    fixture.client.purchase_shares(&property_id, &100, &outsider, &None);
+    assert_eq!(fixture.token_balance(&outsider), 10_000 - 800);
+    assert_eq!(fixture.client.get_phase_sold(&property_id, &1), 100);
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &1, &outsider, &None), Err(Ok(contract_error(Error::PhaseAllocationExceeded))));
+    
+    fixture.advance_time(100);
+    assert!(fixture.client.get_active_phase(&property_id).is_none());
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &1, &outsider, &None), Err(Ok(contract_error(Error::NoActivePhase))));
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_daily_volume_buckets_split_at_midnight_and_clean_up() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let buyer = fixture.buyer_with_tokens(100_000);
+    let day = 20_000;
+    fixture.advance_time(day * 86400 - 1 - fixture.env.ledger().timestamp());
+    
+    // The last second of a day and the exact midnight that follows land in different buckets
+    fixture.client.purchase_shares(&property_id, &3, &buyer, &None);
+    fixture.advance_time(1);
+    fixture.client.purchase_shares(&property_id, &5, &buyer, &None);
+    fixture.client.purchase_shares(&property_id, &2, &buyer, &None);
+    fixture.advance_time(2 * 86400);
+    fixture.client.purchase_shares(&property_id, &7, &buyer, &None);
+    
+    let volumes = fixture.client.get_daily_volume(&property_id, &(day - 1), &4);
+    let shares: std::vec::Vec<u64> = volumes.iter().map(|volume| volume.shares).collect();
+    assert_eq!(shares, [3, 7, 0, 7]);
+    assert_eq!(volumes.get(1).unwrap().value, 70);
+    let global: std::vec::Vec<u64> = fixture.client.get_global_daily_volume(&(day - 1), &4).iter().map(|volume| volume.shares).collect();
+    assert_eq!(global, [3, 7, 0, 7]);
+    
+    // With one day retained everything before yesterday is evicted, resuming where the last call stopped
+    fixture.client.set_volume_retention(&1);
+    assert_eq!(fixture.client.cleanup_daily_volume(&Some(property_id), &1, &None), 1);
+    assert_eq!(fixture.client.cleanup_daily_volume(&Some(property_id), &10, &None), 1);
+    assert_eq!(fixture.client.cleanup_daily_volume(&Some(property_id), &10, &None), 0);
+    let shares: std::vec::Vec<u64> = fixture.client.get_daily_volume(&property_id, &(day - 1), &4).iter().map(|volume| volume.shares).collect();
+    assert_eq!(shares, [0, 0, 0, 7]);
+    
+    // The global buckets are cleaned independently
+    assert_eq!(fixture.client.get_global_daily_volume(&(day - 1), &1).get(0).unwrap().shares, 3);
+    assert_eq!(fixture.client.cleanup_daily_volume(&None, &10, &None), 2);
+    assert_eq!(fixture.client.get_global_daily_volume(&(day - 1), &1).get(0).unwrap().shares, 0);
+}
+
+#[test]
+fn test_receive_policy_combinations_and_switching_while_holding() {
+    let fixture = TestFixture::new();
+    let held = fixture.register_verified_property(1000, 10);
+    let other = fixture.register_verified_property(1000, 10);
+    let sender = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&held, &100, &sender, &None);
+    fixture.client.purchase_shares(&other, &100, &sender, &None);
+    let recipient = Address::generate(&fixture.env);
+    
+    // Open by default: anything can be received
+    assert!(fixture.client.get_receive_policy(&recipient) == ReceivePolicy::Open);
+    assert!(fixture.client.can_receive(&recipient, &held));
+    
+    // Requiring opt-in blocks unsolicited transfers until the recipient opts in
+    fixture.client.set_receive_policy(&recipient, &ReceivePolicy::RequireOptIn);
+    assert!(!fixture.client.can_receive(&recipient, &held));
+    assert_eq!(fixture.client.try_transfer_shares(&held, &sender, &recipient, &10, &None), Err(Ok(contract_error(Error::RecipientNotOptedIn))));
+    fixture.client.opt_in_property(&recipient, &held);
+    fixture.client.transfer_shares(&held, &sender, &recipient, &10, &None);
+    
+    // Opting out does not block further shares of a property already held
+    fixture.client.opt_out_property(&recipient, &held);
+    fixture.client.transfer_shares(&held, &sender, &recipient, &10, &None);
+    assert_eq!(fixture.client.try_transfer_shares(&other, &sender, &recipient, &10, &None), Err(Ok(contract_error(Error::RecipientNotOptedIn))));
+    
+    // Switching back to open while holding shares accepts every property again
+    fixture.client.set_receive_policy(&recipient, &ReceivePolicy::Open);
+    fixture.client.transfer_shares(&other, &sender, &recipient, &10, &None);
+    
+    // Switching to opt-in while holding both keeps both receivable but not a new property
+    let fresh = fixture.register_verified_property(1000, 10);
+    fixture.client.purchase_shares(&fresh, &100, &sender, &None);
+    fixture.client.set_receive_policy(&recipient, &ReceivePolicy::RequireOptIn);
+    fixture.client.transfer_shares(&other, &sender, &recipient, &10, &None);
+    assert!(!fixture.client.can_receive(&recipient, &fresh));
+    assert_eq!(fixture.client.try_transfer_shares(&fresh, &sender, &recipient, &10, &None), Err(Ok(contract_error(Error::RecipientNotOptedIn))));
+    
+    // Purchases are solicited and never gated by the policy
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&recipient, &1000);
+    fixture.client.purchase_shares(&fresh, &5, &recipient, &None);
+    assert_eq!(fixture.client.get_ownership(&fresh, &recipient).shares, 5);
+    assert_eq!(fixture.client.get_ownership(&held, &recipient).shares, 20);
+    assert_eq!(fixture.client.get_ownership(&other, &recipient).shares, 20);
+}
+
+#[test]
+fn test_founder_allocation_reduces_supply_and_locks_until_unlock_time() {
+    let fixture = TestFixture::new();
+    let founder = fixture.buyer_with_tokens(1000);
+    let mut input = fixture.property_input(1000, 10);
+    input.founder_lockup_seconds = 500;
+    let property_id = fixture.client.register_verified_property(&fixture.admin, &founder, &input, &300);
+    
+    let allocation = fixture.client.get_founder_allocation(&property_id).unwrap();
+    assert_eq!(allocation.shares, 300);
+    assert_eq!(allocation.unlock_time, fixture.env.ledger().timestamp() + 500);
+    assert_eq!(fixture.client.get_ownership(&property_id, &founder).shares, 300);
+    assert_eq!(fixture.client.get_available_shares(&property_id), 700);
+    
+    // Only the remaining supply can be sold
+    let buyer = fixture.buyer_with_tokens(100_000);
+    fixture.client.purchase_shares(&property_id, &690, &buyer, &None);
+    fixture.client.purchase_shares(&property_id, &10, &founder, &None);
+    assert_eq!(fixture.client.get_available_shares(&property_id), 0);
+    assert!(fixture.client.try_purchase_shares(&property_id, &1, &buyer, &None).is_err());
+    
+    // During the lockup the founder can move shares bought on top of the allocation, but no more
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.transfer_shares(&property_id, &founder, &recipient, &10, &None);
+    assert_eq!(fixture.client.try_transfer_shares(&property_id, &founder, &recipient, &1, &None), Err(Ok(contract_error(Error::SharesLocked))));
+    
+    fixture.advance_time(500);
+    fixture.client.transfer_shares(&property_id, &founder, &recipient, &300, &None);
+    assert_eq!(fixture.client.get_ownership(&property_id, &recipient).shares, 310);
+    fixture.assert_supply_invariant(property_id, &[founder, buyer, recipient]);
+}
+
+#[test]
+fn test_reduced_property_matches_full_record() {
+    let fixture = TestFixture::new();
+    let unverified = fixture.register_property(500, 7);
+    let verified = fixture.register_verified_property(1000, 10);
+    let mut input = fixture.property_input(2000, 3);
+    input.title = String::from_str(&fixture.env, "Harbour Lofts");
+    input.location = String::from_str(&fixture.env, "Lisbon");
+    let registrant = Address::generate(&fixture.env);
+    let custom = fixture.client.register_property(&registrant, &input);
+    fixture.client.verify_property(&fixture.admin, &custom);
+    let buyer = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&verified, &40, &buyer, &None);
+    fixture.client.purchase_shares(&custom, &15, &buyer, &None);
+    
+    let first = fixture.client.list_properties_v2(&None, &2);
+    assert_eq!(first.total, Some(3));
+    let second = fixture.client.list_properties_v2(&first.next_cursor, &2);
+    assert!(second.next_cursor.is_none());
+    let mut reduced = first.items;
+    reduced.append(&second.items);
+    
+    assert_eq!(reduced.len(), 3);
+    for (summary, property_id) in reduced.iter().zip([unverified, verified, custom]) {
+        let full = fixture.client.get_property(&property_id);
+        assert_eq!(summary.property_id, full.property_id);
+        assert_eq!(summary.title, full.title);
+        assert_eq!(summary.location, full.location);
+        assert_eq!(summary.price_per_share, full.price_per_share);
+        assert_eq!(summary.total_shares, full.total_shares);
+        assert_eq!(summary.is_verified, full.is_verified);
+        assert_eq!(summary.shares_sold, fixture.client.get_shares_sold(&property_id));
+        assert!(summary.status == fixture.client.get_property_status(&property_id));
+    }
+    assert!(reduced.get(0).unwrap().status == PropertyStatus::Draft);
+    assert_eq!(reduced.get(2).unwrap().title, String::from_str(&fixture.env, "Harbour Lofts"));
+    assert_eq!(reduced.get(1).unwrap().shares_sold, 40);
+}
+
+#[test]
+fn test_degenerate_movements_leave_stats_and_storage_untouched() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_id, &100, &holder, &None);
+    let other = Address::generate(&fixture.env);
+    let expires_at = fixture.env.ledger().timestamp() + 100;
+    
+    let stats = fixture.client.get_property_stats();
+    let snapshot = fixture.storage_snapshot();
+    let zero = Some(Ok(contract_error(Error::ZeroAmount)));
+    let self_transfer = Some(Ok(contract_error(Error::SelfTransfer)));
+    
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &0, &other, &None).err(), zero);
+    assert_eq!(fixture.client.try_purchase_shares_batch(&holder, &vec![&fixture.env, (property_id, 0)], &None).err(), zero);
+    assert_eq!(fixture.client.try_lock_quote(&property_id, &holder, &0).err(), zero);
+    assert_eq!(fixture.client.try_transfer_shares(&property_id, &holder, &other, &0, &None).err(), zero);
+    assert_eq!(fixture.client.try_transfer_shares(&property_id, &holder, &holder, &10, &None).err(), self_transfer);
+    assert_eq!(fixture.client.try_transfer_shares_batch(&holder, &vec![&fixture.env, (property_id, holder.clone(), 10)], &None).err(), self_transfer);
+    assert_eq!(fixture.client.try_transfer_from(&other, &property_id, &holder, &holder, &10).err(), self_transfer);
+    assert_eq!(fixture.client.try_schedule_transfer(&holder, &property_id, &other, &0, &expires_at).err(), zero);
+    assert_eq!(fixture.client.try_create_sell_order(&holder, &property_id, &0, &10).err(), zero);
+    assert_eq!(fixture.client.try_make_offer(&holder, &property_id, &0, &10, &expires_at).err(), zero);
+    
+    let after = fixture.client.get_property_stats();
+    assert_eq!(after.total_owners, stats.total_owners);
+    assert_eq!(after.total_transactions, stats.total_transactions);
+    assert!(fixture.storage_snapshot() == snapshot);
+    assert_eq!(fixture.client.get_ownership(&property_id, &other).shares, 0);
+    assert_eq!(fixture.client.get_ownership(&property_id, &holder).shares, 100);
+}
+
+#[test]
+fn test_import_state_matches_fixture_and_is_one_way() {
+    let fixture = TestFixture::new();
+    let legacy_property = |property_id: u64, total_shares: u64, is_verified: bool| Property {
+        property_id,
+        title: String::from_str(&fixture.env, "Legacy Property"),
+        location: String::from_str(&fixture.env, "Legacy Location"),
+        description: String::from_str(&fixture.env, "Imported from v1"),
+        total_shares,
+        price_per_share: 25,
+        image_url: String::from_str(&fixture.env, "https://example.com/legacy.png"),
+        registration_time: 1_000,
+        is_verified,
+        is_private: false,
+        tenant: symbol_short!("default"),
+        is_paused: false,
+        rounding_policy: RoundingPolicy::Down,
+    };
+    let alice = Address::generate(&fixture.env);
+    let bob = Address::generate(&fixture.env);
+    let holding = |property_id: u64, owner: &Address, shares: u64| OwnershipShare {
+        property_id,
+        owner: owner.clone(),
+        shares,
+        purchase_time: 2_000,
+        last_updated: 0,
+    };
+    
+    fixture.client.begin_import();
+    assert!(fixture.client.is_import_mode());
+    // Ownership batches may reference properties loaded by an earlier batch; IDs keep their gaps
+    fixture.client.import_state(&vec![&fixture.env, legacy_property(1, 100, true), legacy_property(4, 50, false)], &vec![&fixture.env, holding(1, &alice, 60)]);
+    fixture.client.import_state(&vec![&fixture.env], &vec![&fixture.env, holding(1, &bob, 40), holding(4, &alice, 10)]);
+    // Over-allocating a property is rejected and rolls back the whole batch
+    assert!(fixture.client.try_import_state(&vec![&fixture.env, legacy_property(5, 10, true)], &vec![&fixture.env, holding(4, &bob, 41)]).is_err());
+    fixture.client.finalize_import();
+    
+    assert!(!fixture.client.is_import_mode());
+    assert!(fixture.client.try_import_state(&vec![&fixture.env, legacy_property(5, 10, true)], &vec![&fixture.env]).is_err());
+    assert!(fixture.client.try_begin_import().is_err());
+    
+    let stats = fixture.client.get_property_stats();
+    assert_eq!((stats.total_properties, stats.verified_properties, stats.total_owners), (2, 1, 3));
+    assert_eq!(fixture.client.get_value_stats().total_market_cap, 100 * 25 + 50 * 25);
+    assert!(fixture.client.try_get_property(&5).is_err());
+    assert_eq!(fixture.client.get_property(&4).total_shares, 50);
+    assert_eq!(fixture.client.get_shares_sold(&1), 100);
+    assert_eq!(fixture.client.get_shares_sold(&4), 10);
+    assert_eq!(fixture.client.get_available_shares(&4), 40);
+    let ownership = fixture.client.get_ownership(&1, &alice);
+    assert_eq!((ownership.shares, ownership.purchase_time, ownership.last_updated), (60, 2_000, 2_000));
+    assert_eq!(fixture.client.get_user_properties(&alice), vec![&fixture.env, 1, 4]);
+    assert_eq!(fixture.client.get_total_shares_owned(&alice), 70);
+    let listed: std::vec::Vec<u64> = fixture.client.list_properties_v2(&None, &10).items.iter().map(|item| item.property_id).collect();
+    assert_eq!(listed, [1, 4]);
+    fixture.assert_supply_invariant(1, &[alice.clone(), bob.clone()]);
+    fixture.assert_supply_invariant(4, &[alice, bob]);
+    
+    // Registration continues after the highest imported ID
+    let property_id = fixture.register_property(10, 10);
+    assert_eq!(property_id, 5);
+}
+
+#[test]
+fn test_compliance_hook_gates_large_transfers_by_amount() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let sender = fixture.buyer_with_tokens(10_000);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &1000, &sender, &None);
+    
+    // Without a hook every transfer proceeds
+    assert!(fixture.client.get_compliance_hook().is_none());
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &300, &None);
+    
+    // Transfers at or above the threshold need the hook's approval; smaller ones bypass it
+    let hook = fixture.env.register(MockComplianceHook, ());
+    fixture.client.set_compliance_hook(&hook, &100);
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &99, &None);
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &200, &None);
+    assert_eq!(fixture.client.try_transfer_shares(&property_id, &sender, &recipient, &201, &None), Err(Ok(contract_error(Error::ComplianceRejected))));
+    
+    // A hook that fails blocks large transfers rather than letting them through
+    let failing = fixture.env.register(failing_hook::FailingComplianceHook, ());
+    fixture.client.set_compliance_hook(&failing, &100);
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &1, &None);
+    assert_eq!(fixture.client.try_transfer_shares(&property_id, &sender, &recipient, &100, &None), Err(Ok(contract_error(Error::ComplianceRejected))));
+    
+    fixture.client.clear_compliance_hook();
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &400, &None);
+    assert_eq!(fixture.client.get_ownership(&property_id, &recipient).shares, 1000);
+}
+
+#[test]
+fn test_fixture_registers_verified_property() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    
+    let property = fixture.client.get_property(&property_id);
+    assert!(property.is_verified);
+    assert_eq!(property.total_shares, 1000);
+    assert_eq!(fixture.client.get_available_shares(&property_id), 1000);
+    fixture.assert_stats_invariants();
+}
+
+#[test]
+fn test_fixture_funds_buyer_for_purchase() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let buyer = fixture.buyer_with_tokens(5000);
+    assert_eq!(fixture.token_balance(&buyer), 5000);
+    
+    fixture.client.purchase_shares(&property_id, &100, &buyer, &None);
+    
+    assert_eq!(fixture.token_balance(&buyer), 4000);
+    assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 100);
+    assert_eq!(fixture.client.get_property_stats().total_owners, 1);
+    fixture.assert_supply_invariant(property_id, &[buyer]);
+    fixture.assert_invariants(property_id);
+    fixture.assert_stats_invariants();
+}
+
+#[test]
+fn test_fixture_advances_time() {
+    let fixture = TestFixture::new();
+    let start = fixture.env.ledger().timestamp();
+    fixture.advance_time(3600);
+    assert_eq!(fixture.env.ledger().timestamp(), start + 3600);
+}
+
+#[test]
+fn test_fixture_unverified_property_rejects_purchase() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_property(1000, 10);
+    let buyer = fixture.buyer_with_tokens(5000);
+    
+    assert!(fixture.client.try_purchase_shares(&property_id, &100, &buyer, &None).is_err());
+    assert_eq!(fixture.token_balance(&buyer), 5000);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_fixture_corruption_hook_is_detected() {
+    let fixture = TestFixture::new();
+    let property_ids: std::vec::Vec<u64> = (0..3).map(|_| fixture.register_verified_property(1000, 10)).collect();
+    let property_id = property_ids[1];
+    let buyer = fixture.buyer_with_tokens(5000);
+    fixture.client.purchase_shares(&property_id, &100, &buyer, &None);
+    assert!(fixture.client.check_all(&0, &10).is_empty());
+    
+    fixture.corrupt_shares_sold(property_id, 150);
+    assert_eq!(fixture.client.check_invariants(&property_id), vec![&fixture.env, symbol_short!("holders")]);
+    
+    fixture.corrupt_shares_sold(property_id, 1500);
+    assert_eq!(fixture.client.check_invariants(&property_id), vec![&fixture.env, symbol_short!("oversold"), symbol_short!("holders")]);
+    assert_eq!(fixture.client.check_invariants(&property_ids[0]), vec![&fixture.env]);
+    
+    // check_all reports the same codes, paged over registration order
+    let expected = vec![&fixture.env, (property_id, symbol_short!("oversold")), (property_id, symbol_short!("holders"))];
+    assert_eq!(fixture.client.check_all(&0, &10), expected);
+    assert!(fixture.client.check_all(&0, &1).is_empty());
+    assert_eq!(fixture.client.check_all(&1, &1), expected);
+    assert!(fixture.client.check_all(&2, &10).is_empty());
+    
+    // Allocating fewer shares than the founder holds breaks the founder invariant too
+    let mut input = fixture.property_input(100, 10);
+    input.founder_shares = 20;
+    let founded = fixture.client.register_property(&Address::generate(&fixture.env), &input);
+    fixture.corrupt_shares_sold(founded, 10);
+    assert_eq!(fixture.client.check_invariants(&founded), vec![&fixture.env, symbol_short!("holders"), symbol_short!("founder")]);
+}
+
+#[test]
+fn test_quote_charges_locked_price_after_price_change() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_property(1000, 10);
+    let now = fixture.env.ledger().timestamp();
+    let early = SalePhase { start_time: now, end_time: now + 100, price_per_share: 10, max_shares: 1000, requires_whitelist: false };
+    let late = SalePhase { start_time: now + 100, end_time: now + 10_000, price_per_share: 30, max_shares: 1000, requires_whitelist: false };
+    fixture.client.configure_sale_phases(&property_id, &vec![&fixture.env, early, late]);
+    fixture.client.verify_property(&fixture.admin, &property_id);
+    let buyer = fixture.buyer_with_tokens(10_000);
+    
+    let quote_id = fixture.client.lock_quote(&property_id, &buyer, &20);
+    let stale_quote_id = fixture.client.lock_quote(&property_id, &buyer, &5);
+    // Quotes lock the price only, never the supply
+    assert_eq!(fixture.client.get_available_shares(&property_id), 1000);
+    
+    // The price triples before the purchase lands, but the quote is honoured
+    fixture.advance_time(100);
+    assert_eq!(fixture.client.get_active_phase(&property_id).unwrap().price_per_share, 30);
+    fixture.client.purchase_with_quote(&quote_id);
+    assert_eq!(fixture.token_balance(&buyer), 10_000 - 20 * 10);
+    assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 20);
+    
+    // Quotes are single use and lapse after their validity window
+    assert!(fixture.client.try_purchase_with_quote(&quote_id).is_err());
+    fixture.advance_time(501);
+    assert!(fixture.client.try_purchase_with_quote(&stale_quote_id).is_err());
+    fixture.client.purchase_shares(&property_id, &5, &buyer, &None);
+    assert_eq!(fixture.token_balance(&buyer), 10_000 - 20 * 10 - 5 * 30);
+}
+
+#[test]
+fn test_purchase_time_is_stable_across_top_up_and_incoming_transfer() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let sender = fixture.buyer_with_tokens(10_000);
+    let acquired_at = fixture.env.ledger().timestamp();
+    fixture.client.purchase_shares(&property_id, &10, &holder, &None);
+    fixture.client.purchase_shares(&property_id, &50, &sender, &None);
+    
+    fixture.advance_time(100);
+    fixture.client.purchase_shares(&property_id, &5, &holder, &None);
+    let ownership = fixture.client.get_ownership(&property_id, &holder);
+    assert_eq!((ownership.shares, ownership.purchase_time, ownership.last_updated), (15, acquired_at, acquired_at + 100));
+    
+    fixture.advance_time(100);
+    fixture.client.transfer_shares(&property_id, &sender, &holder, &20, &None);
+    let ownership = fixture.client.get_ownership(&property_id, &holder);
+    assert_eq!((ownership.shares, ownership.purchase_time, ownership.last_updated), (35, acquired_at, acquired_at + 200));
+    
+    // The sender keeps its own acquisition time while its balance change is recorded
+    let ownership = fixture.client.get_ownership(&property_id, &sender);
+    assert_eq!((ownership.shares, ownership.purchase_time, ownership.last_updated), (30, acquired_at, acquired_at + 200));
+    
+    // A first acquisition by transfer starts the recipient's clock
+    let newcomer = Address::generate(&fixture.env);
+    fixture.client.transfer_shares(&property_id, &sender, &newcomer, &10, &None);
+    let ownership = fixture.client.get_ownership(&property_id, &newcomer);
+    assert_eq!((ownership.purchase_time, ownership.last_updated), (acquired_at + 200, acquired_at + 200));
+}
+
+#[test]
+fn test_tenant_topic_on_purchase_events_and_tenant_listing() {
+    let fixture = TestFixture::new();
+    let tenant = symbol_short!("acme");
+    fixture.client.add_tenant(&tenant);
+    assert!(fixture.client.get_tenants().contains(&tenant));
+    
+    let registrant = Address::generate(&fixture.env);
+    let mut input = fixture.property_input(1000, 10);
+    input.tenant = Some(tenant.clone());
+    let first = fixture.client.register_property(&registrant, &input);
+    let untagged = fixture.register_verified_property(1000, 10);
+    let second = fixture.client.register_property(&registrant, &input);
+    fixture.client.verify_property(&fixture.admin, &first);
+    
+    // Unregistered tenants are rejected at registration
+    input.tenant = Some(symbol_short!("nobody"));
+    assert!(fixture.client.try_register_property(&registrant, &input).is_err());
+    
+    let buyer = fixture.buyer_with_tokens(10_000);
+    let purchased = |property_id: u64, tenant: Symbol| {
+        let topics: Vec<Val> = (Symbol::new(&fixture.env, "shares_purchased"), property_id, tenant).into_val(&fixture.env);
+        fixture.env.events().all().iter().any(|(contract, event_topics, _)| contract == fixture.contract_id && event_topics == topics)
+    };
+    fixture.client.purchase_shares(&first, &10, &buyer, &None);
+    assert!(purchased(first, tenant.clone()));
+    assert!(!purchased(first, symbol_short!("default")));
+    fixture.client.purchase_shares(&untagged, &10, &buyer, &None);
+    assert!(purchased(untagged, symbol_short!("default")));
+    
+    assert_eq!(fixture.client.get_properties_by_tenant(&tenant, &0, &10), vec![&fixture.env, first, second]);
+    assert_eq!(fixture.client.get_properties_by_tenant(&tenant, &1, &10), vec![&fixture.env, second]);
+    assert_eq!(fixture.client.get_properties_by_tenant(&symbol_short!("default"), &0, &10), vec![&fixture.env, untagged]);
+    assert!(fixture.client.get_properties_by_tenant(&tenant, &2, &10).is_empty());
+}
+
+#[test]
+fn test_cached_total_shares_match_recomputation_after_random_operations() {
+    let fixture = TestFixture::new();
+    let sponsor = fixture.buyer_with_tokens(1_000_000);
+    let properties: std::vec::Vec<u64> = (0..3)
+        .map(|_| {
+            let property_id = fixture.client.register_property(&sponsor, &fixture.property_input(500, 2));
+            fixture.client.verify_property(&fixture.admin, &property_id);
+            property_id
+        })
+        .collect();
+    let users: std::vec::Vec<Address> = (0..4).map(|_| fixture.buyer_with_tokens(1_000_000)).collect();
+    
+    // Deterministic pseudo-random sequence of purchases, transfers, batch transfers and, once
+    // buybacks open halfway through, burns
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+    for step in 0..200 {
+        if step == 100 {
+            for property_id in properties.iter() {
+                let sold = fixture.client.get_shares_sold(property_id);
+                if sold > 1 {
+                    fixture.client.buyback_shares(&sponsor, property_id, &(sold / 2), &1);
+                }
+            }
+        }
+        let property_id = properties[next(3) as usize];
+        let from = &users[next(4) as usize];
+        let to = &users[next(4) as usize];
+        let balance = fixture.client.get_ownership(&property_id, from).shares;
+        match next(4) {
+            0 => {
+                let shares = 1 + next(20);
+                if fixture.client.get_available_shares(&property_id) >= shares {
+                    fixture.client.purchase_shares(&property_id, &shares, from, &None);
+                }
+            }
+            1 if balance > 0 && from != to => {
+                fixture.client.transfer_shares(&property_id, from, to, &(1 + next(balance)), &None);
+            }
+            2 if balance > 1 && from != to => {
+                let legs = vec![&fixture.env, (property_id, to.clone(), 1), (property_id, to.clone(), 1 + next(balance - 1))];
+                fixture.client.transfer_shares_batch(from, &legs, &None);
+            }
+            3 if balance > 0 && fixture.client.get_buyback(&property_id).is_some() => {
+                let open = fixture.client.get_buyback(&property_id).unwrap();
+                let shares = (1 + next(balance)).min(open.shares - open.shares_tendered);
+                fixture.client.tender_shares(from, &property_id, &shares);
+            }
+            _ => {}
+        }
+    }
+    
+    for user in users.iter() {
+        let recomputed: u64 = properties.iter().map(|property_id| fixture.client.get_ownership(property_id, user).shares).sum();
+        let mut paged = 0;
+        let mut cursor = Some(0);
+        while let Some(position) = cursor {
+            let (partial_sum, next_cursor) = fixture.client.get_total_shares_owned_page(user, &position, &2);
+            paged += partial_sum;
+            cursor = next_cursor;
+        }
+        assert_eq!(fixture.client.get_total_shares_owned_cached(user), recomputed);
+        assert_eq!(fixture.client.get_total_shares_owned(user), recomputed);
+        assert_eq!(paged, recomputed);
+    }
+    for property_id in properties {
+        fixture.assert_invariants(property_id);
+    }
+}
+
+#[test]
+fn test_rounding_policies_conserve_random_splits_and_distributions() {
+    let fixture = TestFixture::new();
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+    
+    for policy in [RoundingPolicy::Down, RoundingPolicy::Bankers, RoundingPolicy::RemainderToLargest] {
+        let registrant = fixture.buyer_with_tokens(10_000_000);
+        let mut input = fixture.property_input(10_000, 3);
+        input.rounding_policy = policy;
+        let property_id = fixture.client.register_property(&registrant, &input);
+        fixture.client.verify_property(&fixture.admin, &property_id);
+        assert!(fixture.client.get_rounding_policy(&property_id) == policy);
+        
+        // Random cap tables and amounts through the shared split helper
+        for _ in 0..100 {
+            let total = next(1_000_000) as u128;
+            let mut weights = Vec::new(&fixture.env);
+            for _ in 0..1 + next(8) {
+                weights.push_back(next(1000));
+            }
+            weights.push_back(1 + next(1000));
+            let weight_sum: u128 = weights.iter().map(|weight| weight as u128).sum();
+            
+            let parts = fixture.client.preview_split(&property_id, &total, &weights);
+            assert_eq!(parts.len(), weights.len());
+            let distributed: u128 = parts.iter().sum();
+            assert!(distributed <= total);
+            for (part, weight) in parts.iter().zip(weights.iter()) {
+                let exact_floor = total * weight as u128 / weight_sum;
+                match policy {
+                    RoundingPolicy::Down => assert_eq!(part, exact_floor),
+                    RoundingPolicy::Bankers => assert!(part + 1 >= exact_floor && part <= exact_floor + 1),
+                    RoundingPolicy::RemainderToLargest => assert!(part >= exact_floor),
+                }
+            }
+            if policy == RoundingPolicy::RemainderToLargest {
+                assert_eq!(distributed, total);
+            }
+        }
+        
+        // A random cap table never claims more than was deposited
+        let holders: std::vec::Vec<Address> = (0..5).map(|_| fixture.buyer_with_tokens(100_000)).collect();
+        for holder in holders.iter() {
+            fixture.client.purchase_shares(&property_id, &(1 + next(2000)), holder, &None);
+        }
+        let deposited = 1 + next(100_000) as Money;
+        fixture.client.deposit_income(&registrant, &property_id, &deposited);
+        let claimed: Money = holders.iter().map(|holder| fixture.client.claim_dividends(&property_id, holder)).sum();
+        assert!(claimed <= deposited);
+    }
+}
+
+#[test]
+fn test_inheritance_heartbeat_and_partial_claim_with_frozen_property() {
+    let fixture = TestFixture::new();
+    let free = fixture.register_verified_property(1000, 10);
+    let frozen = fixture.register_verified_property(1000, 10);
+    let owner = fixture.buyer_with_tokens(10_000);
+    let beneficiary = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&free, &40, &owner, &None);
+    fixture.client.purchase_shares(&frozen, &60, &owner, &None);
+    fixture.client.set_beneficiary(&owner, &beneficiary, &1000);
+    
+    // An explicit heartbeat restarts the inactivity clock
+    fixture.advance_time(900);
+    fixture.client.touch(&owner);
+    fixture.advance_time(900);
+    assert!(fixture.client.try_claim_inheritance(&owner, &beneficiary).is_err());
+    
+    // So does any authenticated action by the owner
+    fixture.client.purchase_shares(&free, &10, &owner, &None);
+    fixture.advance_time(999);
+    assert!(fixture.client.try_claim_inheritance(&owner, &beneficiary).is_err());
+    fixture.advance_time(1);
+    
+    // A frozen property is skipped and reported while the rest passes to the beneficiary
+    fixture.client.pause_property(&fixture.admin, &frozen);
+    let skipped = fixture.client.claim_inheritance(&owner, &beneficiary);
+    assert_eq!(skipped, vec![&fixture.env, (frozen, symbol_short!("paused"))]);
+    assert_eq!(fixture.client.get_ownership(&free, &beneficiary).shares, 50);
+    assert_eq!(fixture.client.get_ownership(&free, &owner).shares, 0);
+    assert_eq!(fixture.client.get_ownership(&frozen, &owner).shares, 60);
+    
+    // Once unfrozen the remaining holding can be claimed
+    fixture.client.unpause_property(&fixture.admin, &frozen);
+    assert!(fixture.client.claim_inheritance(&owner, &beneficiary).is_empty());
+    assert_eq!(fixture.client.get_ownership(&frozen, &beneficiary).shares, 60);
+}
+
+#[test]
+fn test_each_purchase_failure_category_leaves_storage_untouched() {
+    let fixture = TestFixture::new();
+    let registrant = Address::generate(&fixture.env);
+    let register = |verified: bool| {
+        let property_id = fixture.client.register_property(&registrant, &fixture.property_input(100, 10));
+        if verified {
+            fixture.client.verify_property(&fixture.admin, &property_id);
+        }
+        property_id
+    };
+    let open = register(true);
+    let unverified = register(false);
+    let paused = register(true);
+    let delisted = register(true);
+    let limited = register(true);
+    let phased = register(false);
+    let now = fixture.env.ledger().timestamp();
+    let phase = SalePhase { start_time: now + 100, end_time: now + 200, price_per_share: 10, max_shares: 10, requires_whitelist: true };
+    fixture.client.configure_sale_phases(&phased, &vec![&fixture.env, phase]);
+    fixture.client.verify_property(&fixture.admin, &phased);
+    fixture.client.pause_property(&fixture.admin, &paused);
+    fixture.client.delist_property(&fixture.admin, &delisted);
+    fixture.client.set_purchase_limits(&limited, &5, &20);
+    let buyer = fixture.buyer_with_tokens(100_000);
+    let whitelisted = fixture.buyer_with_tokens(100_000);
+    fixture.client.set_phase_whitelist(&phased, &whitelisted, &true);
+    fixture.client.purchase_shares(&limited, &15, &buyer, &None);
+    
+    let assert_rejected = |property_id: u64, buyer: &Address, shares: u64, error: Error| {
+        let snapshot = fixture.storage_snapshot();
+        let intent = PurchaseIntent { property_id, buyer: buyer.clone(), shares, locked_price: None };
+        assert!(matches!(fixture.client.try_validate_purchase(&intent), Err(Ok(rejected)) if rejected == error));
+        assert_eq!(fixture.client.try_purchase_shares(&property_id, &shares, buyer, &None).err(), Some(Ok(contract_error(error))));
+        assert!(fixture.storage_snapshot() == snapshot);
+    };
+    assert_rejected(open, &buyer, 0, Error::ZeroAmount);
+    assert_rejected(999, &buyer, 1, Error::PropertyNotFound);
+    assert_rejected(unverified, &buyer, 1, Error::PropertyNotVerified);
+    assert_rejected(paused, &buyer, 1, Error::PropertyPaused);
+    assert_rejected(delisted, &buyer, 1, Error::PropertyDelisted);
+    assert_rejected(open, &buyer, 101, Error::SupplyExceeded);
+    assert_rejected(limited, &buyer, 4, Error::BelowMinimumPurchase);
+    assert_rejected(limited, &buyer, 6, Error::WalletCapExceeded);
+    assert_rejected(phased, &whitelisted, 1, Error::NoActivePhase);
+    fixture.advance_time(100);
+    assert_rejected(phased, &buyer, 1, Error::NotWhitelisted);
+    assert_rejected(phased, &whitelisted, 11, Error::PhaseAllocationExceeded);
+    fixture.client.set_kyc_required(&true);
+    assert_rejected(open, &buyer, 1, Error::InvestorNotApproved);
+    fixture.client.set_kyc_required(&false);
+    
+    // Validation alone never writes, and a payment failure after validation rolls back as well
+    let snapshot = fixture.storage_snapshot();
+    let broke = Address::generate(&fixture.env);
+    let plan = fixture.client.validate_purchase(&PurchaseIntent { property_id: open, buyer: broke.clone(), shares: 5, locked_price: None });
+    assert_eq!(plan.total_price, 50);
+    assert!(fixture.client.try_purchase_shares(&open, &5, &broke, &None).is_err());
+    assert!(fixture.storage_snapshot() == snapshot);
+}
+
+#[test]
+fn test_content_hash_ids_detect_collisions() {
+    let fixture = TestFixture::with_id_mode(IdMode::ContentHash);
+    let registrant = Address::generate(&fixture.env);
+    let input = fixture.property_input(1000, 10);
+    let property_id = fixture.client.register_property(&registrant, &input);
+    
+    // The same registrant, title and location hash to the same ID
+    let snapshot = fixture.storage_snapshot();
+    assert_eq!(fixture.client.try_register_property(&registrant, &input).err(), Some(Ok(contract_error(Error::PropertyIdCollision))));
+    assert!(fixture.storage_snapshot() == snapshot);
+    
+    // Any difference in the hashed content yields a fresh ID
+    let mut relocated = input.clone();
+    relocated.location = String::from_str(&fixture.env, "Elsewhere");
+    let other = fixture.client.register_property(&registrant, &relocated);
+    assert_ne!(other, property_id);
+    assert_eq!(fixture.client.list_properties_v2(&None, &10).total, Some(2));
+    
+    // Sequential IDs never collide on content
+    let sequential = TestFixture::new();
+    let registrant = Address::generate(&sequential.env);
+    let input = sequential.property_input(1000, 10);
+    let first = sequential.client.register_property(&registrant, &input);
+    assert_eq!(sequential.client.register_property(&registrant, &input), first + 1);
+}
+
+#[test]
+fn test_indexes_and_views_match_across_id_modes() {
+    for_each_id_mode(|fixture| {
+        let hashed = fixture.client.get_id_config().is_some_and(|config| config.mode == IdMode::ContentHash);
+        let property_ids: std::vec::Vec<u64> = (0..3).map(|_| fixture.register_verified_property(100, 10)).collect();
+        for property_id in &property_ids {
+            assert_eq!(*property_id >= 1 << 63, hashed);
+            assert_eq!(fixture.client.get_property(property_id).property_id, *property_id);
+        }
+        let alice = fixture.buyer_with_tokens(1000);
+        let bob = Address::generate(&fixture.env);
+        fixture.client.purchase_shares(&property_ids[0], &10, &alice, &None);
+        fixture.client.purchase_shares(&property_ids[2], &20, &alice, &None);
+        fixture.client.transfer_shares(&property_ids[2], &alice, &bob, &5, &None);
+        
+        // Every index lists properties in registration order, whatever their IDs look like
+        let listed: std::vec::Vec<u64> = fixture.client.list_properties_v2(&None, &10).items.iter().map(|summary| summary.property_id).collect();
+        assert_eq!(listed, property_ids);
+        let tenant_ids: std::vec::Vec<u64> = fixture.client.get_properties_by_tenant(&symbol_short!("default"), &0, &10).iter().collect();
+        assert_eq!(tenant_ids, property_ids);
+        assert_eq!(fixture.client.get_user_properties(&alice), vec![&fixture.env, property_ids[0], property_ids[2]]);
+        assert_eq!(fixture.client.get_user_properties(&bob), vec![&fixture.env, property_ids[2]]);
+        assert_eq!(fixture.client.get_total_shares_owned(&alice), 25);
+        assert_eq!(fixture.client.get_property_stats().total_properties, 3);
+        assert!(fixture.client.check_all(&0, &10).is_empty());
+    });
+}
+
+#[test]
+fn test_push_distribution_sweeps_in_batches_without_double_pay() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &1000);
+    let first = fixture.buyer_with_tokens(1000);
+    let second = fixture.buyer_with_tokens(1000);
+    let third = fixture.buyer_with_tokens(1000);
+    for holder in [&first, &second, &third] {
+        fixture.client.purchase_shares(&property_id, &10, holder, &None);
+    }
+    fixture.client.deposit_income(&registrant, &property_id, &300);
+    let distribution_id = fixture.client.get_distribution_count(&property_id);
+    
+    // The first holder claims before the sweep reaches them, the second while it is under way
+    fixture.client.claim_dividends(&property_id, &first);
+    let cursor = fixture.client.push_distribute(&fixture.admin, &property_id, &distribution_id, &0, &2);
+    assert_eq!(cursor, Some(2));
+    assert!(fixture.client.try_claim_dividends(&property_id, &second).is_err());
+    assert!(fixture.client.try_push_distribute(&fixture.admin, &property_id, &distribution_id, &0, &2).is_err());
+    fixture.client.claim_dividends(&property_id, &third);
+    assert_eq!(fixture.client.push_distribute(&fixture.admin, &property_id, &distribution_id, &2, &2), None);
+    assert!(fixture.client.is_distribution_settled(&property_id, &distribution_id));
+    assert!(fixture.client.try_get_distribution(&property_id, &distribution_id).is_err());
+    
+    assert_eq!(fixture.client.get_internal_balance(&first), 0);
+    assert_eq!(fixture.client.get_internal_balance(&second), 100);
+    assert_eq!(fixture.client.get_internal_balance(&third), 0);
+    assert_eq!(fixture.client.withdraw_balance(&second), 100);
+    for holder in [&first, &second, &third] {
+        assert_eq!(fixture.token_balance(holder), 1000);
+    }
+    assert!(fixture.client.try_push_distribute(&fixture.admin, &property_id, &distribution_id, &2, &2).is_err());
+}
+
+#[test]
+fn test_sandbox_reset_wipes_state_until_go_live() {
+    let fixture = TestFixture::sandbox();
+    assert!(fixture.client.is_sandbox());
+    let first = fixture.register_verified_property(1000, 10);
+    let second = fixture.register_verified_property(500, 20);
+    fixture.register_property(100, 5);
+    let alice = fixture.buyer_with_tokens(100_000);
+    let bob = fixture.buyer_with_tokens(100_000);
+    fixture.client.purchase_shares(&first, &100, &alice, &None);
+    fixture.client.purchase_shares(&second, &50, &bob, &None);
+    fixture.client.transfer_shares(&first, &alice, &bob, &30, &None);
+    let sandbox_topic: Val = symbol_short!("sandbox").into_val(&fixture.env);
+    assert!(fixture.env.events().all().iter().any(|(contract, topics, _)| contract == fixture.contract_id && topics.get(3).is_some_and(|topic| topic.shallow_eq(&sandbox_topic))));
+    
+    // Three properties and two accounts, wiped two items at a time
+    let mut cursor = Some(0);
+    let mut batches = 0;
+    while let Some(position) = cursor {
+        cursor = fixture.client.reset_sandbox(&position, &2);
+        batches += 1;
+    }
+    assert_eq!(batches, 3);
+    
+    let stats = fixture.client.get_property_stats();
+    assert_eq!((stats.total_properties, stats.verified_properties, stats.total_owners, stats.total_transactions), (0, 0, 0, 0));
+    let value_stats = fixture.client.get_value_stats();
+    assert_eq!((value_stats.total_market_cap, value_stats.verified_market_cap), (0, 0));
+    assert!(fixture.client.try_get_property(&first).is_err());
+    assert!(fixture.client.list_properties_v2(&None, &10).items.is_empty());
+    for owner in [&alice, &bob] {
+        assert!(fixture.client.get_user_properties(owner).is_empty());
+        assert_eq!(fixture.client.get_total_shares_owned_cached(owner), 0);
+        assert_eq!(fixture.client.get_ownership(&first, owner).shares, 0);
+    }
+    assert_eq!(fixture.register_property(10, 10), 1);
+    
+    // Going live drops the sandbox topic and makes the reset impossible for good
+    fixture.client.go_live();
+    assert!(!fixture.client.is_sandbox());
+    assert!(fixture.client.try_reset_sandbox(&0, &10).is_err());
+    assert!(fixture.client.try_go_live().is_err());
+    let live = fixture.register_verified_property(10, 10);
+    fixture.client.purchase_shares(&live, &1, &alice, &None);
+    assert!(!fixture.env.events().all().iter().any(|(_, topics, _)| topics.iter().any(|topic| topic.shallow_eq(&sandbox_topic))));
+    assert_eq!(fixture.client.get_ownership(&live, &alice).shares, 1);
+}
+
+#[test]
+fn test_strict_auth_rejects_entries_signed_for_other_terms() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let from = fixture.buyer_with_tokens(100_000);
+    let to = Address::generate(&fixture.env);
+    let other = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &1000, &from, &None);
+    fixture.client.set_strict_auth(&true);
+    
+    let signed = |shares: u64, recipient: &Address| MockAuthInvoke {
+        contract: &fixture.contract_id,
+        fn_name: "transfer_shares",
+        args: (property_id, shares, recipient.clone()).into_val(&fixture.env),
+        sub_invokes: &[],
+    };
+    let one_share = signed(1, &to);
+    let redirected = signed(1, &other);
+    
+    // A signature over one share cannot be replayed for a thousand, nor redirected
+    assert!(fixture.client.mock_auths(&[MockAuth { address: &from, invoke: &one_share }]).try_transfer_shares(&property_id, &from, &to, &1000, &None).is_err());
+    assert!(fixture.client.mock_auths(&[MockAuth { address: &from, invoke: &redirected }]).try_transfer_shares(&property_id, &from, &to, &1, &None).is_err());
+    assert_eq!(fixture.client.get_ownership(&property_id, &to).shares, 0);
+    
+    // The exact terms go through
+    fixture.client.mock_auths(&[MockAuth { address: &from, invoke: &one_share }]).transfer_shares(&property_id, &from, &to, &1, &None);
+    assert_eq!(fixture.client.get_ownership(&property_id, &to).shares, 1);
+    assert_eq!(fixture.client.get_ownership(&property_id, &from).shares, 999);
+}
+
+#[test]
+fn test_finalize_offering_credits_unsold_shares_to_treasury() {
+    for_each_id_mode(|fixture| {
+        let registrant = Address::generate(&fixture.env);
+        let treasury = Address::generate(&fixture.env);
+        let mut input = fixture.property_input(1000, 10);
+        input.unsold_shares_recipient = Some(treasury.clone());
+        let property_id = fixture.client.register_property(&registrant, &input);
+        fixture.client.verify_property(&fixture.admin, &property_id);
+        let alice = fixture.buyer_with_tokens(100_000);
+        let bob = fixture.buyer_with_tokens(100_000);
+        fixture.client.purchase_shares(&property_id, &400, &alice, &None);
+        fixture.client.purchase_shares(&property_id, &300, &bob, &None);
+        let owners_before = fixture.client.get_property_stats().total_owners;
+        
+        // 30% of the supply is unsold at close
+        fixture.client.finalize_offering(&property_id);
+        let allocation = fixture.client.get_treasury_allocation(&property_id).unwrap();
+        assert_eq!((allocation.recipient.clone(), allocation.shares, allocation.allocated_at), (treasury.clone(), 300, fixture.env.ledger().timestamp()));
+        assert_eq!(fixture.client.get_available_shares(&property_id), 0);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 1000);
+        assert_eq!(fixture.client.get_property_stats().total_owners, owners_before + 1);
+        assert_eq!(fixture.client.get_user_properties(&treasury), vec![&fixture.env, property_id]);
+        let cap_table = fixture.client.get_cap_table(&property_id, &fixture.admin, &None, &10).items;
+        assert_eq!(cap_table.len(), 3);
+        assert!(cap_table.contains((treasury.clone(), 300)));
+        fixture.assert_supply_invariant(property_id, &[alice.clone(), bob.clone(), treasury.clone()]);
+        assert!(fixture.client.try_finalize_offering(&property_id).is_err());
+        
+        // Treasury shares move like any other position
+        fixture.client.transfer_shares(&property_id, &treasury, &alice, &100, &None);
+        assert_eq!(fixture.client.try_transfer_shares(&property_id, &treasury, &alice, &201, &None).err(), Some(Ok(contract_error(Error::InsufficientShares))));
+        fixture.client.pause_property(&fixture.admin, &property_id);
+        assert_eq!(fixture.client.try_transfer_shares(&property_id, &treasury, &bob, &1, &None).err(), Some(Ok(contract_error(Error::PropertyPaused))));
+        assert_eq!(fixture.client.get_ownership(&property_id, &treasury).shares, 200);
+        assert_eq!(fixture.client.get_total_shares_owned_cached(&treasury), 200);
+        
+        // Without a recipient the unsold supply stays on sale
+        let open = fixture.register_verified_property(1000, 10);
+        fixture.client.purchase_shares(&open, &700, &alice, &None);
+        fixture.client.finalize_offering(&open);
+        assert!(fixture.client.get_treasury_allocation(&open).is_none());
+        assert_eq!(fixture.client.get_available_shares(&open), 300);
+    });
+}
+
+#[test]
+fn test_badges_are_awarded_once_per_condition() {
+    for_each_id_mode(|fixture| {
+        let properties: std::vec::Vec<u64> = (0..5).map(|_| fixture.register_verified_property(100, 10)).collect();
+        let early = fixture.buyer_with_tokens(10_000);
+        let late = fixture.buyer_with_tokens(10_000);
+        
+        // Buying within a day of registration earns FirstPurchase and EarlyBacker
+        fixture.client.purchase_shares(&properties[0], &10, &early, &None);
+        fixture.client.purchase_shares(&properties[4], &10, &early, &None);
+        assert!(fixture.client.get_badges(&early) == vec![&fixture.env, Badge::FirstPurchase, Badge::EarlyBacker]);
+        
+        // A later buyer only earns FirstPurchase, however many purchases they make
+        fixture.advance_time(2 * 86400);
+        for property_id in &properties[..4] {
+            fixture.client.purchase_shares(property_id, &10, &late, &None);
+        }
+        assert!(fixture.client.get_badges(&late) == vec![&fixture.env, Badge::FirstPurchase]);
+        
+        // A transfer that brings the recipient to a fifth property earns FivePropertiesOwned
+        fixture.client.transfer_shares(&properties[4], &early, &late, &5, &None);
+        assert!(fixture.client.get_badges(&late) == vec![&fixture.env, Badge::FirstPurchase, Badge::FivePropertiesOwned]);
+        fixture.client.purchase_shares(&properties[4], &1, &late, &None);
+        assert_eq!(fixture.client.get_badges(&late).len(), 2);
+        
+        // DiamondHands needs a position held for a year, and is claimed once
+        assert!(fixture.client.claim_time_based_badges(&late).is_empty());
+        fixture.advance_time(365 * 86400);
+        assert!(fixture.client.claim_time_based_badges(&late) == vec![&fixture.env, Badge::DiamondHands]);
+        assert!(fixture.client.claim_time_based_badges(&late).is_empty());
+        assert!(fixture.client.get_badges(&late) == vec![&fixture.env, Badge::FirstPurchase, Badge::FivePropertiesOwned, Badge::DiamondHands]);
+        
+        // Badges stay with their owner
+        assert!(fixture.client.get_badges(&early) == vec![&fixture.env, Badge::FirstPurchase, Badge::EarlyBacker]);
+    });
+}
+
+#[test]
+fn test_atomic_onboarding_matches_separate_calls() {
+    for_each_id_mode(|fixture| {
+        let event_names = || -> std::vec::Vec<Symbol> {
+            fixture.env.events().all().iter()
+                .filter(|(contract, _, _)| *contract == fixture.contract_id)
+                .map(|(_, topics, _)| Symbol::try_from_val(&fixture.env, &topics.get(0).unwrap()).unwrap())
+                .collect()
+        };
+        let mut input = fixture.property_input(100, 10);
+        
+        // Register, verify and credit the founder stake as separate calls
+        let separate_registrant = Address::generate(&fixture.env);
+        input.founder_shares = 20;
+        let separate = fixture.client.register_property(&separate_registrant, &input);
+        let mut separate_events = event_names();
+        fixture.client.verify_property(&fixture.admin, &separate);
+        separate_events.extend(event_names());
+        
+        // The same onboarding in one invocation
+        let atomic_registrant = Address::generate(&fixture.env);
+        input.title = String::from_str(&fixture.env, "Atomic Property");
+        input.founder_shares = 0;
+        let atomic = fixture.client.register_verified_property(&fixture.admin, &atomic_registrant, &input, &20);
+        let signers: std::vec::Vec<Address> = fixture.env.auths().into_iter().map(|(signer, _)| signer).collect();
+        assert!(signers.contains(&fixture.admin) && signers.contains(&atomic_registrant));
+        assert!(separate_events.len() >= 2);
+        assert_eq!(event_names(), separate_events);
+        
+        let (separate_property, atomic_property) = (fixture.client.get_property(&separate), fixture.client.get_property(&atomic));
+        assert!(atomic_property.is_verified && separate_property.is_verified);
+        assert_eq!(atomic_property.total_shares, separate_property.total_shares);
+        assert_eq!(atomic_property.price_per_share, separate_property.price_per_share);
+        assert_eq!(atomic_property.registration_time, separate_property.registration_time);
+        assert_eq!(fixture.client.get_shares_sold(&atomic), fixture.client.get_shares_sold(&separate));
+        assert_eq!(fixture.client.get_ownership(&atomic, &atomic_registrant).shares, 20);
+        assert_eq!(fixture.client.get_ownership(&separate, &separate_registrant).shares, 20);
+        assert_eq!(fixture.client.get_property_stats().verified_properties, 2);
+        fixture.assert_invariants(atomic);
+        fixture.assert_stats_invariants();
+        
+        // Only the admin may co-sign, whatever roles the caller holds
+        let partner = Address::generate(&fixture.env);
+        fixture.client.grant_role(&Role::Registrar, &partner);
+        fixture.client.grant_role(&Role::Verifier, &partner);
+        input.title = String::from_str(&fixture.env, "Partner Property");
+        assert!(fixture.client.try_register_verified_property(&partner, &Address::generate(&fixture.env), &input, &20).is_err());
+        
+        // A failing step reverts the whole call
+        let rejected_registrant = Address::generate(&fixture.env);
+        input.title = String::from_str(&fixture.env, "Rejected Property");
+        assert!(fixture.client.try_register_verified_property(&fixture.admin, &rejected_registrant, &input, &101).is_err());
+        assert_eq!(fixture.client.get_property_stats().total_properties, 2);
+        assert!(fixture.client.get_user_properties(&rejected_registrant).is_empty());
+    });
+}
+
+#[test]
+fn test_metadata_overwrite_crossing_total_limit_is_rejected() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_property(100, 10);
+        let value = |len: u32| Bytes::from_slice(&fixture.env, &std::vec![7u8; len as usize]);
+        let key = |i: u32| Symbol::new(&fixture.env, &std::format!("key{i}"));
+        let registrant = fixture.client.get_registrant(&property_id);
+        let manager = Address::generate(&fixture.env);
+        fixture.client.set_property_manager(&property_id, &manager);
+        
+        // Seven full keys and two smaller ones leave 48 bytes of headroom
+        for i in 0..7 {
+            fixture.client.set_property_metadata(&registrant, &property_id, &key(i), &value(2048));
+        }
+        fixture.client.set_property_metadata(&registrant, &property_id, &symbol_short!("amenities"), &value(1000));
+        fixture.client.set_property_metadata(&registrant, &property_id, &symbol_short!("legal"), &value(1000));
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 16336);
+        
+        // Only the registrant and the manager may edit, and values live in persistent storage
+        let stranger = Address::generate(&fixture.env);
+        assert!(fixture.client.try_set_property_metadata(&stranger, &property_id, &symbol_short!("legal"), &value(1)).is_err());
+        assert!(fixture.client.try_delete_metadata_key(&stranger, &property_id, &symbol_short!("legal")).is_err());
+        fixture.env.as_contract(&fixture.contract_id, || {
+            let value_key = MetadataRegistry::Value(property_id, symbol_short!("legal"));
+            assert!(fixture.env.storage().persistent().has(&value_key) && !fixture.env.storage().instance().has(&value_key));
+            assert!(fixture.env.storage().persistent().has(&MetadataRegistry::TotalSize(property_id)));
+        });
+        
+        // Growing a key past the total is rejected and leaves the old value in place
+        assert!(fixture.client.try_set_property_metadata(&manager, &property_id, &symbol_short!("amenities"), &value(1049)).is_err());
+        assert_eq!(fixture.client.get_property_metadata(&property_id, &symbol_short!("amenities")).unwrap().len(), 1000);
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 16336);
+        
+        // Overwrites are charged only the difference, up to exactly the limit
+        fixture.client.set_property_metadata(&manager, &property_id, &symbol_short!("amenities"), &value(1048));
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 16384);
+        assert_eq!(fixture.client.list_metadata_keys(&property_id).len(), 9);
+        
+        // Deletes and shrinking overwrites release their bytes
+        fixture.client.delete_metadata_key(&manager, &property_id, &symbol_short!("legal"));
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 15384);
+        fixture.client.set_property_metadata(&manager, &property_id, &symbol_short!("amenities"), &value(10));
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 14346);
+        assert_eq!(fixture.client.list_metadata_keys(&property_id).len(), 8);
+        assert!(fixture.client.get_property_metadata(&property_id, &symbol_short!("legal")).is_none());
+        
+        for i in 0..7 {
+            fixture.client.delete_metadata_key(&registrant, &property_id, &key(i));
+        }
+        fixture.client.delete_metadata_key(&registrant, &property_id, &symbol_short!("amenities"));
+        assert_eq!(fixture.client.get_metadata_size(&property_id), 0);
+        assert!(fixture.client.list_metadata_keys(&property_id).is_empty());
+    });
+}
+
+#[test]
+fn test_migration_moves_three_positions_and_pending_dividend() {
+    for_each_id_mode(|fixture| {
+        let properties: std::vec::Vec<u64> = (0..3).map(|_| fixture.register_verified_property(100, 10)).collect();
+        let old = fixture.buyer_with_tokens(1000);
+        let other = fixture.buyer_with_tokens(1000);
+        let new = Address::generate(&fixture.env);
+        for property_id in &properties {
+            fixture.client.purchase_shares(property_id, &10, &old, &None);
+        }
+        fixture.client.purchase_shares(&properties[0], &10, &other, &None);
+        let registrant = fixture.client.get_registrant(&properties[0]);
+        StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &400);
+        fixture.client.deposit_income(&registrant, &properties[0], &400);
+        let pending = fixture.client.get_claimable(&properties[0], &old);
+        assert!(pending > 0);
+        
+        // Two positions per batch, resumed from the returned cursor
+        assert_eq!(fixture.client.migrate_account(&old, &new, &0, &2), Some(2));
+        assert_eq!(fixture.client.migrate_account(&old, &new, &2, &2), None);
+        
+        for property_id in &properties {
+            assert_eq!(fixture.client.get_ownership(property_id, &old).shares, 0);
+            assert_eq!(fixture.client.get_ownership(property_id, &new).shares, 10);
+            fixture.assert_supply_invariant(*property_id, &[old.clone(), new.clone(), other.clone()]);
+            fixture.assert_invariants(*property_id);
+        }
+        assert_eq!(fixture.client.get_user_properties(&new).len(), 3);
+        assert_eq!(fixture.client.get_total_shares_owned_cached(&old), 0);
+        assert_eq!(fixture.client.get_total_shares_owned_cached(&new), 30);
+        
+        // The pending dividend follows the shares, once
+        assert_eq!(fixture.client.get_claimable(&properties[0], &old), 0);
+        assert_eq!(fixture.client.get_claimable(&properties[0], &new), pending);
+        assert_eq!(fixture.client.claim_dividends(&properties[0], &new), pending);
+        assert_eq!(fixture.token_balance(&new), pending as i128);
+        assert!(fixture.client.try_claim_dividends(&properties[0], &old).is_err());
+        assert_eq!(fixture.client.claim_dividends(&properties[0], &other), pending);
+        
+        // The old address is marked and refuses incoming shares
+        assert_eq!(fixture.client.get_migrated_to(&old), Some(new.clone()));
+        assert_eq!(fixture.client.try_transfer_shares(&properties[0], &other, &old, &1, &None), Err(Ok(contract_error(Error::AccountMigrated))));
+    });
+}
+
+#[test]
+fn test_milestone_escrow_partial_releases_and_refunds() {
+    for_each_id_mode(|fixture| {
+        let registrant = Address::generate(&fixture.env);
+        let mut input = fixture.property_input(100, 10);
+        input.milestone_escrow = true;
+        let property_id = fixture.client.register_property(&registrant, &input);
+        fixture.client.verify_property(&fixture.admin, &property_id);
+        let verifier = Address::generate(&fixture.env);
+        fixture.client.grant_role(&Role::Verifier, &verifier);
+        fixture.client.set_milestones(&verifier, &property_id, &vec![
+            &fixture.env,
+            (String::from_str(&fixture.env, "Permits"), 25),
+            (String::from_str(&fixture.env, "Foundation"), 25),
+            (String::from_str(&fixture.env, "Completion"), 50),
+        ]);
+        let (first, second, third) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        fixture.client.purchase_shares(&property_id, &30, &first, &None);
+        fixture.client.purchase_shares(&property_id, &10, &second, &None);
+        assert_eq!(fixture.client.get_escrow_balance(&property_id), 400);
+        assert_eq!(fixture.token_balance(&registrant), 0);
+        
+        // Each release pays its percentage of the proceeds escrowed so far, whether a verifier
+        // releases it or co-owners vote it through
+        let stranger = Address::generate(&fixture.env);
+        assert!(fixture.client.try_release_milestone(&stranger, &property_id, &0).is_err());
+        assert_eq!(fixture.client.release_milestone(&verifier, &property_id, &0), 100);
+        fixture.client.purchase_shares(&property_id, &20, &third, &None);
+        assert_eq!(fixture.client.get_escrow_balance(&property_id), 500);
+        let proposal_id = fixture.client.create_proposal(&third, &property_id, &ProposalAction::ReleaseMilestone(1), &BytesN::from_array(&fixture.env, &[3u8; 32]));
+        fixture.client.vote(&first, &proposal_id, &true);
+        fixture.client.vote(&third, &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        assert_eq!(fixture.client.get_escrow_balance(&property_id), 350);
+        assert_eq!(fixture.token_balance(&registrant), 250);
+        assert!(fixture.client.try_release_milestone(&verifier, &property_id, &1).is_err());
+        assert!(fixture.client.try_set_milestones(&verifier, &property_id, &vec![&fixture.env, (String::from_str(&fixture.env, "All"), 100)]).is_err());
+        
+        let milestones = fixture.client.get_milestones(&property_id);
+        assert!(milestones.get(0).unwrap().is_released && milestones.get(1).unwrap().is_released);
+        assert!(!milestones.get(2).unwrap().is_released);
+        assert_eq!(milestones.get(1).unwrap().released_amount, 150);
+        
+        // After failure the unreleased 350 is refunded pro rata to what each buyer paid
+        assert!(fixture.client.try_claim_escrow_refund(&property_id, &first).is_err());
+        fixture.client.mark_offering_failed(&property_id);
+        assert!(fixture.client.try_release_milestone(&verifier, &property_id, &2).is_err());
+        assert_eq!(fixture.client.claim_escrow_refund(&property_id, &first), 175);
+        assert_eq!(fixture.client.get_ownership(&property_id, &first).shares, 0);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 30);
+        assert_eq!(fixture.client.claim_escrow_refund(&property_id, &second), 58);
+        assert_eq!(fixture.client.claim_escrow_refund(&property_id, &third), 116);
+        assert!(fixture.client.try_claim_escrow_refund(&property_id, &first).is_err());
+        assert!(fixture.client.try_claim_escrow_refund(&property_id, &registrant).is_err());
+        
+        assert_eq!(fixture.token_balance(&first), 1000 - 300 + 175);
+        assert_eq!(fixture.token_balance(&second), 1000 - 100 + 58);
+        assert_eq!(fixture.token_balance(&third), 1000 - 200 + 116);
+        // Only rounding dust stays behind, and it is still covered by the contract's balance
+        assert_eq!(fixture.client.get_escrow_balance(&property_id), 1);
+        assert!(fixture.token_balance(&fixture.contract_id) >= 1);
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_cursors_round_trip_and_are_rejected_by_other_endpoints() {
+    for_each_id_mode(|fixture| {
+        let buyer = fixture.buyer_with_tokens(10_000);
+        let property_ids: std::vec::Vec<u64> = (0..5).map(|_| {
+            let property_id = fixture.register_verified_property(100, 10);
+            fixture.client.purchase_shares(&property_id, &10, &buyer, &None);
+            property_id
+        }).collect();
+        let filter = PropertyFilter {
+            verified_only: true,
+            statuses: Vec::new(&fixture.env),
+            categories: Vec::new(&fixture.env),
+            occupancies: Vec::new(&fixture.env),
+            min_price: None,
+            max_price: None,
+        };
+        
+        // Following next_cursor two items at a time visits every entry once, in order
+        let (mut listed, mut filtered, mut held) = (std::vec::Vec::new(), std::vec::Vec::new(), std::vec::Vec::new());
+        let mut cursor = None;
+        loop {
+            let page = fixture.client.list_properties_v2(&cursor, &2);
+            assert_eq!(page.total, Some(5));
+            listed.extend(page.items.iter().map(|summary| summary.property_id));
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        loop {
+            let page = fixture.client.list_properties(&filter, &cursor, &2);
+            filtered.extend(page.items.iter().map(|summary| summary.property_id));
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        loop {
+            let page = fixture.client.get_user_holdings(&buyer, &cursor, &2);
+            assert_eq!(page.total, Some(5));
+            held.extend(page.items.iter().map(|ownership| ownership.property_id));
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        assert_eq!(listed, property_ids);
+        assert_eq!(filtered, property_ids);
+        assert_eq!(held, property_ids);
+        
+        // A cursor is stable across calls but only valid on the endpoint that issued it
+        let properties_cursor = fixture.client.list_properties_v2(&None, &2).next_cursor;
+        let filtered_cursor = fixture.client.list_properties(&filter, &None, &2).next_cursor;
+        let holdings_cursor = fixture.client.get_user_holdings(&buyer, &None, &2).next_cursor;
+        assert!(properties_cursor.is_some() && filtered_cursor.is_some() && holdings_cursor.is_some());
+        assert_eq!(fixture.client.list_properties_v2(&properties_cursor, &2).items.get(0).unwrap().property_id, property_ids[2]);
+        
+        let invalid = Some(Ok(contract_error(Error::InvalidCursor)));
+        assert_eq!(fixture.client.try_get_user_holdings(&buyer, &properties_cursor, &2).err(), invalid);
+        assert_eq!(fixture.client.try_list_properties(&filter, &holdings_cursor, &2).err(), invalid);
+        assert_eq!(fixture.client.try_list_properties_v2(&filtered_cursor, &2).err(), invalid);
+        assert_eq!(fixture.client.try_list_properties_v2(&holdings_cursor, &2).err(), invalid);
+        
+        // Malformed cursors are rejected the same way
+        let truncated = properties_cursor.map(|cursor| cursor.slice(..8));
+        assert_eq!(fixture.client.try_list_properties_v2(&truncated, &2).err(), invalid);
+        assert_eq!(fixture.client.try_get_user_holdings(&buyer, &Some(Bytes::new(&fixture.env)), &2).err(), invalid);
+    });
+}
+
+#[test]
+fn test_time_weighted_income_follows_holding_time() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &10_000);
+    let early = fixture.buyer_with_tokens(10_000);
+    let late = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_id, &100, &early, &None);
+    fixture.client.enable_time_weighted_income(&fixture.admin, &property_id);
+    
+    // 100 shares held for 200s against 100 shares held for 100s
+    fixture.advance_time(100);
+    fixture.client.purchase_shares(&property_id, &100, &late, &None);
+    fixture.advance_time(100);
+    fixture.client.deposit_income(&registrant, &property_id, &3000);
+    assert_eq!(fixture.client.get_claimable(&property_id, &early), 2000);
+    assert_eq!(fixture.client.get_claimable(&property_id, &late), 1000);
+    
+    // A transfer halfway through a period splits it: 7500 share-seconds against 12500
+    fixture.advance_time(50);
+    fixture.client.transfer_shares(&property_id, &early, &late, &50, &None);
+    fixture.advance_time(50);
+    fixture.client.deposit_income(&registrant, &property_id, &2000);
+    assert_eq!(fixture.client.get_claimable(&property_id, &early), 2000 + 750);
+    assert_eq!(fixture.client.get_claimable(&property_id, &late), 1000 + 1250);
+    
+    assert_eq!(fixture.client.claim_dividends(&property_id, &early), 2750);
+    assert_eq!(fixture.token_balance(&early), 10_000 - 1000 + 2750);
+    assert!(fixture.client.try_enable_time_weighted_income(&fixture.admin, &property_id).is_err());
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_match_orders_fills_crossing_orders_at_the_resting_price() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let (first_seller, second_seller) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        let buyer = fixture.buyer_with_tokens(1000);
+        fixture.client.purchase_shares(&property_id, &30, &first_seller, &None);
+        fixture.client.purchase_shares(&property_id, &20, &second_seller, &None);
+        let expensive = fixture.client.create_sell_order(&first_seller, &property_id, &10, &12);
+        let cheap = fixture.client.create_sell_order(&second_seller, &property_id, &10, &11);
+        
+        // Nothing crosses while the best bid is below the cheapest ask
+        let low = fixture.client.make_offer(&buyer, &property_id, &5, &10, &1000);
+        assert_eq!(fixture.client.match_orders(&property_id, &10, &None), 0);
+        fixture.client.cancel_offer(&low, &None);
+        
+        // The asks rest first, so each trade executes at the ask and the bid's excess is refunded
+        let offer_id = fixture.client.make_offer(&buyer, &property_id, &15, &13, &1000);
+        assert_eq!(fixture.token_balance(&buyer), 805);
+        assert_eq!(fixture.client.match_orders(&property_id, &1, &None), 1);
+        assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 10);
+        assert_eq!(fixture.token_balance(&second_seller), 910);
+        assert_eq!(fixture.client.get_sell_orders(&property_id), vec![&fixture.env, expensive]);
+        assert_eq!(fixture.client.get_offer(&offer_id).shares, 5);
+        
+        assert_eq!(fixture.client.match_orders(&property_id, &10, &None), 1);
+        assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 15);
+        assert_eq!(fixture.token_balance(&buyer), 830);
+        assert_eq!(fixture.token_balance(&first_seller), 760);
+        assert_eq!(fixture.client.get_sell_order(&expensive).shares, 5);
+        assert!(fixture.client.get_offers(&property_id).is_empty());
+        assert!(fixture.client.try_get_sell_order(&cheap).is_err());
+        let last_trade = fixture.client.get_price_history(&property_id, &None, &1).items.get(0).unwrap();
+        assert_eq!((last_trade.price_per_share, last_trade.shares, last_trade.primary), (12, 5, false));
+        
+        // A resting bid sets the price for an ask that arrives below it
+        let resting = fixture.client.make_offer(&buyer, &property_id, &5, &14, &1000);
+        fixture.client.cancel_sell_order(&expensive);
+        fixture.client.create_sell_order(&first_seller, &property_id, &5, &9);
+        assert_eq!(fixture.client.match_orders(&property_id, &10, &None), 1);
+        assert_eq!(fixture.token_balance(&first_seller), 830);
+        assert!(fixture.client.try_get_offer(&resting).is_err());
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_installment_plans_release_shares_on_full_payment_and_forfeit_on_default() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let (buyer, defaulter) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        let interval = 30 * 86400;
+        assert!(fixture.client.try_start_installment_purchase(&buyer, &property_id, &10, &1).is_err());
+        assert!(fixture.client.try_start_installment_purchase(&buyer, &property_id, &101, &3).is_err());
+        
+        // The first of three installments is taken up front, the last one carries the remainder
+        let plan_id = fixture.client.start_installment_purchase(&buyer, &property_id, &10, &3);
+        let plan = fixture.client.get_installment_plan(&plan_id);
+        assert_eq!((plan.total_payable, plan.payments_made, plan.amount_paid, plan.next_due), (100, 1, 33, interval));
+        assert_eq!(fixture.client.get_reserved_shares(&property_id), 10);
+        assert_eq!(fixture.client.get_available_shares(&property_id), 90);
+        assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 0);
+        assert!(fixture.client.try_reclaim_reservation(&plan_id, &None).is_err());
+        
+        fixture.advance_time(interval);
+        assert_eq!(fixture.client.pay_installment(&plan_id), 2);
+        fixture.advance_time(interval);
+        assert_eq!(fixture.client.pay_installment(&plan_id), 3);
+        assert_eq!(fixture.token_balance(&buyer), 900);
+        assert_eq!(fixture.token_balance(&registrant), 100);
+        assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 10);
+        assert_eq!(fixture.client.get_reserved_shares(&property_id), 0);
+        assert!(fixture.client.try_get_installment_plan(&plan_id).is_err());
+        
+        // Missing a due date lets anyone reclaim the reservation; the registrant keeps 10% of the
+        // total payable and the rest of what was paid goes back to the buyer
+        let defaulted = fixture.client.start_installment_purchase(&defaulter, &property_id, &20, &4);
+        fixture.advance_time(interval + 1);
+        assert!(fixture.client.try_pay_installment(&defaulted).is_err());
+        assert_eq!(fixture.client.reclaim_reservation(&defaulted, &None), 20);
+        assert_eq!(fixture.token_balance(&defaulter), 980);
+        assert_eq!(fixture.token_balance(&registrant), 120);
+        assert_eq!(fixture.client.get_ownership(&property_id, &defaulter).shares, 0);
+        assert_eq!(fixture.client.get_available_shares(&property_id), 90);
+        assert!(fixture.client.try_reclaim_reservation(&defaulted, &None).is_err());
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_vested_shares_unlock_linearly_after_the_cliff() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(200, 10);
+        let (grantee, recipient) = (Address::generate(&fixture.env), Address::generate(&fixture.env));
+        assert_eq!(fixture.client.try_grant_vested_shares(&property_id, &grantee, &0, &25, &100).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        assert!(fixture.client.try_grant_vested_shares(&property_id, &grantee, &100, &101, &100).is_err());
+        assert_eq!(fixture.client.try_grant_vested_shares(&property_id, &grantee, &201, &25, &100).err(), Some(Ok(contract_error(Error::SupplyExceeded))));
+        
+        // Granted shares are credited at once but locked until they vest
+        fixture.client.grant_vested_shares(&property_id, &grantee, &100, &25, &100);
+        assert_eq!(fixture.client.get_ownership(&property_id, &grantee).shares, 100);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 100);
+        assert_eq!(fixture.client.get_vesting_schedules(&property_id, &grantee).len(), 1);
+        fixture.advance_time(24);
+        assert_eq!(fixture.client.get_unvested_shares(&property_id, &grantee), 100);
+        assert!(fixture.client.try_transfer_shares(&property_id, &grantee, &recipient, &1, &None).is_err());
+        
+        // Past the cliff the vested share grows with elapsed time, and only it can move or be listed
+        fixture.advance_time(26);
+        assert_eq!(fixture.client.get_unvested_shares(&property_id, &grantee), 50);
+        assert!(fixture.client.try_transfer_shares(&property_id, &grantee, &recipient, &51, &None).is_err());
+        fixture.client.transfer_shares(&property_id, &grantee, &recipient, &30, &None);
+        assert!(fixture.client.try_create_sell_order(&grantee, &property_id, &21, &10).is_err());
+        fixture.client.create_sell_order(&grantee, &property_id, &20, &10);
+        
+        // A second grant vests on its own schedule
+        fixture.client.grant_vested_shares(&property_id, &grantee, &40, &0, &40);
+        fixture.advance_time(10);
+        assert_eq!(fixture.client.get_unvested_shares(&property_id, &grantee), 40 + 30);
+        fixture.advance_time(40);
+        assert_eq!(fixture.client.get_unvested_shares(&property_id, &grantee), 0);
+        fixture.client.transfer_shares(&property_id, &grantee, &recipient, &70, &None);
+        // The 20 listed shares stay in the grantee's position until the order fills
+        assert_eq!(fixture.client.get_ownership(&property_id, &grantee).shares, 40);
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_transfer_from_spends_the_allowance() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let holder = fixture.buyer_with_tokens(1000);
+        let (spender, recipient) = (Address::generate(&fixture.env), Address::generate(&fixture.env));
+        fixture.client.purchase_shares(&property_id, &40, &holder, &None);
+        assert!(fixture.client.try_transfer_from(&spender, &property_id, &holder, &recipient, &1).is_err());
+        
+        fixture.client.approve(&holder, &property_id, &spender, &25);
+        assert_eq!(fixture.client.get_allowance(&property_id, &holder, &spender), 25);
+        fixture.client.transfer_from(&spender, &property_id, &holder, &recipient, &10);
+        assert_eq!(fixture.client.get_allowance(&property_id, &holder, &spender), 15);
+        assert_eq!(fixture.client.get_ownership(&property_id, &recipient).shares, 10);
+        assert!(fixture.client.try_transfer_from(&spender, &property_id, &holder, &recipient, &16).is_err());
+        
+        // Approving again replaces the allowance rather than adding to it, and spending all of it clears it
+        fixture.client.approve(&holder, &property_id, &spender, &5);
+        fixture.client.transfer_from(&spender, &property_id, &holder, &spender, &5);
+        assert_eq!(fixture.client.get_allowance(&property_id, &holder, &spender), 0);
+        assert!(!fixture.env.as_contract(&fixture.contract_id, || {
+            fixture.env.storage().persistent().has(&AllowanceRegistry::Allowance(property_id, holder.clone(), spender.clone()))
+        }));
+        fixture.client.approve(&holder, &property_id, &spender, &10);
+        fixture.client.approve(&holder, &property_id, &spender, &0);
+        assert!(fixture.client.try_transfer_from(&spender, &property_id, &holder, &recipient, &1).is_err());
+        assert_eq!(fixture.client.get_ownership(&property_id, &holder).shares, 25);
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_operators_move_shares_across_properties_without_allowances() {
+    for_each_id_mode(|fixture| {
+        let (house, flat) = (fixture.register_verified_property(100, 10), fixture.register_verified_property(100, 10));
+        let holder = fixture.buyer_with_tokens(1000);
+        let (custodian, recipient) = (Address::generate(&fixture.env), Address::generate(&fixture.env));
+        fixture.client.purchase_shares(&house, &30, &holder, &None);
+        fixture.client.purchase_shares(&flat, &20, &holder, &None);
+        assert!(!fixture.client.is_operator(&holder, &custodian));
+        
+        fixture.client.set_operator(&holder, &custodian, &true);
+        assert!(fixture.client.is_operator(&holder, &custodian));
+        fixture.client.transfer_from(&custodian, &house, &holder, &recipient, &30);
+        fixture.client.transfer_from(&custodian, &flat, &holder, &recipient, &5);
+        assert_eq!(fixture.client.get_ownership(&house, &recipient).shares, 30);
+        assert_eq!(fixture.client.get_ownership(&flat, &recipient).shares, 5);
+        assert_eq!(fixture.client.get_allowance(&flat, &holder, &custodian), 0);
+        assert!(fixture.client.try_transfer_from(&custodian, &flat, &holder, &recipient, &16).is_err());
+        
+        // The right covers only the granting owner, and ends when revoked
+        assert!(fixture.client.try_transfer_from(&custodian, &house, &recipient, &holder, &1).is_err());
+        fixture.client.set_operator(&holder, &custodian, &false);
+        assert!(!fixture.client.is_operator(&holder, &custodian));
+        assert!(fixture.client.try_transfer_from(&custodian, &flat, &holder, &recipient, &1).is_err());
+        fixture.assert_invariants(house);
+        fixture.assert_invariants(flat);
+    });
+}
+
+#[test]
+fn test_delegated_votes_follow_balances_at_each_snapshot() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let (holder, other) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        let representative = Address::generate(&fixture.env);
+        fixture.client.purchase_shares(&property_id, &30, &holder, &None);
+        fixture.client.purchase_shares(&property_id, &20, &other, &None);
+        let propose = |seed: u8| fixture.client.create_proposal(&other, &property_id, &ProposalAction::Other, &BytesN::from_array(&fixture.env, &[seed; 32]));
+        
+        fixture.client.delegate_votes(&holder, &property_id, &representative);
+        assert_eq!(fixture.client.get_delegate(&property_id, &holder), representative);
+        assert_eq!(fixture.client.get_delegated_votes(&property_id, &representative), 30);
+        assert!(fixture.client.try_delegate_votes(&holder, &property_id, &representative).is_err());
+        let first = propose(8);
+        
+        // Delegated weight moves with the balance, but votes count the balances at each snapshot
+        fixture.client.transfer_shares(&property_id, &holder, &other, &10, &None);
+        assert_eq!(fixture.client.get_delegated_votes(&property_id, &representative), 20);
+        let second = propose(9);
+        assert_eq!(fixture.client.try_vote(&holder, &first, &true).err(), Some(Ok(contract_error(Error::NotOwner))));
+        assert_eq!(fixture.client.vote(&representative, &first, &true), 30);
+        assert_eq!(fixture.client.vote(&other, &first, &false), 20);
+        assert_eq!(fixture.client.vote(&representative, &second, &true), 20);
+        assert_eq!(fixture.client.vote(&other, &second, &false), 30);
+        
+        // Delegating back to oneself ends the delegation for later snapshots only
+        fixture.client.delegate_votes(&holder, &property_id, &holder);
+        assert_eq!(fixture.client.get_delegate(&property_id, &holder), holder);
+        assert_eq!(fixture.client.get_delegated_votes(&property_id, &representative), 0);
+        let third = propose(10);
+        let snapshot_of = |proposal_id: u64| fixture.client.get_proposal(&proposal_id).snapshot_id;
+        assert_eq!(fixture.client.get_votes_at(&property_id, &snapshot_of(first), &holder), 0);
+        assert_eq!(fixture.client.get_votes_at(&property_id, &snapshot_of(third), &holder), 20);
+        assert_eq!(fixture.client.try_vote(&representative, &third, &true).err(), Some(Ok(contract_error(Error::NotOwner))));
+        assert_eq!(fixture.client.vote(&holder, &third, &true), 20);
+    });
+}
+
+#[test]
+fn test_liquidation_redeems_pro_rata_with_remainder_to_last_holder() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let holders: std::vec::Vec<Address> = (0..3).map(|_| {
+            let holder = fixture.buyer_with_tokens(1000);
+            fixture.client.purchase_shares(&property_id, &10, &holder, &None);
+            holder
+        }).collect();
+        StellarAssetClient::new(&fixture.env, &fixture.token).mint(&fixture.admin, &100);
+        
+        // The sale can only be recorded once co-owners approve it
+        assert!(fixture.client.try_record_liquidation(&fixture.admin, &property_id, &100).is_err());
+        let proposal_id = fixture.client.create_proposal(&holders[0], &property_id, &ProposalAction::Liquidate, &BytesN::from_array(&fixture.env, &[4u8; 32]));
+        fixture.client.vote(&holders[0], &proposal_id, &true);
+        fixture.client.vote(&holders[1], &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        assert!(fixture.client.is_liquidation_approved(&property_id));
+        
+        let stranger = Address::generate(&fixture.env);
+        assert!(fixture.client.try_record_liquidation(&stranger, &property_id, &100).is_err());
+        fixture.client.record_liquidation(&fixture.admin, &property_id, &100);
+        assert_eq!(fixture.token_balance(&fixture.admin), 0);
+        assert_eq!(fixture.client.try_record_liquidation(&fixture.admin, &property_id, &100).err(), Some(Ok(contract_error(Error::PropertyLiquidated))));
+        assert!(fixture.client.get_property_status(&property_id) == PropertyStatus::Archived);
+        
+        // Shares are frozen; redemption burns them for 100 * 10 / 30, the last holder taking the remainder
+        assert_eq!(fixture.client.try_transfer_shares(&property_id, &holders[0], &stranger, &1, &None).err(), Some(Ok(contract_error(Error::PropertyLiquidated))));
+        assert_eq!(fixture.client.redeem_shares(&holders[0], &property_id), 33);
+        assert_eq!(fixture.client.redeem_shares(&holders[1], &property_id), 33);
+        assert_eq!(fixture.client.try_redeem_shares(&holders[0], &property_id).err(), Some(Ok(contract_error(Error::NotOwner))));
+        assert_eq!(fixture.client.redeem_shares(&holders[2], &property_id), 34);
+        
+        let liquidation = fixture.client.get_liquidation(&property_id).unwrap();
+        assert_eq!((liquidation.shares_redeemed, liquidation.amount_paid), (30, 100));
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 0);
+        for (holder, payout) in holders.iter().zip([33, 33, 34]) {
+            assert_eq!(fixture.client.get_ownership(&property_id, holder).shares, 0);
+            assert_eq!(fixture.token_balance(holder), 1000 - 100 + payout);
+        }
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_buyout_squeezes_out_remaining_holders_at_the_threshold() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let (bidder, seller, holdout) = (fixture.buyer_with_tokens(2000), fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        fixture.client.purchase_shares(&property_id, &50, &bidder, &None);
+        fixture.client.purchase_shares(&property_id, &40, &seller, &None);
+        fixture.client.purchase_shares(&property_id, &10, &holdout, &None);
+        
+        // Co-owners lower the squeeze-out threshold from 100% to 90%
+        let proposal_id = fixture.client.create_proposal(&bidder, &property_id, &ProposalAction::SetSqueezeOutThreshold(9000), &BytesN::from_array(&fixture.env, &[5u8; 32]));
+        fixture.client.vote(&bidder, &proposal_id, &true);
+        fixture.client.vote(&seller, &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        assert_eq!(fixture.client.get_squeeze_out_threshold(&property_id), 9000);
+        
+        // The bidder escrows the price of every share they do not hold
+        fixture.client.make_buyout_offer(&bidder, &property_id, &12);
+        assert_eq!(fixture.client.get_buyout(&property_id).unwrap().escrow, 600);
+        assert_eq!(fixture.token_balance(&bidder), 2000 - 500 - 600);
+        assert!(fixture.client.try_make_buyout_offer(&seller, &property_id, &15).is_err());
+        assert_eq!(fixture.client.try_accept_buyout(&bidder, &property_id).err(), Some(Ok(contract_error(Error::NotOwner))));
+        assert!(fixture.client.try_squeeze_out(&property_id).is_err());
+        
+        // One acceptance takes the bidder to 90%, and the holdout is then settled at the offer price
+        assert_eq!(fixture.client.accept_buyout(&seller, &property_id), 480);
+        assert_eq!(fixture.client.get_buyout(&property_id).unwrap().shares_acquired, 40);
+        fixture.client.squeeze_out(&property_id);
+        
+        assert!(fixture.client.get_buyout(&property_id).is_none());
+        assert_eq!(fixture.client.get_ownership(&property_id, &bidder).shares, 100);
+        assert_eq!(fixture.client.get_ownership(&property_id, &holdout).shares, 0);
+        assert_eq!(fixture.token_balance(&seller), 1000 - 400 + 480);
+        assert_eq!(fixture.token_balance(&holdout), 1000 - 100 + 120);
+        assert_eq!(fixture.token_balance(&bidder), 2000 - 500 - 600);
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_deed_consolidates_transfers_and_re_fractionalizes() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let (owner, minority) = (fixture.buyer_with_tokens(2000), fixture.buyer_with_tokens(1000));
+        let successor = Address::generate(&fixture.env);
+        fixture.client.purchase_shares(&property_id, &99, &owner, &None);
+        fixture.client.purchase_shares(&property_id, &1, &minority, &None);
+        
+        // Only a holder of every share can consolidate
+        assert!(fixture.client.try_consolidate(&owner, &property_id).is_err());
+        fixture.client.transfer_shares(&property_id, &minority, &owner, &1, &None);
+        fixture.client.consolidate(&owner, &property_id);
+        
+        let deed = fixture.client.get_deed(&property_id).unwrap();
+        assert_eq!((deed.owner.clone(), deed.shares), (owner.clone(), 100));
+        assert_eq!(fixture.client.get_deeds(&owner), vec![&fixture.env, property_id]);
+        assert_eq!(fixture.client.get_ownership(&property_id, &owner).shares, 0);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 0);
+        assert!(fixture.client.get_property_status(&property_id) == PropertyStatus::Sold);
+        assert!(fixture.client.try_purchase_shares(&property_id, &1, &minority, &None).is_err());
+        
+        // The deed moves whole, to anyone but its current owner
+        assert!(fixture.client.try_transfer_deed(&property_id, &owner).is_err());
+        fixture.client.transfer_deed(&property_id, &successor);
+        assert_eq!(fixture.client.get_deed(&property_id).unwrap().owner, successor);
+        assert!(fixture.client.get_deeds(&owner).is_empty());
+        assert_eq!(fixture.client.get_deeds(&successor), vec![&fixture.env, property_id]);
+        
+        // Splitting it back credits the same shares to the deed owner
+        fixture.client.re_fractionalize(&property_id);
+        assert!(fixture.client.get_deed(&property_id).is_none());
+        assert!(fixture.client.get_deeds(&successor).is_empty());
+        assert_eq!(fixture.client.get_ownership(&property_id, &successor).shares, 100);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 100);
+        assert!(fixture.client.try_re_fractionalize(&property_id).is_err());
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_issue_shares_requires_an_approved_proposal_with_matching_terms() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let holder = fixture.buyer_with_tokens(5000);
+        fixture.client.purchase_shares(&property_id, &100, &holder, &None);
+        assert!(fixture.client.get_property_status(&property_id) == PropertyStatus::Sold);
+        assert!(fixture.client.try_issue_shares(&property_id, &50, &12).is_err());
+        
+        let proposal_id = fixture.client.create_proposal(&holder, &property_id, &ProposalAction::IssueShares(50, 12), &BytesN::from_array(&fixture.env, &[6u8; 32]));
+        fixture.client.vote(&holder, &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        assert_eq!(fixture.client.get_approved_issuance(&property_id).unwrap().extra_shares, 50);
+        assert!(fixture.client.try_issue_shares(&property_id, &60, &12).is_err());
+        assert!(fixture.client.try_issue_shares(&property_id, &50, &10).is_err());
+        
+        // The approved terms grow the supply, reprice the primary sale and announce the dilution
+        fixture.client.issue_shares(&property_id, &50, &12);
+        let (_, _, data) = fixture.env.events().all().last().unwrap();
+        let dilution = DilutionEvent::try_from_val(&fixture.env, &data).unwrap();
+        assert_eq!((dilution.extra_shares, dilution.price_per_share, dilution.previous_total_shares, dilution.total_shares), (50, 12, 100, 150));
+        let property = fixture.client.get_property(&property_id);
+        assert_eq!((property.total_shares, property.price_per_share), (150, 12));
+        assert_eq!(fixture.client.get_available_shares(&property_id), 50);
+        assert!(fixture.client.get_approved_issuance(&property_id).is_none());
+        assert!(fixture.client.try_issue_shares(&property_id, &50, &12).is_err());
+        
+        // The new shares sell at the new price
+        let buyer = fixture.buyer_with_tokens(1000);
+        fixture.client.purchase_shares(&property_id, &10, &buyer, &None);
+        assert_eq!(fixture.token_balance(&buyer), 1000 - 120);
+        fixture.assert_invariants(property_id);
+    });
+}
+
+#[test]
+fn test_split_property_allocates_units_pro_rata_or_as_planned() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let (first, second) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        fixture.client.purchase_shares(&property_id, &30, &first, &None);
+        fixture.client.purchase_shares(&property_id, &20, &second, &None);
+        
+        let unit = |title: &str, total_shares: u64, allocations: Map<Address, u64>| UnitSpec {
+            title: String::from_str(&fixture.env, title),
+            description: String::from_str(&fixture.env, "Unit"),
+            image_url: String::from_str(&fixture.env, "https://example.com/unit.png"),
+            total_shares,
+            price_per_share: 10,
+            allocations,
+        };
+        let units = vec![
+            &fixture.env,
+            unit("Unit A", 40, Map::new(&fixture.env)),
+            unit("Unit B", 10, Map::from_array(&fixture.env, [(first.clone(), 10)])),
+        ];
+        let units_hash: BytesN<32> = fixture.env.crypto().sha256(&units.clone().to_xdr(&fixture.env)).into();
+        
+        // The split needs co-owners' approval of exactly these units
+        assert!(fixture.client.try_split_property(&property_id, &units).is_err());
+        let proposal_id = fixture.client.create_proposal(&first, &property_id, &ProposalAction::SplitProperty(units_hash), &BytesN::from_array(&fixture.env, &[7u8; 32]));
+        fixture.client.vote(&first, &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        assert!(fixture.client.try_split_property(&property_id, &vec![&fixture.env, unit("Unit A", 50, Map::new(&fixture.env))]).is_err());
+        
+        let children = fixture.client.split_property(&property_id, &units);
+        assert_eq!(children.len(), 2);
+        assert_eq!(fixture.client.get_child_properties(&property_id), children);
+        assert!(fixture.client.get_property_status(&property_id) == PropertyStatus::Archived);
+        assert_eq!(fixture.client.get_ownership(&property_id, &first).shares, 0);
+        assert_eq!(fixture.client.get_shares_sold(&property_id), 0);
+        
+        // Unit A follows the parent's 30/20/50 split including unsold shares; Unit B its allocation
+        let (unit_a, unit_b) = (children.get(0).unwrap(), children.get(1).unwrap());
+        for child in [unit_a, unit_b] {
+            assert_eq!(fixture.client.get_parent_property(&child), Some(property_id));
+            assert!(fixture.client.get_property(&child).is_verified);
+        }
+        assert_eq!(fixture.client.get_ownership(&unit_a, &first).shares, 12);
+        assert_eq!(fixture.client.get_ownership(&unit_a, &second).shares, 8);
+        assert_eq!(fixture.client.get_available_shares(&unit_a), 20);
+        assert_eq!(fixture.client.get_ownership(&unit_b, &first).shares, 10);
+        assert_eq!(fixture.client.get_ownership(&unit_b, &second).shares, 0);
+        assert_eq!(fixture.client.get_available_shares(&unit_b), 0);
+        fixture.assert_invariants(unit_a);
+        fixture.assert_invariants(unit_b);
+    });
+}
+
+#[test]
+fn test_merge_properties_weights_holders_by_appraised_value() {
+    for_each_id_mode(|fixture| {
+        let registrant = Address::generate(&fixture.env);
+        let parcel = |title: &str, shares: u64| {
+            let mut input = fixture.property_input(shares, 10);
+            input.title = String::from_str(&fixture.env, title);
+            let property_id = fixture.client.register_property(&registrant, &input);
+            fixture.client.verify_property(&fixture.admin, &property_id);
+            property_id
+        };
+        let (east, west) = (parcel("East Parcel", 100), parcel("West Parcel", 50));
+        let outsider = fixture.register_verified_property(100, 10);
+        let (first, second) = (fixture.buyer_with_tokens(1000), fixture.buyer_with_tokens(1000));
+        fixture.client.purchase_shares(&east, &50, &first, &None);
+        fixture.client.purchase_shares(&east, &10, &second, &None);
+        fixture.client.purchase_shares(&west, &25, &second, &None);
+        
+        let spec = MergeSpec {
+            title: String::from_str(&fixture.env, "Combined Parcel"),
+            location: String::from_str(&fixture.env, "Sample Location"),
+            description: String::from_str(&fixture.env, "Merged parcels"),
+            image_url: String::from_str(&fixture.env, "https://example.com/merged.png"),
+            total_shares: 200,
+            price_per_share: 10,
+            property_type: PropertyType::Residential,
+            appraised_values: vec![&fixture.env, 3000, 1000],
+        };
+        assert!(fixture.client.try_merge_properties(&vec![&fixture.env, east, outsider], &spec).is_err());
+        assert!(fixture.client.try_merge_properties(&vec![&fixture.env, east, east], &spec).is_err());
+        assert!(fixture.client.try_merge_properties(&vec![&fixture.env, east], &spec).is_err());
+        
+        // East carries three quarters of the new shares and West one quarter, each split by holdings:
+        // 200 * 3/4 * 50/100 = 75 for the first holder, 200 * 3/4 * 10/100 + 200 * 1/4 * 25/50 = 40 for the second
+        let merged = fixture.client.merge_properties(&vec![&fixture.env, east, west], &spec);
+        assert_eq!(fixture.client.get_constituent_properties(&merged), vec![&fixture.env, east, west]);
+        for constituent in [east, west] {
+            assert_eq!(fixture.client.get_merged_into(&constituent), Some(merged));
+            assert!(fixture.client.get_property_status(&constituent) == PropertyStatus::Archived);
+            assert_eq!(fixture.client.get_shares_sold(&constituent), 0);
+        }
+        assert_eq!(fixture.client.get_registrant(&merged), registrant);
+        assert!(fixture.client.get_property(&merged).is_verified);
+        assert_eq!(fixture.client.get_ownership(&merged, &first).shares, 75);
+        assert_eq!(fixture.client.get_ownership(&merged, &second).shares, 40);
+        assert_eq!(fixture.client.get_available_shares(&merged), 85);
+        fixture.assert_invariants(merged);
+    });
+}
+
+#[test]
+fn test_period_statement_nets_income_expenses_and_fees_per_month() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let holder = fixture.buyer_with_tokens(1000);
+        let stranger = Address::generate(&fixture.env);
+        fixture.client.purchase_shares(&property_id, &50, &holder, &None);
+        StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &1000);
+        fixture.client.set_management_fee(&property_id, &10, &500);
+        let doc_hash = BytesN::from_array(&fixture.env, &[12; 32]);
+        
+        assert!(fixture.client.try_record_expense(&stranger, &property_id, &symbol_short!("repair"), &40, &doc_hash).is_err());
+        assert_eq!(fixture.client.try_record_expense(&registrant, &property_id, &symbol_short!("repair"), &0, &doc_hash).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        
+        // The ledger starts at the epoch, so the first entries fall in January 1970
+        fixture.client.deposit_income(&registrant, &property_id, &200);
+        assert_eq!(fixture.client.record_expense(&registrant, &property_id, &symbol_short!("repair"), &40, &doc_hash), 0);
+        fixture.advance_time(31 * 86400);
+        assert_eq!(fixture.client.record_expense(&fixture.admin, &property_id, &symbol_short!("tax"), &25, &doc_hash), 1);
+        
+        assert_eq!(fixture.client.get_expense_count(&property_id), 2);
+        let expense = fixture.client.get_expense(&property_id, &1);
+        assert_eq!((expense.amount, expense.recorded_by, expense.recorded_at), (25, fixture.admin.clone(), 31 * 86400));
+        assert_eq!(fixture.client.get_accounting_periods(&property_id), vec![&fixture.env, 197001, 197002]);
+        
+        // A flat fee of 10 plus 5% of 200 is withheld from January's income
+        let january = fixture.client.get_period_statement(&property_id, &197001);
+        assert_eq!((january.income, january.expenses, january.management_fees, january.net_income), (200, 40, 20, 140));
+        let february = fixture.client.get_period_statement(&property_id, &197002);
+        assert_eq!((february.income, february.expenses, february.management_fees, february.net_income), (0, 25, 0, -25));
+        assert_eq!(fixture.client.get_period_statement(&property_id, &197003).net_income, 0);
+    });
+}
+
+#[test]
+fn test_budget_draws_are_capped_by_approval_and_reserve() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let holder = fixture.buyer_with_tokens(1000);
+        let funder = fixture.buyer_with_tokens(1000);
+        fixture.client.purchase_shares(&property_id, &60, &holder, &None);
+        assert!(fixture.client.try_create_proposal(&holder, &property_id, &ProposalAction::ApproveBudget(0), &BytesN::from_array(&fixture.env, &[13; 32])).is_err());
+        
+        let proposal_id = fixture.client.create_proposal(&holder, &property_id, &ProposalAction::ApproveBudget(300), &BytesN::from_array(&fixture.env, &[14; 32]));
+        assert!(fixture.client.try_draw_budget(&registrant, &proposal_id, &10).is_err());
+        fixture.client.vote(&holder, &proposal_id, &true);
+        fixture.advance_time(7 * 86400);
+        fixture.client.execute_proposal(&proposal_id);
+        let budget = fixture.client.get_budget(&proposal_id).unwrap();
+        assert_eq!((budget.property_id, budget.approved, budget.drawn), (property_id, 300, 0));
+        assert_eq!(fixture.client.get_budgets(&property_id), vec![&fixture.env, proposal_id]);
+        
+        // Draws need the reserve to cover them, and only the manager or admin may make them
+        assert!(fixture.client.try_draw_budget(&registrant, &proposal_id, &100).is_err());
+        fixture.client.fund_reserve(&funder, &property_id, &250);
+        assert_eq!(fixture.token_balance(&funder), 750);
+        assert!(fixture.client.try_draw_budget(&holder, &proposal_id, &100).is_err());
+        assert_eq!(fixture.client.try_draw_budget(&registrant, &proposal_id, &0).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        
+        let before = fixture.token_balance(&registrant);
+        fixture.client.draw_budget(&registrant, &proposal_id, &200);
+        assert_eq!(fixture.token_balance(&registrant) - before, 200);
+        assert_eq!(fixture.client.get_reserve_balance(&property_id), 50);
+        assert!(fixture.client.try_draw_budget(&registrant, &proposal_id, &60).is_err());
+        
+        // Once the reserve is topped up the draw is capped by what the budget has left
+        fixture.client.fund_reserve(&funder, &property_id, &250);
+        assert!(fixture.client.try_draw_budget(&fixture.admin, &proposal_id, &101).is_err());
+        fixture.client.draw_budget(&fixture.admin, &proposal_id, &100);
+        assert_eq!(fixture.client.get_budget(&proposal_id).unwrap().drawn, 300);
+        assert_eq!(fixture.client.get_reserve_balance(&property_id), 200);
+    });
+}
+
+#[test]
+fn test_lease_lifecycle_holds_and_returns_the_deposit() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let lessee = fixture.buyer_with_tokens(1000);
+        let terms = LeaseTerms {
+            lessee: Some(lessee.clone()),
+            lessee_ref: None,
+            rent: 50,
+            period_seconds: 30 * 86400,
+            deposit: 100,
+            doc_hash: BytesN::from_array(&fixture.env, &[15; 32]),
+            start: 0,
+            end: 360 * 86400,
+        };
+        assert!(fixture.client.try_create_lease(&lessee, &property_id, &terms).is_err());
+        assert!(fixture.client.try_create_lease(&registrant, &property_id, &LeaseTerms { lessee: None, ..terms.clone() }).is_err());
+        assert!(fixture.client.try_create_lease(&registrant, &property_id, &LeaseTerms { end: 0, ..terms.clone() }).is_err());
+        assert_eq!(fixture.client.try_create_lease(&registrant, &property_id, &LeaseTerms { rent: 0, ..terms.clone() }).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        
+        // The lessee signs by paying the deposit into the contract
+        let lease_id = fixture.client.create_lease(&registrant, &property_id, &terms);
+        assert!(fixture.client.get_lease(&lease_id).status == LeaseStatus::Pending);
+        fixture.client.activate_lease(&lease_id);
+        let lease = fixture.client.get_lease(&lease_id);
+        assert!(lease.status == LeaseStatus::Active);
+        assert_eq!((lease.deposit_held, lease.activated_at), (100, Some(0)));
+        assert_eq!(fixture.token_balance(&lessee), 900);
+        assert!(fixture.client.try_activate_lease(&lease_id).is_err());
+        
+        // Terminating early returns the deposit less what is withheld into the reserve
+        assert!(fixture.client.try_end_lease(&registrant, &lease_id, &101).is_err());
+        fixture.client.end_lease(&registrant, &lease_id, &30);
+        let lease = fixture.client.get_lease(&lease_id);
+        assert!(lease.status == LeaseStatus::Terminated);
+        assert_eq!((lease.deposit_held, lease.ended_at), (0, Some(0)));
+        assert_eq!(fixture.token_balance(&lessee), 970);
+        assert_eq!(fixture.client.get_reserve_balance(&property_id), 30);
+        assert!(fixture.client.try_end_lease(&registrant, &lease_id, &0).is_err());
+        
+        // A lease with an off-chain lessee is signed by the manager and expires at its end date
+        let offline_terms = LeaseTerms { lessee: None, lessee_ref: Some(String::from_str(&fixture.env, "TENANT-7")), ..terms };
+        let offline_id = fixture.client.create_lease(&registrant, &property_id, &offline_terms);
+        fixture.client.activate_lease(&offline_id);
+        fixture.advance_time(360 * 86400);
+        assert!(fixture.client.get_lease(&offline_id).status == LeaseStatus::Expired);
+        fixture.client.end_lease(&fixture.admin, &offline_id, &0);
+        assert!(fixture.client.get_lease(&offline_id).status == LeaseStatus::Expired);
+        assert_eq!(fixture.client.get_leases(&property_id), vec![&fixture.env, lease_id, offline_id]);
+    });
+}
+
+#[test]
+fn test_rent_payments_track_arrears_and_credit_holders() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let holder = fixture.buyer_with_tokens(1000);
+        let lessee = fixture.buyer_with_tokens(1000);
+        fixture.client.purchase_shares(&property_id, &50, &holder, &None);
+        let period = 30 * 86400;
+        let terms = LeaseTerms {
+            lessee: Some(lessee.clone()),
+            lessee_ref: None,
+            rent: 50,
+            period_seconds: period,
+            deposit: 0,
+            doc_hash: BytesN::from_array(&fixture.env, &[16; 32]),
+            start: 0,
+            end: 3 * period,
+        };
+        let lease_id = fixture.client.create_lease(&registrant, &property_id, &terms);
+        assert!(fixture.client.try_pay_rent(&lease_id, &50).is_err());
+        fixture.client.activate_lease(&lease_id);
+        
+        // Rent for the first period is due at the start of the lease
+        let status = fixture.client.get_rent_status(&lease_id);
+        assert_eq!((status.periods_due, status.rent_due, status.arrears, status.next_due_at), (1, 50, 50, Some(period)));
+        assert_eq!(fixture.client.try_pay_rent(&lease_id, &0).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        fixture.client.pay_rent(&lease_id, &30);
+        fixture.advance_time(period);
+        let status = fixture.client.get_rent_status(&lease_id);
+        assert_eq!((status.periods_due, status.rent_paid, status.arrears), (2, 30, 70));
+        
+        // Rent is credited to the holders like income, and can be prepaid up to the whole term
+        fixture.client.pay_rent(&lease_id, &90);
+        assert_eq!(fixture.token_balance(&lessee), 880);
+        assert_eq!(fixture.client.get_claimable(&property_id, &holder), 120);
+        assert_eq!(fixture.client.get_rent_status(&lease_id).arrears, 0);
+        assert!(fixture.client.try_pay_rent(&lease_id, &31).is_err());
+        
+        // After termination only the arrears that had fallen due can still be paid
+        fixture.client.end_lease(&registrant, &lease_id, &0);
+        fixture.advance_time(period);
+        let status = fixture.client.get_rent_status(&lease_id);
+        assert_eq!((status.periods_due, status.arrears, status.next_due_at), (2, 0, None));
+        assert!(fixture.client.try_pay_rent(&lease_id, &1).is_err());
+    });
+}
+
+#[test]
+fn test_occupancy_accumulates_vacancies_and_filters_listings() {
+    for_each_id_mode(|fixture| {
+        let (house, flat) = (fixture.register_property(100, 10), fixture.register_property(100, 10));
+        let registrant = fixture.client.get_registrant(&house);
+        assert!(fixture.client.get_occupancy(&house).is_none());
+        assert_eq!(fixture.client.get_vacancy_duration(&house), 0);
+        assert!(fixture.client.try_set_occupancy(&Address::generate(&fixture.env), &house, &Occupancy::Vacant).is_err());
+        
+        // Vacancies run until the property is reported occupied again, and their lengths add up
+        fixture.client.set_occupancy(&registrant, &house, &Occupancy::Vacant);
+        fixture.advance_time(100);
+        fixture.client.set_occupancy(&registrant, &house, &Occupancy::Vacant);
+        assert_eq!(fixture.client.get_vacancy_duration(&house), 100);
+        fixture.advance_time(50);
+        fixture.client.set_occupancy(&fixture.admin, &house, &Occupancy::PartiallyOccupied);
+        assert_eq!(fixture.client.get_vacancy_duration(&house), 0);
+        fixture.advance_time(200);
+        fixture.client.set_occupancy(&registrant, &house, &Occupancy::Vacant);
+        fixture.advance_time(25);
+        let record = fixture.client.get_occupancy(&house).unwrap();
+        assert!(record.occupancy == Occupancy::Vacant);
+        assert_eq!((record.vacant_since, record.vacant_seconds), (Some(350), 175));
+        assert_eq!(fixture.client.get_vacancy_duration(&house), 25);
+        
+        // Listings can be narrowed to occupancies; unreported properties never match
+        let filter = |occupancies: Vec<Occupancy>| PropertyFilter {
+            verified_only: false,
+            statuses: Vec::new(&fixture.env),
+            categories: Vec::new(&fixture.env),
+            occupancies,
+            min_price: None,
+            max_price: None,
+        };
+        let listed = |occupancies: Vec<Occupancy>| -> std::vec::Vec<u64> {
+            fixture.client.list_properties(&filter(occupancies), &None, &10).items.iter().map(|summary| summary.property_id).collect()
+        };
+        assert_eq!(listed(vec![&fixture.env, Occupancy::Vacant]), [house]);
+        assert!(listed(vec![&fixture.env, Occupancy::Occupied]).is_empty());
+        fixture.client.set_occupancy(&fixture.client.get_registrant(&flat), &flat, &Occupancy::Occupied);
+        assert_eq!(listed(vec![&fixture.env, Occupancy::Occupied, Occupancy::Vacant]), [house, flat]);
+        assert_eq!(listed(Vec::new(&fixture.env)), [house, flat]);
+    });
+}
+
+#[test]
+fn test_insurance_policies_warn_before_expiry_and_claims_pay_into_the_reserve() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_property(100, 10);
+        let registrant = fixture.client.get_registrant(&property_id);
+        let insurer = fixture.buyer_with_tokens(1000);
+        let day = 86400;
+        let terms = PolicyTerms {
+            insurer_ref: String::from_str(&fixture.env, "POLICY-42"),
+            coverage: 500,
+            premium: 20,
+            expires_at: 60 * day,
+            doc_hash: BytesN::from_array(&fixture.env, &[17; 32]),
+        };
+        assert!(fixture.client.try_add_insurance_policy(&insurer, &property_id, &terms).is_err());
+        assert!(fixture.client.try_add_insurance_policy(&registrant, &property_id, &PolicyTerms { expires_at: 0, ..terms.clone() }).is_err());
+        assert_eq!(fixture.client.try_add_insurance_policy(&registrant, &property_id, &PolicyTerms { coverage: 0, ..terms.clone() }).err(), Some(Ok(contract_error(Error::ZeroAmount))));
+        
+        // Policies warn within thirty days of expiry, until they are renewed
+        let policy_id = fixture.client.add_insurance_policy(&registrant, &property_id, &terms);
+        assert!(fixture.client.get_insurance_policy(&property_id, &policy_id).status == PolicyStatus::Active);
+        assert!(fixture.client.get_insurance_warnings(&property_id).is_empty());
+        fixture.advance_time(30 * day);
+        assert!(fixture.client.get_insurance_policy(&property_id, &policy_id).status == PolicyStatus::ExpiringSoon);
+        assert_eq!(fixture.client.get_insurance_warnings(&property_id), vec![&fixture.env, policy_id]);
+        fixture.client.update_insurance_policy(&fixture.admin, &property_id, &policy_id, &PolicyTerms { expires_at: 400 * day, ..terms.clone() });
+        assert!(fixture.client.get_insurance_warnings(&property_id).is_empty());
+        
+        // Claims are capped by coverage, settled once, and paid into the reserve up to the approval
+        let doc_hash = BytesN::from_array(&fixture.env, &[18; 32]);
+        assert!(fixture.client.try_file_insurance_claim(&registrant, &property_id, &policy_id, &501, &doc_hash).is_err());
+        let claim_id = fixture.client.file_insurance_claim(&registrant, &property_id, &policy_id, &300, &doc_hash);
+        assert!(fixture.client.try_pay_insurance_claim(&insurer, &property_id, &claim_id, &100).is_err());
+        assert!(fixture.client.try_settle_insurance_claim(&registrant, &property_id, &claim_id, &301).is_err());
+        fixture.client.settle_insurance_claim(&registrant, &property_id, &claim_id, &250);
+        assert!(fixture.client.try_settle_insurance_claim(&registrant, &property_id, &claim_id, &250).is_err());
+        fixture.client.pay_insurance_claim(&insurer, &property_id, &claim_id, &100);
+        assert!(fixture.client.get_insurance_claim(&property_id, &claim_id).status == ClaimStatus::Approved);
+        assert!(fixture.client.try_pay_insurance_claim(&insurer, &property_id, &claim_id, &151).is_err());
+        fixture.client.pay_insurance_claim(&insurer, &property_id, &claim_id, &150);
+        let claim = fixture.client.get_insurance_claim(&property_id, &claim_id);
+        assert!(claim.status == ClaimStatus::Paid);
+        assert_eq!((claim.filed_amount, claim.approved_amount, claim.paid_amount), (300, 250, 250));
+        assert_eq!(fixture.client.get_reserve_balance(&property_id), 250);
+        assert_eq!(fixture.token_balance(&insurer), 750);
+        
+        // A zero approval rejects a claim, and expired policies take no new claims
+        let rejected = fixture.client.file_insurance_claim(&registrant, &property_id, &policy_id, &50, &doc_hash);
+        fixture.client.settle_insurance_claim(&registrant, &property_id, &rejected, &0);
+        assert!(fixture.client.get_insurance_claim(&property_id, &rejected).status == ClaimStatus::Rejected);
+        assert_eq!(fixture.client.get_insurance_claim_count(&property_id), 2);
+        fixture.advance_time(400 * day);
+        assert!(fixture.client.get_insurance_policy(&property_id, &policy_id).status == PolicyStatus::Expired);
+        assert!(fixture.client.try_file_insurance_claim(&registrant, &property_id, &policy_id, &50, &doc_hash).is_err());
+    });
+}
+
+#[test]
+fn test_proposals_do_not_advance_property_ids() {
+    let fixture = TestFixture::new();
+    let first = fixture.register_verified_property(1000, 10);
+    let buyer = fixture.buyer_with_tokens(5000);
+    fixture.client.purchase_shares(&first, &100, &buyer, &None);
+    
+    let proposal_id = fixture.client.create_proposal(&buyer, &first, &ProposalAction::Other, &BytesN::from_array(&fixture.env, &[1u8; 32]));
+    let second = fixture.register_verified_property(500, 20);
+    
+    assert_eq!(proposal_id, 1);
+    assert_eq!(second, first + 1);
+    assert_eq!(fixture.client.get_property(&first).total_shares, 1000);
+    assert_eq!(fixture.client.get_property(&second).total_shares, 500);
+    assert_eq!(fixture.client.get_proposal(&proposal_id).property_id, first);
+    let filter = PropertyFilter {
+        verified_only: false,
+        statuses: Vec::new(&fixture.env),
+        categories: Vec::new(&fixture.env),
+        occupancies: Vec::new(&fixture.env),
+        min_price: None,
+        max_price: None,
+    };
+    assert_eq!(fixture.client.list_properties(&filter, &None, &10).items.len(), 2);
+}
+
+#[test]
+fn test_prices_above_u64_max() {
+    let fixture = TestFixture::new();
+    let price = u64::MAX as Money + 1;
+    let property_id = fixture.register_verified_property(1000, price);
+    let buyer = fixture.buyer_with_tokens(3 * price as i128);
+    
+    fixture.client.purchase_shares(&property_id, &2, &buyer, &None);
+    
+    assert_eq!(fixture.token_balance(&buyer), price as i128);
+    let day = fixture.env.ledger().timestamp() / 86400;
+    assert_eq!(fixture.client.get_daily_volume(&property_id, &day, &1).get(0).unwrap().value, 2 * price);
+    assert_eq!(fixture.client.get_global_daily_volume(&day, &1).get(0).unwrap().value, 2 * price);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_sale_phase_price_above_u64_max() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_property(1000, 10);
+    let now = fixture.env.ledger().timestamp();
+    let price = u64::MAX as Money * 4;
+    let phase = SalePhase { start_time: now, end_time: now + 86400, price_per_share: price, max_shares: 100, requires_whitelist: false };
+    fixture.client.configure_sale_phases(&property_id, &vec![&fixture.env, phase]);
+    fixture.client.verify_property(&fixture.admin, &property_id);
+    let buyer = fixture.buyer_with_tokens(price as i128);
+    
+    fixture.client.purchase_shares(&property_id, &1, &buyer, &None);
+    
+    assert_eq!(fixture.token_balance(&buyer), 0);
+    assert_eq!(fixture.client.get_active_phase(&property_id).unwrap().price_per_share, price);
+}
+
+#[test]
+fn test_purchase_cost_overflow_is_rejected() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, Money::MAX / 2);
+    let buyer = fixture.buyer_with_tokens(1000);
+    
+    assert_eq!(fixture.client.try_purchase_shares(&property_id, &3, &buyer, &None), Err(Ok(contract_error(Error::AmountOverflow))));
+    assert_eq!(fixture.client.get_shares_sold(&property_id), 0);
+}
+
+#[test]
+fn test_bundle_purchase_charges_bundle_price_and_redeems() {
+    let fixture = TestFixture::new();
+    let first = fixture.register_verified_property(100, 10);
+    let second = fixture.register_verified_property(50, 20);
+    let creator = Address::generate(&fixture.env);
+    let bundle_id = fixture.client.create_bundle(&creator, &vec![&fixture.env, (first, 2), (second, 1)], &50);
+    let buyer = fixture.buyer_with_tokens(1000);
+    
+    fixture.client.purchase_bundle_shares(&bundle_id, &buyer, &3);
+    
+    // 3 x (2 x 10 + 1 x 20) goes to the registrants and the 3 x 10 markup to the creator
+    assert_eq!(fixture.token_balance(&buyer), 850);
+    assert_eq!(fixture.token_balance(&creator), 30);
+    assert_eq!(fixture.token_balance(&fixture.client.get_registrant(&first)), 60);
+    assert_eq!(fixture.client.get_bundle_balance(&bundle_id, &buyer), 3);
+    assert_eq!(fixture.client.get_ownership(&first, &fixture.contract_id).shares, 6);
+    
+    fixture.client.redeem_bundle_shares(&bundle_id, &buyer, &2);
+    
+    assert_eq!(fixture.client.get_bundle_balance(&bundle_id, &buyer), 1);
+    assert_eq!(fixture.client.get_ownership(&first, &buyer).shares, 4);
+    assert_eq!(fixture.client.get_ownership(&second, &buyer).shares, 2);
+    assert_eq!(fixture.client.get_ownership(&first, &fixture.contract_id).shares, 2);
+    fixture.assert_invariants(first);
+    fixture.assert_invariants(second);
+}
+
+#[test]
+fn test_bundle_price_below_underlying_cost_is_rejected() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let creator = Address::generate(&fixture.env);
+    let bundle_id = fixture.client.create_bundle(&creator, &vec![&fixture.env, (property_id, 2)], &15);
+    let buyer = fixture.buyer_with_tokens(1000);
+    
+    assert!(fixture.client.try_purchase_bundle_shares(&bundle_id, &buyer, &1).is_err());
+    assert_eq!(fixture.token_balance(&buyer), 1000);
+    assert_eq!(fixture.client.get_shares_sold(&property_id), 0);
+}
+
+#[test]
+fn test_bundle_underlying_sells_out() {
+    let fixture = TestFixture::new();
+    let scarce = fixture.register_verified_property(10, 10);
+    let plentiful = fixture.register_verified_property(1000, 10);
+    let creator = Address::generate(&fixture.env);
+    let bundle_id = fixture.client.create_bundle(&creator, &vec![&fixture.env, (plentiful, 5), (scarce, 2)], &70);
+    let buyer = fixture.buyer_with_tokens(10_000);
+    assert_eq!(fixture.client.get_bundle_available(&bundle_id), 5);
+    
+    fixture.client.purchase_bundle_shares(&bundle_id, &buyer, &3);
+    let other = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&scarce, &3, &other, &None);
+    
+    // Only one share of the scarce property is left, so not even one bundle share can be bought
+    assert_eq!(fixture.client.get_bundle_available(&bundle_id), 0);
+    assert!(fixture.client.try_purchase_bundle_shares(&bundle_id, &buyer, &1).is_err());
+    assert_eq!(fixture.client.get_ownership(&plentiful, &fixture.contract_id).shares, 15);
+    fixture.assert_invariants(plentiful);
+}
+
+#[test]
+fn test_bundle_custody_earns_no_income() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    let holder = fixture.buyer_with_tokens(1000);
+    fixture.client.purchase_shares(&property_id, &10, &holder, &None);
+    let creator = Address::generate(&fixture.env);
+    let bundle_id = fixture.client.create_bundle(&creator, &vec![&fixture.env, (property_id, 10)], &100);
+    let bundle_buyer = fixture.buyer_with_tokens(1000);
+    fixture.client.purchase_bundle_shares(&bundle_id, &bundle_buyer, &3);
+    
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &1000);
+    fixture.client.deposit_income(&registrant, &property_id, &1000);
+    
+    assert_eq!(fixture.client.get_claimable(&property_id, &holder), 1000);
+    assert_eq!(fixture.client.get_claimable(&property_id, &fixture.contract_id), 0);
+    assert_eq!(fixture.client.claim_dividends(&property_id, &holder), 1000);
+    
+    // Redeemed shares only earn income deposited after they leave custody
+    fixture.client.redeem_bundle_shares(&bundle_id, &bundle_buyer, &3);
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &400);
+    fixture.client.deposit_income(&registrant, &property_id, &400);
+    assert_eq!(fixture.client.get_claimable(&property_id, &holder), 100);
+    assert_eq!(fixture.client.get_claimable(&property_id, &bundle_buyer), 300);
+}
+
+#[test]
+fn test_scheduled_transfer_escrows_without_contract_position() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let sender = fixture.buyer_with_tokens(1000);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.approve_investor(&fixture.admin, &sender, &1, &symbol_short!("US"));
+    fixture.client.approve_investor(&fixture.admin, &recipient, &1, &symbol_short!("US"));
+    fixture.client.set_kyc_required(&true);
+    fixture.client.purchase_shares(&property_id, &10, &sender, &None);
+    let owners_before = fixture.client.get_property_stats().total_owners;
+    
+    let release_time = fixture.env.ledger().timestamp() + 86400;
+    let transfer_id = fixture.client.schedule_transfer(&sender, &property_id, &recipient, &6, &release_time);
+    
+    // The shares stay with the sender but cannot be moved, and the contract holds nothing
+    assert_eq!(fixture.client.get_ownership(&property_id, &sender).shares, 10);
+    assert_eq!(fixture.client.get_ownership(&property_id, &fixture.contract_id).shares, 0);
+    assert_eq!(fixture.client.get_property_stats().total_owners, owners_before);
+    assert!(fixture.client.try_transfer_shares(&property_id, &sender, &recipient, &5, &None).is_err());
+    assert!(fixture.client.try_claim_scheduled_transfer(&transfer_id).is_err());
+    
+    fixture.advance_time(86400);
+    fixture.client.claim_scheduled_transfer(&transfer_id);
+    
+    assert_eq!(fixture.client.get_ownership(&property_id, &sender).shares, 4);
+    assert_eq!(fixture.client.get_ownership(&property_id, &recipient).shares, 6);
+    assert_eq!(fixture.client.get_listed_shares(&property_id, &sender), 0);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_cancelled_scheduled_transfer_unlocks_shares() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let sender = fixture.buyer_with_tokens(1000);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &10, &sender, &None);
+    let release_time = fixture.env.ledger().timestamp() + 86400;
+    let transfer_id = fixture.client.schedule_transfer(&sender, &property_id, &recipient, &10, &release_time);
+    
+    fixture.client.cancel_scheduled_transfer(&transfer_id);
+    
+    assert!(fixture.client.try_get_scheduled_transfer(&transfer_id).is_err());
+    fixture.client.transfer_shares(&property_id, &sender, &recipient, &10, &None);
+    assert_eq!(fixture.client.get_ownership(&property_id, &recipient).shares, 10);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_migration_keeps_lockup_lots() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    fixture.client.set_lockup_period(&property_id, &86400);
+    let old = fixture.buyer_with_tokens(1000);
+    let new = Address::generate(&fixture.env);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &10, &old, &None);
+    
+    fixture.client.migrate_account(&old, &new, &0, &10);
+    
+    assert_eq!(fixture.client.get_locked_shares(&property_id, &old), 0);
+    assert_eq!(fixture.client.get_locked_shares(&property_id, &new), 10);
+    assert!(fixture.client.try_transfer_shares(&property_id, &new, &recipient, &1, &None).is_err());
+    fixture.advance_time(86400);
+    fixture.client.transfer_shares(&property_id, &new, &recipient, &10, &None);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_migration_keeps_unvested_shares_locked() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let old = Address::generate(&fixture.env);
+    let new = Address::generate(&fixture.env);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.grant_vested_shares(&property_id, &old, &40, &0, &(100 * 86400));
+    fixture.advance_time(25 * 86400);
+    
+    fixture.client.migrate_account(&old, &new, &0, &10);
+    
+    assert!(fixture.client.get_vesting_schedules(&property_id, &old).is_empty());
+    assert_eq!(fixture.client.get_unvested_shares(&property_id, &new), 30);
+    assert!(fixture.client.try_transfer_shares(&property_id, &new, &recipient, &11, &None).is_err());
+    fixture.client.transfer_shares(&property_id, &new, &recipient, &10, &None);
+    fixture.assert_invariants(property_id);
+}
+
+#[test]
+fn test_migration_moves_class_only_holdings() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    let class_id = fixture.client.create_share_class(&property_id, &symbol_short!("PREF"), &50, &20, &false);
+    let old = fixture.buyer_with_tokens(1000);
+    let new = Address::generate(&fixture.env);
+    fixture.client.purchase_class_shares(&old, &property_id, &class_id, &5);
+    StellarAssetClient::new(&fixture.env, &fixture.token).mint(&registrant, &500);
+    fixture.client.deposit_class_income(&registrant, &property_id, &class_id, &500);
+    
+    fixture.client.migrate_account(&old, &new, &0, &10);
+    
+    assert_eq!(fixture.client.get_class_balance(&property_id, &class_id, &old), 0);
+    assert_eq!(fixture.client.get_class_balance(&property_id, &class_id, &new), 5);
+    assert!(fixture.client.try_claim_class_dividends(&property_id, &class_id, &old).is_err());
+    assert_eq!(fixture.client.claim_class_dividends(&property_id, &class_id, &new), 500);
+    assert_eq!(fixture.client.get_user_properties(&new), vec![&fixture.env, property_id]);
+}
+
+#[test]
+fn test_manager_change_settles_fees_to_outgoing_manager() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(100, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    let first_manager = fixture.buyer_with_tokens(1000);
+    let second_manager = fixture.buyer_with_tokens(1000);
+    let holder = fixture.buyer_with_tokens(1000);
+    fixture.client.purchase_shares(&property_id, &10, &holder, &None);
+    fixture.client.set_management_fee(&property_id, &0, &1000);
+    fixture.client.set_property_manager(&property_id, &first_manager);
+    
+    fixture.client.deposit_income(&first_manager, &property_id, &500);
+    assert_eq!(fixture.client.get_accrued_management_fee(&property_id), 50);
+    fixture.client.set_property_manager(&property_id, &second_manager);
+    
+    assert_eq!(fixture.token_balance(&first_manager), 550);
+    assert_eq!(fixture.client.get_accrued_management_fee(&property_id), 0);
+    assert!(fixture.client.try_claim_management_fee(&property_id).is_err());
+    fixture.client.deposit_income(&second_manager, &property_id, &200);
+    assert_eq!(fixture.client.claim_management_fee(&property_id), 20);
+    assert_eq!(fixture.token_balance(&second_manager), 820);
+    assert_eq!(fixture.token_balance(&registrant), 100);
+}
+
+#[test]
+fn test_keeper_rewards_come_from_fees_up_to_daily_cap() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let bidder = fixture.buyer_with_tokens(10_000);
+    let keeper = Address::generate(&fixture.env);
+    fixture.client.set_platform_fee(&1000);
+    fixture.client.purchase_shares(&property_id, &100, &holder, &None);
+    let fees = fixture.client.get_fee_balance();
+    assert!(fees > 0);
+    fixture.client.set_keeper_rate(&symbol_short!("offer_exp"), &5, &0);
+    fixture.client.set_keeper_daily_cap(&8);
+    
+    let expires_at = fixture.env.ledger().timestamp() + 100;
+    let live = fixture.client.make_offer(&bidder, &property_id, &10, &10, &expires_at);
+    let first = fixture.client.make_offer(&bidder, &property_id, &10, &10, &expires_at);
+    let second = fixture.client.make_offer(&bidder, &property_id, &10, &10, &expires_at);
+    let third = fixture.client.make_offer(&bidder, &property_id, &10, &10, &expires_at);
+    
+    // The buyer withdrawing a live offer is not maintenance
+    fixture.client.cancel_offer(&live, &Some(keeper.clone()));
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 0);
+    
+    fixture.advance_time(100);
+    fixture.client.cancel_offer(&first, &Some(keeper.clone()));
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 5);
+    fixture.client.cancel_offer(&second, &Some(keeper.clone()));
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 8);
+    fixture.client.cancel_offer(&third, &Some(keeper.clone()));
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 8);
+    assert_eq!(fixture.client.get_fee_balance(), fees - 8);
+    assert_eq!(fixture.token_balance(&bidder), 10_000);
+    
+    assert_eq!(fixture.client.claim_keeper_rewards(&keeper), 8);
+    assert_eq!(fixture.token_balance(&keeper), 8);
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 0);
+    assert!(fixture.client.try_claim_keeper_rewards(&keeper).is_err());
+}
+
+#[test]
+fn test_keeper_rewards_stop_at_fee_balance() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let seller = fixture.buyer_with_tokens(10_000);
+    let bidder = fixture.buyer_with_tokens(10_000);
+    let keeper = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &100, &seller, &None);
+    fixture.client.set_keeper_rate(&symbol_short!("auction"), &5, &0);
+    fixture.client.set_keeper_daily_cap(&1000);
+    
+    let auction_id = fixture.client.create_auction(&seller, &property_id, &10, &50, &100);
+    fixture.client.place_bid(&bidder, &auction_id, &60);
+    fixture.advance_time(100);
+    assert_eq!(fixture.client.settle_auction(&auction_id, &Some(keeper.clone())), Some(bidder.clone()));
+    
+    // No platform fees were collected, so there is nothing to pay the keeper from
+    assert_eq!(fixture.client.get_fee_balance(), 0);
+    assert_eq!(fixture.client.get_keeper_balance(&keeper), 0);
+    assert_eq!(fixture.client.get_ownership(&property_id, &bidder).shares, 10);
+}
+
+#[test]
+fn test_user_records_live_in_persistent_storage() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let spender = Address::generate(&fixture.env);
+    fixture.client.approve_investor(&fixture.admin, &holder, &1, &symbol_short!("US"));
+    fixture.client.purchase_shares(&property_id, &100, &holder, &None);
+    fixture.client.approve(&holder, &property_id, &spender, &10);
+    let expires_at = fixture.env.ledger().timestamp() + 100;
+    let offer_id = fixture.client.make_offer(&holder, &property_id, &10, &10, &expires_at);
+    
+    fixture.env.as_contract(&fixture.contract_id, || {
+        let persistent = fixture.env.storage().persistent();
+        let instance = fixture.env.storage().instance();
+        let investor = KycRegistry::Investor(holder.clone());
+        let allowance = AllowanceRegistry::Allowance(property_id, holder.clone(), spender.clone());
+        let offer = MarketRegistry::Offer(offer_id);
+        let holders = SupplyRegistry::Holders(property_id);
+        let total = HoldingsRegistry::TotalShares(holder.clone());
+        assert!(persistent.has(&investor) && !instance.has(&investor));
+        assert!(persistent.has(&allowance) && !instance.has(&allowance));
+        assert!(persistent.has(&offer) && !instance.has(&offer));
+        assert!(persistent.has(&holders) && !instance.has(&holders));
+        assert!(persistent.has(&total) && !instance.has(&total));
+    });
+    
+    fixture.client.approve(&holder, &property_id, &spender, &0);
+    assert_eq!(fixture.client.get_allowance(&property_id, &holder, &spender), 0);
+    fixture.client.cancel_offer(&offer_id, &None);
+    assert!(fixture.client.try_get_offer(&offer_id).is_err());
+}
+
+#[test]
+fn test_booked_shares_cannot_be_rebooked_or_moved() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let other = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_id, &50, &holder, &None);
+    fixture.client.configure_redemption(&property_id, &10, &30);
+    let day = 86_400;
+    let start = fixture.env.ledger().timestamp() + day;
+    
+    fixture.client.redeem_usage(&property_id, &holder, &5, &start);
+    assert_eq!(fixture.client.get_booked_shares(&property_id, &holder), 50);
+    
+    // The same shares cannot cover a second stay, nor be handed to someone else to book with
+    assert!(fixture.client.try_redeem_usage(&property_id, &holder, &1, &(start + 10 * day)).is_err());
+    assert!(fixture.client.try_transfer_shares(&property_id, &holder, &other, &10, &None).is_err());
+    assert!(fixture.client.try_redeem_usage(&property_id, &other, &1, &(start + 10 * day)).is_err());
+}
+
+#[test]
+fn test_ended_bookings_are_pruned_and_release_shares() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let other = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_id, &50, &holder, &None);
+    fixture.client.configure_redemption(&property_id, &10, &30);
+    let day = 86_400;
+    let start = fixture.env.ledger().timestamp() + day;
+    let first = fixture.client.redeem_usage(&property_id, &holder, &2, &start);
+    
+    fixture.advance_time(3 * day);
+    assert_eq!(fixture.client.get_booked_shares(&property_id, &holder), 0);
+    fixture.client.transfer_shares(&property_id, &holder, &other, &30, &None);
+    
+    let now = fixture.env.ledger().timestamp();
+    let second = fixture.client.redeem_usage(&property_id, &other, &3, &(now + day));
+    assert!(fixture.client.try_get_booking(&first).is_err());
+    let bookings = fixture.client.get_bookings(&property_id, &0, &u64::MAX);
+    assert_eq!(bookings.len(), 1);
+    assert_eq!(bookings.get(0).unwrap().booking_id, second);
+    assert_eq!(bookings.get(0).unwrap().shares, 30);
+}
+
+#[test]
+fn test_private_cap_table_views_are_gated() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let registrant = fixture.client.get_registrant(&property_id);
+    fixture.client.set_property_privacy(&property_id, &true);
+    let holder = fixture.buyer_with_tokens(10_000);
+    let stranger = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &100, &holder, &None);
+    
+    assert!(matches!(fixture.client.try_get_property_owners(&property_id, &stranger, &None, &10), Err(Ok(Error::NotAuthorizedToView))));
+    assert!(matches!(fixture.client.try_get_cap_table(&property_id, &stranger, &None, &10), Err(Ok(Error::NotAuthorizedToView))));
+    assert!(matches!(fixture.client.try_export_cap_table(&property_id, &stranger, &None, &10), Err(Ok(Error::NotAuthorizedToView))));
+    
+    let verifier = Address::generate(&fixture.env);
+    fixture.client.grant_role(&Role::Verifier, &verifier);
+    for viewer in [holder.clone(), registrant, verifier] {
+        assert_eq!(fixture.client.get_property_owners(&property_id, &viewer, &None, &10).items, vec![&fixture.env, holder.clone()]);
+        assert_eq!(fixture.client.get_cap_table(&property_id, &viewer, &None, &10).items, vec![&fixture.env, (holder.clone(), 100)]);
+        let records = fixture.client.export_cap_table(&property_id, &viewer, &None, &10).items;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records.get(0).unwrap().shares, 100);
+    }
+    
+    // Public cap tables stay open to anyone
+    let public_id = fixture.register_verified_property(1000, 10);
+    fixture.client.purchase_shares(&public_id, &5, &holder, &None);
+    assert_eq!(fixture.client.get_cap_table(&public_id, &stranger, &None, &10).items, vec![&fixture.env, (holder.clone(), 5)]);
+}
+
+#[test]
+fn test_batch_replays_with_same_key_change_state_once() {
+    let fixture = TestFixture::new();
+    let first_id = fixture.register_verified_property(1000, 10);
+    let second_id = fixture.register_verified_property(1000, 10);
+    let buyer = fixture.buyer_with_tokens(10_000);
+    let recipient = Address::generate(&fixture.env);
+    let key = Some(BytesN::from_array(&fixture.env, &[7; 32]));
+    
+    let purchases = vec![&fixture.env, (first_id, 10), (second_id, 20)];
+    fixture.client.purchase_shares_batch(&buyer, &purchases, &key);
+    assert_eq!(
+        fixture.client.try_purchase_shares_batch(&buyer, &purchases, &key),
+        Err(Ok(contract_error(Error::DuplicateRequest)))
+    );
+    assert_eq!(fixture.client.get_ownership(&first_id, &buyer).shares, 10);
+    assert_eq!(fixture.client.get_ownership(&second_id, &buyer).shares, 20);
+    let record = fixture.client.get_request_record(&buyer, &key.clone().unwrap()).unwrap();
+    assert_eq!((record.property_id, record.shares, record.resulting_balance), (second_id, 30, 20));
+    
+    let transfer_key = Some(BytesN::from_array(&fixture.env, &[8; 32]));
+    let transfers = vec![&fixture.env, (first_id, recipient.clone(), 4), (second_id, recipient.clone(), 5)];
+    fixture.client.transfer_shares_batch(&buyer, &transfers, &transfer_key);
+    assert_eq!(
+        fixture.client.try_transfer_shares_batch(&buyer, &transfers, &transfer_key),
+        Err(Ok(contract_error(Error::DuplicateRequest)))
+    );
+    assert_eq!(fixture.client.get_ownership(&first_id, &recipient).shares, 4);
+    assert_eq!(fixture.client.get_ownership(&second_id, &recipient).shares, 5);
+}
+
+#[test]
+fn test_fill_replays_with_same_key_change_state_once() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let seller = fixture.buyer_with_tokens(10_000);
+    let buyer = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_id, &100, &seller, &None);
+    let order_id = fixture.client.create_sell_order(&seller, &property_id, &50, &12);
+    let key = Some(BytesN::from_array(&fixture.env, &[1; 32]));
+    
+    fixture.client.fill_sell_order(&buyer, &order_id, &10, &key);
+    assert_eq!(
+        fixture.client.try_fill_sell_order(&buyer, &order_id, &10, &key),
+        Err(Ok(contract_error(Error::DuplicateRequest)))
+    );
+    assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 10);
+    assert_eq!(fixture.client.get_sell_order(&order_id).shares, 40);
+    
+    let expires_at = fixture.env.ledger().timestamp() + 100;
+    let offer_id = fixture.client.make_offer(&buyer, &property_id, &20, &11, &expires_at);
+    let accept_key = Some(BytesN::from_array(&fixture.env, &[2; 32]));
+    fixture.client.accept_offer(&seller, &offer_id, &5, &accept_key);
+    assert_eq!(
+        fixture.client.try_accept_offer(&seller, &offer_id, &5, &accept_key),
+        Err(Ok(contract_error(Error::DuplicateRequest)))
+    );
+    assert_eq!(fixture.client.get_ownership(&property_id, &buyer).shares, 15);
+    assert_eq!(fixture.client.get_offer(&offer_id).shares, 15);
+    let record = fixture.client.get_request_record(&seller, &accept_key.clone().unwrap()).unwrap();
+    assert_eq!(record.resulting_balance, 85);
+}
+
+#[test]
+fn test_verification_checklist_depends_on_property_type() {
+    let fixture = TestFixture::new();
+    let env = &fixture.env;
+    let title = symbol_short!("title");
+    let insurance = symbol_short!("insure");
+    fixture.client.set_verification_checklist(&PropertyType::Residential, &vec![env, title.clone()]);
+    fixture.client.set_verification_checklist(&PropertyType::Commercial, &vec![env, title.clone(), insurance.clone()]);
+    
+    let registrant = Address::generate(env);
+    let mut input = fixture.property_input(1000, 10);
+    input.property_type = PropertyType::Commercial;
+    let commercial_id = fixture.client.register_property(&registrant, &input);
+    let residential_id = fixture.register_property(1000, 10);
+    let evidence = String::from_str(env, "ipfs://evidence");
+    
+    // Residential properties have no insurance requirement to complete
+    assert!(fixture.client.try_complete_requirement(&fixture.admin, &residential_id, &insurance, &evidence).is_err());
+    fixture.client.complete_requirement(&fixture.admin, &residential_id, &title, &evidence);
+    fixture.client.verify_property(&fixture.admin, &residential_id);
+    
+    fixture.client.complete_requirement(&fixture.admin, &commercial_id, &title, &evidence);
+    assert!(fixture.client.try_verify_property(&fixture.admin, &commercial_id).is_err());
+    assert_eq!(fixture.client.get_verification_progress(&commercial_id).len(), 2);
+    fixture.client.complete_requirement(&fixture.admin, &commercial_id, &insurance, &evidence);
+    fixture.client.verify_property(&fixture.admin, &commercial_id);
+    assert!(fixture.client.get_property(&commercial_id).is_verified);
+}
+
+#[test]
+fn test_backfill_restores_holder_list() {
+    let fixture = TestFixture::new();
+    let property_id = fixture.register_verified_property(1000, 10);
+    let first = fixture.buyer_with_tokens(10_000);
+    let second = fixture.buyer_with_tokens(10_000);
+    let stranger = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&property_id, &100, &first, &None);
+    fixture.client.purchase_shares(&property_id, &50, &second, &None);
+    
+    // Deployments from before the holder list have ownership records but no list
+    fixture.env.as_contract(&fixture.contract_id, || {
+        fixture.env.storage().persistent().remove(&SupplyRegistry::Holders(property_id));
+    });
+    assert_eq!(fixture.client.check_invariants(&property_id), vec![&fixture.env, symbol_short!("holders")]);
+    
+    let candidates = vec![&fixture.env, first.clone(), stranger.clone(), first.clone()];
+    assert_eq!(fixture.client.backfill_holders(&property_id, &candidates), 1);
+    assert_eq!(fixture.client.backfill_holders(&property_id, &vec![&fixture.env, second.clone(), first.clone()]), 1);
+    
+    fixture.assert_invariants(property_id);
+    assert_eq!(fixture.client.get_cap_table(&property_id, &stranger, &None, &10).items, vec![&fixture.env, (first, 100), (second, 50)]);
+}
+
+#[test]
+fn test_value_stats_follow_register_verify_reprice_delist() {
+    let fixture = TestFixture::new();
+    let caps = || {
+        let value_stats = fixture.client.get_value_stats();
+        (value_stats.total_market_cap, value_stats.verified_market_cap)
+    };
+    let other = fixture.register_property(500, 4);
+    assert_eq!(caps(), (2_000, 0));
+    
+    let property_id = fixture.register_property(1000, 10);
+    assert_eq!(caps(), (12_000, 0));
+    
+    fixture.client.verify_property(&fixture.admin, &property_id);
+    assert_eq!(caps(), (12_000, 10_000));
+    
+    // Holders reprice the primary sale through governance
+    let holder = fixture.buyer_with_tokens(1000);
+    fixture.client.purchase_shares(&property_id, &10, &holder, &None);
+    let proposal_id = fixture.client.create_proposal(&holder, &property_id, &ProposalAction::SetSalePrice(15), &BytesN::from_array(&fixture.env, &[2u8; 32]));
+    fixture.client.vote(&holder, &proposal_id, &true);
+    fixture.advance_time(7 * 86400);
+    fixture.client.execute_proposal(&proposal_id);
+    assert_eq!(fixture.client.get_property(&property_id).price_per_share, 15);
+    assert_eq!(caps(), (17_000, 15_000));
+    
+    fixture.client.delist_property(&fixture.admin, &property_id);
+    assert_eq!(caps(), (2_000, 0));
+    fixture.client.delist_property(&fixture.admin, &other);
+    assert_eq!(caps(), (0, 0));
+    fixture.assert_stats_invariants();
+}
+
+#[test]
+fn test_property_pause_is_scoped_and_still_allows_claims() {
+    let fixture = TestFixture::new();
+    let registrant = fixture.buyer_with_tokens(10_000);
+    let paused = fixture.client.register_property(&registrant, &fixture.property_input(1000, 10));
+    let other = fixture.client.register_property(&registrant, &fixture.property_input(1000, 10));
+    fixture.client.verify_property(&fixture.admin, &paused);
+    fixture.client.verify_property(&fixture.admin, &other);
+    let holder = fixture.buyer_with_tokens(100_000);
+    let recipient = Address::generate(&fixture.env);
+    fixture.client.purchase_shares(&paused, &100, &holder, &None);
+    fixture.client.purchase_shares(&other, &100, &holder, &None);
+    fixture.client.deposit_income(&registrant, &paused, &1000);
+    
+    fixture.client.pause_property(&fixture.admin, &paused);
+    let expires_at = fixture.env.ledger().timestamp() + 100;
+    let property_paused = Some(Ok(contract_error(Error::PropertyPaused)));
+    assert_eq!(fixture.client.try_purchase_shares(&paused, &1, &holder, &None).err(), property_paused);
+    assert_eq!(fixture.client.try_transfer_shares(&paused, &holder, &recipient, &1, &None).err(), property_paused);
+    assert_eq!(fixture.client.try_create_sell_order(&holder, &paused, &1, &10).err(), property_paused);
+    assert_eq!(fixture.client.try_make_offer(&holder, &paused, &1, &10, &expires_at).err(), property_paused);
+    assert_eq!(fixture.client.try_deposit_income(&registrant, &paused, &1000).err(), property_paused);
+    
+    // Income deposited before the pause can still be claimed
+    let before = fixture.token_balance(&holder);
+    let claimed = fixture.client.claim_dividends(&paused, &holder);
+    assert!(claimed > 0);
+    assert_eq!(fixture.token_balance(&holder), before + claimed as i128);
+    
+    // The other property trades as usual
+    fixture.client.purchase_shares(&other, &1, &holder, &None);
+    fixture.client.transfer_shares(&other, &holder, &recipient, &1, &None);
+    fixture.client.create_sell_order(&holder, &other, &1, &10);
+    fixture.client.make_offer(&holder, &other, &1, &10, &expires_at);
+    fixture.client.deposit_income(&registrant, &other, &1000);
+    
+    // The global pause stacks on top: lifting it leaves the property pause in place
+    fixture.client.pause(&fixture.admin);
+    assert_eq!(fixture.client.try_transfer_shares(&other, &holder, &recipient, &1, &None).err(), Some(Ok(contract_error(Error::ContractPaused))));
+    fixture.client.unpause(&fixture.admin);
+    fixture.client.transfer_shares(&other, &holder, &recipient, &1, &None);
+    assert_eq!(fixture.client.try_transfer_shares(&paused, &holder, &recipient, &1, &None).err(), property_paused);
+    
+    fixture.client.unpause_property(&fixture.admin, &paused);
+    fixture.client.transfer_shares(&paused, &holder, &recipient, &1, &None);
+}
+
+#[test]
+fn test_swap_moves_both_legs_or_reverts_on_wallet_cap() {
+    let fixture = TestFixture::new();
+    let registrant = Address::generate(&fixture.env);
+    let property_a = fixture.register_verified_property(1000, 10);
+    let property_b = fixture.client.register_property(&registrant, &fixture.property_input(1000, 10));
+    fixture.client.verify_property(&fixture.admin, &property_b);
+    let a = fixture.buyer_with_tokens(10_000);
+    let b = fixture.buyer_with_tokens(10_000);
+    fixture.client.purchase_shares(&property_a, &100, &a, &None);
+    fixture.client.purchase_shares(&property_b, &100, &b, &None);
+    
+    fixture.client.swap_shares(&a, &property_a, &10, &b, &property_b, &20);
+    let transferred = Symbol::new(&fixture.env, "shares_transferred");
+    let legs: std::vec::Vec<u64> = fixture.env.events().all().iter()
+        .filter(|(contract, topics, _)| *contract == fixture.contract_id && topics.get(0).is_some_and(|topic| Symbol::try_from_val(&fixture.env, &topic) == Ok(transferred.clone())))
+        .map(|(_, topics, _)| u64::try_from_val(&fixture.env, &topics.get(1).unwrap()).unwrap())
+        .collect();
+    assert_eq!(legs, [property_a, property_b]);
+    assert_eq!(fixture.client.get_ownership(&property_a, &b).shares, 10);
+    assert_eq!(fixture.client.get_ownership(&property_b, &a).shares, 20);
+    
+    // The second leg would push A past property B's wallet cap, so the first leg reverts too
+    fixture.client.set_purchase_limits(&property_b, &0, &25);
+    assert_eq!(fixture.client.try_swap_shares(&a, &property_a, &10, &b, &property_b, &10).err(), Some(Ok(contract_error(Error::WalletCapExceeded))));
+    assert_eq!(fixture.client.get_ownership(&property_a, &a).shares, 90);
+    assert_eq!(fixture.client.get_ownership(&property_a, &b).shares, 10);
+    assert_eq!(fixture.client.get_ownership(&property_b, &a).shares, 20);
+    assert_eq!(fixture.client.get_ownership(&property_b, &b).shares, 80);
+    
+    fixture.client.swap_shares(&a, &property_a, &10, &b, &property_b, &5);
+    assert_eq!(fixture.client.get_ownership(&property_b, &a).shares, 25);
+}
+
+#[test]
+fn test_rejections_are_counted_per_error_code() {
+    for_each_id_mode(|fixture| {
+        let verified = fixture.register_verified_property(100, 10);
+        let unverified = fixture.register_property(100, 10);
+        let holder = fixture.buyer_with_tokens(10_000);
+        let other = Address::generate(&fixture.env);
+        fixture.client.purchase_shares(&verified, &10, &holder, &None);
+        let purchase = |property_id: u64, shares: u64| RejectionCheck::Purchase(PurchaseIntent { property_id, buyer: holder.clone(), shares, locked_price: None });
+        
+        assert_eq!(fixture.client.record_rejection(&purchase(unverified, 1)), Some(Error::PropertyNotVerified as u32));
+        assert_eq!(fixture.client.record_rejection(&purchase(verified, 91)), Some(Error::SupplyExceeded as u32));
+        assert_eq!(fixture.client.record_rejection(&purchase(verified, 200)), Some(Error::SupplyExceeded as u32));
+        assert_eq!(fixture.client.record_rejection(&RejectionCheck::Transfer(verified, holder.clone(), other.clone(), 11)), Some(Error::InsufficientShares as u32));
+        
+        // Checks that would succeed, and reverted calls themselves, count nothing
+        assert_eq!(fixture.client.record_rejection(&purchase(verified, 90)), None);
+        assert!(fixture.client.try_purchase_shares(&unverified, &1, &holder, &None).is_err());
+        
+        let counters = fixture.client.get_error_counters();
+        assert_eq!(counters.len(), 3);
+        assert_eq!(counters.get(Error::PropertyNotVerified as u32), Some(1));
+        assert_eq!(counters.get(Error::SupplyExceeded as u32), Some(2));
+        assert_eq!(counters.get(Error::InsufficientShares as u32), Some(1));
+        
+        fixture.client.reset_error_counters();
+        assert!(fixture.client.get_error_counters().is_empty());
+    });
+}
+
+#[test]
+fn test_class_purchases_settle_like_common_purchases() {
+    for_each_id_mode(|fixture| {
+        // Escrowed properties hold class proceeds for milestones too
+        let registrant = Address::generate(&fixture.env);
+        let mut input = fixture.property_input(100, 10);
+        input.milestone_escrow = true;
+        let escrowed = fixture.client.register_property(&registrant, &input);
+        fixture.client.verify_property(&fixture.admin, &escrowed);
+        let class_id = fixture.client.create_share_class(&escrowed, &symbol_short!("PREF"), &50, &20, &false);
+        let buyer = fixture.buyer_with_tokens(1000);
+        fixture.client.purchase_class_shares(&buyer, &escrowed, &class_id, &5);
+        assert_eq!(fixture.client.get_escrow_balance(&escrowed), 100);
+        assert_eq!(fixture.token_balance(&registrant), 0);
+        
+        // Crowdfunded properties hold them until the target is met, and refund them if it is missed
+        let mut input = fixture.property_input(100, 10);
+        input.title = String::from_str(&fixture.env, "Crowdfunded Property");
+        input.funding_deadline = Some(fixture.env.ledger().timestamp() + 1000);
+        let crowdfunded = fixture.client.register_property(&registrant, &input);
+        fixture.client.verify_property(&fixture.admin, &crowdfunded);
+        let class_id = fixture.client.create_share_class(&crowdfunded, &symbol_short!("PREF"), &50, &20, &false);
+        fixture.client.purchase_class_shares(&buyer, &crowdfunded, &class_id, &5);
+        assert_eq!(fixture.client.get_funds_raised(&crowdfunded), 100);
+        assert_eq!(fixture.token_balance(&registrant), 0);
+        
+        fixture.advance_time(1000);
+        assert_eq!(fixture.client.refund(&crowdfunded, &buyer), 100);
+        assert_eq!(fixture.client.get_class_balance(&crowdfunded, &class_id, &buyer), 0);
+        assert_eq!(fixture.client.get_class_shares_sold(&crowdfunded, &class_id), 0);
+        assert_eq!(fixture.token_balance(&buyer), 1000 - 100);
+    });
+}
+
+#[test]
+fn test_holder_export_and_price_history_pages_use_their_own_cursors() {
+    for_each_id_mode(|fixture| {
+        let property_id = fixture.register_verified_property(100, 10);
+        let buyers: std::vec::Vec<Address> = (1..=5u64).map(|shares| {
+            let buyer = fixture.buyer_with_tokens(1000);
+            fixture.client.purchase_shares(&property_id, &shares, &buyer, &None);
+            buyer
+        }).collect();
+        let viewer = Address::generate(&fixture.env);
+        
+        // Holders and exported records come in order of first acquisition, trades newest first
+        let (mut holders, mut exported, mut trades) = (std::vec::Vec::new(), std::vec::Vec::new(), std::vec::Vec::new());
+        let mut cursor = None;
+        loop {
+            let page = fixture.client.get_property_holders(&property_id, &viewer, &cursor, &2);
+            holders.extend(page.items.iter());
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        loop {
+            let page = fixture.client.export_cap_table(&property_id, &viewer, &cursor, &2);
+            exported.extend(page.items.iter().map(|ownership| ownership.owner));
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        loop {
+            let page = fixture.client.get_price_history(&property_id, &cursor, &2);
+            assert_eq!(page.total, Some(5));
+            trades.extend(page.items.iter().map(|point| point.shares));
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        let mut cap_table = std::vec::Vec::new();
+        loop {
+            let page = fixture.client.get_cap_table(&property_id, &viewer, &cursor, &2);
+            cap_table.extend(page.items.iter());
+            cursor = page.next_cursor;
+            if cursor.is_none() { break; }
+        }
+        let expected: std::vec::Vec<(Address, u64)> = buyers.iter().cloned().zip(1..=5u64).collect();
+        assert_eq!(holders, expected);
+        assert_eq!(cap_table, expected);
+        assert_eq!(exported, buyers);
+        assert_eq!(trades, [5, 4, 3, 2, 1]);
+        
+        // None of these cursors is accepted by another endpoint
+        let holders_cursor = fixture.client.get_property_holders(&property_id, &viewer, &None, &2).next_cursor;
+        let export_cursor = fixture.client.export_cap_table(&property_id, &viewer, &None, &2).next_cursor;
+        let history_cursor = fixture.client.get_price_history(&property_id, &None, &2).next_cursor;
+        assert!(matches!(fixture.client.try_export_cap_table(&property_id, &viewer, &holders_cursor, &2), Err(Ok(Error::InvalidCursor))));
+        assert!(matches!(fixture.client.try_get_property_holders(&property_id, &viewer, &export_cursor, &2), Err(Ok(Error::InvalidCursor))));
+        assert!(matches!(fixture.client.try_get_property_holders(&property_id, &viewer, &history_cursor, &2), Err(Ok(Error::InvalidCursor))));
+        assert!(matches!(fixture.client.try_get_cap_table(&property_id, &viewer, &holders_cursor, &2), Err(Ok(Error::InvalidCursor))));
+        assert!(matches!(fixture.client.try_get_property_owners(&property_id, &viewer, &export_cursor, &2), Err(Ok(Error::InvalidCursor))));
+        assert_eq!(fixture.client.try_get_price_history(&property_id, &holders_cursor, &2).err(), Some(Ok(contract_error(Error::InvalidCursor))));
+    });
+}
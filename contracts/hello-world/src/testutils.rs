@@ -0,0 +1,151 @@
+// Shared scaffolding for integration tests against the RealEstateNFT contract.
+// Enabled for this crate's own tests and for downstream crates via the `testutils` feature.
+use soroban_sdk::testutils::storage::{Instance as _, Persistent as _, Temporary as _};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, BytesN, Env, Map, String, Val};
+
+use crate::{IdMode, Money, PropertyInput, PropertyType, RealEstateNFT, RealEstateNFTClient, RoundingPolicy, SupplyRegistry};
+
+// Struct bundling an initialized contract with its admin and a mock payment token
+pub struct TestFixture<'a> {
+    pub env: Env,
+    pub contract_id: Address,
+    pub client: RealEstateNFTClient<'a>,
+    pub admin: Address,
+    pub token: Address,
+}
+
+impl<'a> TestFixture<'a> {
+    // Create an env with all auths mocked, register and initialize the contract
+    pub fn new() -> Self {
+        Self::initialized(false)
+    }
+    
+    // Create a fixture whose contract starts in sandbox mode, so its state can be reset before go-live
+    pub fn sandbox() -> Self {
+        Self::initialized(true)
+    }
+    
+    // Shared constructor of new and sandbox
+    fn initialized(sandbox: bool) -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register(RealEstateNFT, ());
+        let client = RealEstateNFTClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        client.initialize(&admin, &token, &sandbox);
+        
+        TestFixture { env, contract_id, client, admin, token }
+    }
+    
+    // Create a fixture whose property IDs are assigned under the given mode, so a suite can
+    // run against both sequential and content-hash IDs
+    pub fn with_id_mode(mode: IdMode) -> Self {
+        let fixture = Self::new();
+        fixture.client.set_id_mode(&mode, &BytesN::from_array(&fixture.env, &[7u8; 32]));
+        fixture
+    }
+    
+    // Register an unverified sample property owned by a fresh registrant
+    pub fn register_property(&self, shares: u64, price: Money) -> u64 {
+        let registrant = Address::generate(&self.env);
+        self.client.register_property(&registrant, &self.property_input(shares, price))
+    }
+    
+    // Details of a sample property with no founder allocation
+    pub fn property_input(&self, shares: u64, price: Money) -> PropertyInput {
+        PropertyInput {
+            title: String::from_str(&self.env, "Sample Property"),
+            location: String::from_str(&self.env, "Sample Location"),
+            description: String::from_str(&self.env, "Sample description"),
+            total_shares: shares,
+            price_per_share: price,
+            image_url: String::from_str(&self.env, "https://example.com/property.png"),
+            founder_shares: 0,
+            founder_lockup_seconds: 0,
+            is_private: false,
+            tenant: None,
+            rounding_policy: RoundingPolicy::Down,
+            unsold_shares_recipient: None,
+            milestone_escrow: false,
+            property_type: PropertyType::Residential,
+            royalty_bps: 0,
+            funding_deadline: None,
+        }
+    }
+    
+    // Register a sample property and verify it so it is open for purchases
+    pub fn register_verified_property(&self, shares: u64, price: Money) -> u64 {
+        let property_id = self.register_property(shares, price);
+        self.client.verify_property(&self.admin, &property_id);
+        property_id
+    }
+    
+    // Create a fresh address funded with the mock payment token
+    pub fn buyer_with_tokens(&self, amount: i128) -> Address {
+        let buyer = Address::generate(&self.env);
+        StellarAssetClient::new(&self.env, &self.token).mint(&buyer, &amount);
+        buyer
+    }
+    
+    // Token balance of an address in the mock payment token
+    pub fn token_balance(&self, address: &Address) -> i128 {
+        TokenClient::new(&self.env, &self.token).balance(address)
+    }
+    
+    // Move the ledger clock forward
+    pub fn advance_time(&self, secs: u64) {
+        self.env.ledger().with_mut(|ledger| ledger.timestamp += secs);
+    }
+    
+    // Assert the platform-wide counters are mutually consistent
+    pub fn assert_stats_invariants(&self) {
+        let stats = self.client.get_property_stats();
+        let value_stats = self.client.get_value_stats();
+        assert!(stats.verified_properties <= stats.total_properties);
+        assert!(value_stats.verified_market_cap <= value_stats.total_market_cap);
+    }
+    
+    // Assert that a property satisfies every protocol invariant
+    pub fn assert_invariants(&self, property_id: u64) {
+        let violations = self.client.check_invariants(&property_id);
+        assert!(violations.is_empty(), "invariants violated: {:?}", violations);
+    }
+    
+    // Test hook: overwrite a property's shares-sold counter to simulate corrupted state
+    pub fn corrupt_shares_sold(&self, property_id: u64, shares_sold: u64) {
+        self.env.as_contract(&self.contract_id, || {
+            self.env.storage().instance().set(&SupplyRegistry::SharesSold(property_id), &shares_sold);
+        });
+    }
+    
+    // Every instance, persistent and temporary entry of the contract, for asserting that a
+    // rejected call left storage untouched
+    pub fn storage_snapshot(&self) -> (Map<Val, Val>, Map<Val, Val>, Map<Val, Val>) {
+        self.env.as_contract(&self.contract_id, || {
+            let storage = self.env.storage();
+            (storage.instance().all(), storage.persistent().all(), storage.temporary().all())
+        })
+    }
+    
+    // Assert that the listed holders account for exactly the shares sold of a property
+    pub fn assert_supply_invariant(&self, property_id: u64, holders: &[Address]) {
+        let property = self.client.get_property(&property_id);
+        let shares_sold = self.client.get_shares_sold(&property_id);
+        let held: u64 = holders
+            .iter()
+            .map(|holder| self.client.get_ownership(&property_id, holder).shares)
+            .sum();
+        assert!(shares_sold <= property.total_shares);
+        assert_eq!(held, shares_sold);
+    }
+}
+
+impl Default for TestFixture<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}